@@ -0,0 +1,88 @@
+extern crate clap;
+extern crate soem;
+
+use clap::{App, Arg};
+use soem::*;
+
+fn sii_dump(file_name: &str) -> i32 {
+    let image = match std::fs::read(file_name) {
+        Err(ref err) => {
+            println!("Cannot read {}: {}", file_name, err);
+            return 1;
+        }
+        Ok(x) => x,
+    };
+
+    let sii = match Sii::parse(&image) {
+        Err(ref err) => {
+            println!("Cannot parse SII: {}", err);
+            return 1;
+        }
+        Ok(x) => x,
+    };
+
+    println!("Name: {}", sii.name().unwrap_or(""));
+    println!(
+        "Vendor: {:08x} Product: {:08x} Revision: {:08x} Serial: {:08x}",
+        sii.vendor_id(),
+        sii.product_code(),
+        sii.revision(),
+        sii.serial_number()
+    );
+    println!("Alias: {}", sii.alias());
+    let mbx = sii.standard_mailbox();
+    println!(
+        "Mailbox: rx {:04x}:{} tx {:04x}:{} protocols {:04x}",
+        mbx.receive_offset(),
+        mbx.receive_size(),
+        mbx.send_offset(),
+        mbx.send_size(),
+        sii.mailbox_protocols()
+    );
+    for (i, sm) in sii.sync_managers().iter().enumerate() {
+        println!(
+            "SM{}: {:04x}:{} control {:02x} {:?}",
+            i,
+            sm.start_addr(),
+            sm.length(),
+            sm.control(),
+            sm.sm_type()
+        );
+    }
+    for (i, fmmu) in sii.fmmu().iter().enumerate() {
+        println!("FMMU{}: {:?}", i, fmmu);
+    }
+    for (dir, pdos) in [("TxPDO", sii.tx_pdos()), ("RxPDO", sii.rx_pdos())].iter() {
+        for pdo in pdos.iter() {
+            println!(
+                "{} {:04x} SM{} {}",
+                dir,
+                pdo.index(),
+                pdo.sync_manager(),
+                sii.string(pdo.name_idx()).unwrap_or("")
+            );
+            for e in pdo.entries() {
+                println!(
+                    "  {:04x}:{:02x} {} bits {}",
+                    e.index(),
+                    e.subindex(),
+                    e.bit_len(),
+                    sii.string(e.name_idx()).unwrap_or("")
+                );
+            }
+        }
+    }
+
+    0
+}
+
+fn main() {
+    let matches = App::new("EtherCat SII dump")
+        .version("1.0")
+        .author("Matwey V. Kornilov <matwey.kornilov@gmail.com>")
+        .arg(Arg::with_name("file").required(true))
+        .get_matches();
+
+    let exit_code = sii_dump(matches.value_of("file").unwrap());
+    std::process::exit(exit_code);
+}
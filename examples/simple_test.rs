@@ -131,9 +131,9 @@ fn simple_test(iface_name: &str) -> i32 {
 
     for i in 1..10000 {
         c.send_processdata();
-        let wck = c.receive_processdata(2000);
+        let result = c.receive_processdata(2000);
 
-        if wck >= expected_wkc {
+        if result.is_ok() {
             print!("Processdata cycle {}, ", i);
             for s in c.slaves().iter() {
                 for x in s.inputs() {
@@ -141,12 +141,23 @@ fn simple_test(iface_name: &str) -> i32 {
                 }
                 print!(" ");
             }
-            println!("WKC {} T:{}", wck, c.dc_time());
+            println!("WKC {} T:{}", result.wkc(), c.dc_time());
+        } else {
+            println!("Processdata cycle {}, {}", i, result);
         }
 
         sleep(Duration::from_micros(5000));
     }
 
+    if let Some(stats) = c.group_stats(0) {
+        println!(
+            "{} cycles, {} WKC mismatches, {} lost frames",
+            stats.cycles(),
+            stats.wkc_mismatches(),
+            stats.lost_frames()
+        );
+    }
+
     println!("Request {} state for the slaves", EtherCatState::Init);
     c.set_state(EtherCatState::Init, 0);
     match c.write_state(0) {
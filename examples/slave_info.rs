@@ -8,7 +8,61 @@ use std::iter::Iterator;
 use std::mem::zeroed;
 use std::os::raw::c_int;
 
-fn slave_info(iface_name: &str) -> i32 {
+#[cfg(feature = "xml")]
+fn load_esi(esi_files: &[&str]) -> Result<Vec<Esi>, String> {
+    esi_files
+        .iter()
+        .map(|&path| {
+            let text = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+            Esi::parse(&text).map_err(|err| format!("{}: {}", path, err))
+        })
+        .collect()
+}
+
+#[cfg(feature = "xml")]
+fn print_esi(esi: &[Esi], slave: &Slave) {
+    let device = match esi.iter().find_map(|x| x.slave_device(slave)) {
+        Some(x) => x,
+        None => return,
+    };
+
+    println!(" Type: {} ({})", device.type_name(), device.name());
+    for pdo in device.default_pdos() {
+        println!(
+            "  PDO {:04x} {} [SM{}]",
+            pdo.index(),
+            pdo.name(),
+            pdo.sm().unwrap_or(0)
+        );
+        for entry in pdo.entries().iter().filter(|x| x.index() != 0) {
+            println!(
+                "   {:04x}:{:02x} {} bits {}",
+                entry.index(),
+                entry.subindex(),
+                entry.bit_len(),
+                device
+                    .object_name(entry.index(), entry.subindex())
+                    .unwrap_or_else(|| entry.name())
+            );
+        }
+    }
+}
+
+fn slave_info(iface_name: &str, esi_files: &[&str]) -> i32 {
+    #[cfg(feature = "xml")]
+    let esi = match load_esi(esi_files) {
+        Err(ref err) => {
+            println!("Cannot load ESI: {}", err);
+            return 1;
+        }
+        Ok(x) => x,
+    };
+    #[cfg(not(feature = "xml"))]
+    if !esi_files.is_empty() {
+        println!("ESI files require the xml feature");
+        return 1;
+    }
+
     let mut port: Port = Default::default();
     let mut slaves: [Slave; 8] = Default::default();
     let mut slavecount: c_int = Default::default();
@@ -86,6 +140,8 @@ fn slave_info(iface_name: &str) -> i32 {
     for (i, s) in c.slaves().iter().enumerate() {
         println!("Slave {}", i);
         println!("{}", s);
+        #[cfg(feature = "xml")]
+        print_esi(&esi, s);
     }
 
     0
@@ -96,8 +152,20 @@ fn main() {
         .version("1.0")
         .author("Matwey V. Kornilov <matwey.kornilov@gmail.com>")
         .arg(Arg::with_name("iface").required(true))
+        .arg(
+            Arg::with_name("esi")
+                .long("esi")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("ESI file used to describe the slaves"),
+        )
         .get_matches();
 
-    let exit_code = slave_info(matches.value_of("iface").unwrap());
+    let esi_files: Vec<&str> = matches
+        .values_of("esi")
+        .map(Iterator::collect)
+        .unwrap_or_default();
+    let exit_code = slave_info(matches.value_of("iface").unwrap(), &esi_files);
     std::process::exit(exit_code);
 }
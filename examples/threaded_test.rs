@@ -0,0 +1,134 @@
+extern crate clap;
+extern crate soem;
+
+use clap::{App, Arg};
+use soem::*;
+use std::default::Default;
+use std::mem::zeroed;
+use std::os::raw::c_int;
+use std::thread;
+use std::time::Duration;
+
+fn cycle(mut c: Context) -> i32 {
+    println!(
+        "Cycling on thread {:?}",
+        thread::current().name().unwrap_or("unnamed")
+    );
+
+    c.set_state(EtherCatState::Op, 0);
+    if let Err(ref err) = c.write_state(0) {
+        println!("Cannot set state for the slaves: {}", err);
+        return 1;
+    }
+
+    for _ in 0..40 {
+        c.send_processdata();
+        c.receive_processdata(2000);
+        if c.check_state(0, EtherCatState::Op, 50000) == EtherCatState::Op {
+            break;
+        }
+    }
+
+    for i in 1..1000 {
+        c.send_processdata();
+        let result = c.receive_processdata(2000);
+        println!("Processdata cycle {}, {} T:{}", i, result, c.dc_time());
+        thread::sleep(Duration::from_micros(5000));
+    }
+
+    c.set_state(EtherCatState::Init, 0);
+    if let Err(ref err) = c.write_state(0) {
+        println!("Cannot set state for the slaves: {}", err);
+        return 1;
+    }
+
+    0
+}
+
+fn threaded_test(iface_name: &str) -> i32 {
+    let mut port: Port = Default::default();
+    let mut slaves: [Slave; 8] = Default::default();
+    let mut slavecount: c_int = Default::default();
+    let mut groups: [Group; 2] = Default::default();
+    let mut esibuf: ESIBuf = Default::default();
+    let mut esimap: ESIMap = Default::default();
+    let mut elist: ERing = Default::default();
+    let mut idxstack: IdxStack = Default::default();
+    let mut ecaterror: Boolean = Default::default();
+    let mut dc_time: i64 = Default::default();
+    let mut sm_commtype: SMCommType = Default::default();
+    let mut pdo_assign: PDOAssign = Default::default();
+    let mut pdo_desc: PDODesc = Default::default();
+    let mut eep_sm: EEPROMSM = Default::default();
+    let mut eep_fmmu: EEPROMFMMU = Default::default();
+
+    let mut io_map: [u8; 4096] = unsafe { zeroed() };
+
+    let mut c = match Context::new(
+        iface_name,
+        &mut port,
+        &mut slaves,
+        &mut slavecount,
+        &mut groups,
+        &mut esibuf,
+        &mut esimap,
+        &mut elist,
+        &mut idxstack,
+        &mut ecaterror,
+        &mut dc_time,
+        &mut sm_commtype,
+        &mut pdo_assign,
+        &mut pdo_desc,
+        &mut eep_sm,
+        &mut eep_fmmu,
+    ) {
+        Err(ref err) => {
+            println!("Cannot create EtherCat context: {}", err);
+            return 1;
+        }
+        Ok(c) => c,
+    };
+
+    if let Err(ref err) = c.config_init(false) {
+        println!("Cannot configure EtherCat: {}", err);
+        return 1;
+    }
+
+    if let Err(ref err) = c.config_map_group(&mut io_map, 0) {
+        println!("Cannot configure group map: {}", err);
+        return 1;
+    }
+
+    if let Err(ref err) = c.config_dc() {
+        println!("Cannot configure DC: {}", err);
+        return 1;
+    }
+
+    println!("{} slaves found and configured.", c.slaves().len());
+
+    c.check_state(0, EtherCatState::SafeOp, 20000 * 3);
+
+    // The context borrows the buffers above, so it may only be moved to a thread
+    // which is joined before they go out of scope.
+    thread::scope(|s| {
+        thread::Builder::new()
+            .name("cyclic".to_owned())
+            .spawn_scoped(s, move || cycle(c))
+            .and_then(|handle| handle.join().map_err(|_| std::io::ErrorKind::Other.into()))
+            .unwrap_or_else(|err| {
+                println!("Cannot run cyclic thread: {}", err);
+                1
+            })
+    })
+}
+
+fn main() {
+    let matches = App::new("EtherCat threaded test")
+        .version("1.0")
+        .author("Matwey V. Kornilov <matwey.kornilov@gmail.com>")
+        .arg(Arg::with_name("iface").required(true))
+        .get_matches();
+
+    let exit_code = threaded_test(matches.value_of("iface").unwrap());
+    std::process::exit(exit_code);
+}
@@ -0,0 +1,454 @@
+use crate::{
+    error::{AoEError, ParseNetIdError},
+    Context, Mailbox, MailboxType,
+};
+use std::{fmt, os::raw::c_int, result, str::FromStr};
+
+/** Size of the AMS header following the mailbox header */
+const AMS_HEADER_SIZE: usize = 32;
+/** State flags of an ADS request */
+const AMS_REQUEST: u16 = 0x0004;
+/** State flags of an ADS response */
+const AMS_RESPONSE: u16 = 0x0005;
+
+/// AMS network identifier, e.g. `5.12.34.56.2.1`
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct AmsNetId([u8; 6]);
+
+impl AmsNetId {
+    pub const fn new(x: [u8; 6]) -> Self {
+        AmsNetId(x)
+    }
+    pub const fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl fmt::Display for AmsNetId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let x = &self.0;
+        write!(f, "{}.{}.{}.{}.{}.{}", x[0], x[1], x[2], x[3], x[4], x[5])
+    }
+}
+
+impl FromStr for AmsNetId {
+    type Err = ParseNetIdError;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let mut octets = [0u8; 6];
+        let mut parts = s.trim().split('.');
+        for x in octets.iter_mut() {
+            *x = parts
+                .next()
+                .and_then(|x| x.parse().ok())
+                .ok_or(ParseNetIdError)?;
+        }
+        match parts.next() {
+            None => Ok(AmsNetId(octets)),
+            Some(_) => Err(ParseNetIdError),
+        }
+    }
+}
+
+/// AMS address of an ADS device
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct AmsAddr {
+    net_id: AmsNetId,
+    port: u16,
+}
+
+impl AmsAddr {
+    pub const fn new(net_id: AmsNetId, port: u16) -> Self {
+        AmsAddr { net_id, port }
+    }
+    pub const fn net_id(&self) -> AmsNetId {
+        self.net_id
+    }
+    pub const fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl fmt::Display for AmsAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.net_id, self.port)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+enum AdsCommand {
+    Read = 2,
+    Write = 3,
+    ReadState = 4,
+    ReadWrite = 9,
+}
+
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u16)]
+pub enum AdsState {
+    Invalid = 0,
+    Idle = 1,
+    Reset = 2,
+    Init = 3,
+    Start = 4,
+    Run = 5,
+    Stop = 6,
+    SaveConfig = 7,
+    LoadConfig = 8,
+    PowerFailure = 9,
+    PowerGood = 10,
+    Error = 11,
+    Shutdown = 12,
+    Suspend = 13,
+    Resume = 14,
+    Config = 15,
+    Reconfig = 16,
+}
+
+/// Reply to the ADS ReadState request
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct AdsDeviceState {
+    ads_state: u16,
+    device_state: u16,
+}
+
+impl AdsDeviceState {
+    /// ADS state, `None` for values unknown to the crate
+    pub fn ads_state(&self) -> Option<AdsState> {
+        num::FromPrimitive::from_u16(self.ads_state)
+    }
+    pub const fn raw_ads_state(&self) -> u16 {
+        self.ads_state
+    }
+    /// Device specific state
+    pub const fn device_state(&self) -> u16 {
+        self.device_state
+    }
+}
+
+fn u32_at(x: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([x[offset], x[offset + 1], x[offset + 2], x[offset + 3]])
+}
+
+fn u16_at(x: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([x[offset], x[offset + 1]])
+}
+
+/// ADS client talking to the AoE server of a slave, see [`Context::aoe`]
+#[derive(Debug)]
+pub struct AoE<'c, 'a> {
+    context: &'c mut Context<'a>,
+    slave: u16,
+    target: AmsAddr,
+    source: AmsAddr,
+    timeout: c_int,
+}
+
+impl<'c, 'a> AoE<'c, 'a> {
+    pub const fn target(&self) -> AmsAddr {
+        self.target
+    }
+    pub const fn source(&self) -> AmsAddr {
+        self.source
+    }
+
+    fn encode(&self, command: AdsCommand, invoke_id: u32, data: &[u8]) -> Mailbox {
+        let mut payload = Vec::with_capacity(AMS_HEADER_SIZE + data.len());
+        payload.extend_from_slice(&self.target.net_id.0);
+        payload.extend_from_slice(&self.target.port.to_le_bytes());
+        payload.extend_from_slice(&self.source.net_id.0);
+        payload.extend_from_slice(&self.source.port.to_le_bytes());
+        payload.extend_from_slice(&(command as u16).to_le_bytes());
+        payload.extend_from_slice(&AMS_REQUEST.to_le_bytes());
+        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        payload.extend_from_slice(&0u32.to_le_bytes());
+        payload.extend_from_slice(&invoke_id.to_le_bytes());
+        payload.extend_from_slice(data);
+        Mailbox::new(MailboxType::AoE, &payload)
+    }
+
+    /// Data of the reply, `None` if it answers another request
+    fn decode(
+        mbx: &Mailbox,
+        command: AdsCommand,
+        invoke_id: u32,
+    ) -> result::Result<Option<Vec<u8>>, AoEError> {
+        if mbx.header().mailbox_type() != Some(MailboxType::AoE) {
+            return Err(AoEError::UnexpectedMailbox(mbx.header().raw_type()));
+        }
+
+        let reply = mbx.data();
+        if reply.len() < AMS_HEADER_SIZE {
+            return Err(AoEError::TooShort(reply.len()));
+        }
+        if u32_at(reply, 28) != invoke_id
+            || u16_at(reply, 16) != command as u16
+            || u16_at(reply, 18) != AMS_RESPONSE
+        {
+            return Ok(None);
+        }
+
+        match u32_at(reply, 24) {
+            0 => Ok(Some(reply[AMS_HEADER_SIZE..].to_vec())),
+            x => Err(AoEError::Ads(x)),
+        }
+    }
+
+    fn request(&mut self, command: AdsCommand, data: &[u8]) -> result::Result<Vec<u8>, AoEError> {
+        self.context.aoe_invoke_id = self.context.aoe_invoke_id.wrapping_add(1);
+        let invoke_id = self.context.aoe_invoke_id;
+
+        let mbx = self.encode(command, invoke_id, data);
+        self.context
+            .mbx_send(self.slave, &mbx, self.timeout)
+            .map_err(AoEError::MailboxError)?;
+
+        // Replies to requests which timed out earlier may still be queued in the slave
+        loop {
+            let mbx = self
+                .context
+                .mbx_receive(self.slave, self.timeout)
+                .map_err(AoEError::MailboxError)?;
+            if let Some(reply) = AoE::decode(&mbx, command, invoke_id)? {
+                return Ok(reply);
+            }
+        }
+    }
+
+    fn result(data: &[u8], size: usize) -> result::Result<(), AoEError> {
+        if data.len() < size {
+            return Err(AoEError::TooShort(data.len()));
+        }
+        match u32_at(data, 0) {
+            0 => Ok(()),
+            x => Err(AoEError::Ads(x)),
+        }
+    }
+
+    fn read_data(data: &[u8]) -> result::Result<Vec<u8>, AoEError> {
+        AoE::result(data, 8)?;
+        let length = u32_at(data, 4) as usize;
+        8usize
+            .checked_add(length)
+            .and_then(|end| data.get(8..end))
+            .map(<[u8]>::to_vec)
+            .ok_or(AoEError::TooShort(data.len()))
+    }
+
+    /// ADS Read of at most `length` bytes
+    pub fn read(
+        &mut self,
+        index_group: u32,
+        index_offset: u32,
+        length: u32,
+    ) -> result::Result<Vec<u8>, AoEError> {
+        let mut data = Vec::with_capacity(12);
+        data.extend_from_slice(&index_group.to_le_bytes());
+        data.extend_from_slice(&index_offset.to_le_bytes());
+        data.extend_from_slice(&length.to_le_bytes());
+
+        let reply = self.request(AdsCommand::Read, &data)?;
+        AoE::read_data(&reply)
+    }
+
+    /// ADS Write
+    pub fn write(
+        &mut self,
+        index_group: u32,
+        index_offset: u32,
+        value: &[u8],
+    ) -> result::Result<(), AoEError> {
+        let mut data = Vec::with_capacity(12 + value.len());
+        data.extend_from_slice(&index_group.to_le_bytes());
+        data.extend_from_slice(&index_offset.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        data.extend_from_slice(value);
+
+        let reply = self.request(AdsCommand::Write, &data)?;
+        AoE::result(&reply, 4)
+    }
+
+    /// ADS ReadState
+    pub fn read_state(&mut self) -> result::Result<AdsDeviceState, AoEError> {
+        let reply = self.request(AdsCommand::ReadState, &[])?;
+        AoE::result(&reply, 8)?;
+        Ok(AdsDeviceState {
+            ads_state: u16_at(&reply, 4),
+            device_state: u16_at(&reply, 6),
+        })
+    }
+
+    /// ADS ReadWrite, writes `value` and reads at most `length` bytes back
+    pub fn read_write(
+        &mut self,
+        index_group: u32,
+        index_offset: u32,
+        length: u32,
+        value: &[u8],
+    ) -> result::Result<Vec<u8>, AoEError> {
+        let mut data = Vec::with_capacity(16 + value.len());
+        data.extend_from_slice(&index_group.to_le_bytes());
+        data.extend_from_slice(&index_offset.to_le_bytes());
+        data.extend_from_slice(&length.to_le_bytes());
+        data.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        data.extend_from_slice(value);
+
+        let reply = self.request(AdsCommand::ReadWrite, &data)?;
+        AoE::read_data(&reply)
+    }
+}
+
+impl<'a> Context<'a> {
+    /// ADS client for the AoE server of the slave.
+    ///
+    /// Requests are addressed to `target` on behalf of `source`, each of them waits `timeout`
+    /// for the mailbox to be sent and for the reply to arrive.
+    pub fn aoe(
+        &mut self,
+        slave: u16,
+        target: AmsAddr,
+        source: AmsAddr,
+        timeout: c_int,
+    ) -> AoE<'_, 'a> {
+        AoE {
+            context: self,
+            slave,
+            target,
+            source,
+            timeout,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+
+    fn reply(command: u16, state: u16, error: u32, invoke_id: u32, data: &[u8]) -> Mailbox {
+        let mut payload = vec![0; 16];
+        payload.extend_from_slice(&command.to_le_bytes());
+        payload.extend_from_slice(&state.to_le_bytes());
+        payload.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        payload.extend_from_slice(&error.to_le_bytes());
+        payload.extend_from_slice(&invoke_id.to_le_bytes());
+        payload.extend_from_slice(data);
+        Mailbox::new(MailboxType::AoE, &payload)
+    }
+
+    #[test]
+    fn net_id_round_trip() {
+        let id: AmsNetId = "5.12.34.56.2.1".parse().unwrap();
+        assert_eq!(id.octets(), [5, 12, 34, 56, 2, 1]);
+        assert_eq!(id.to_string(), "5.12.34.56.2.1");
+        assert_eq!(
+            " 0.0.0.0.0.255 ".parse(),
+            Ok(AmsNetId::new([0, 0, 0, 0, 0, 255]))
+        );
+
+        let addr = AmsAddr::new(id, 851);
+        assert_eq!(addr.to_string(), "5.12.34.56.2.1:851");
+        assert_eq!((addr.net_id(), addr.port()), (id, 851));
+    }
+
+    #[test]
+    fn invalid_net_ids() {
+        for s in [
+            "",
+            "5.12.34.56.2",
+            "5.12.34.56.2.1.1",
+            "5.12.34.56.2.256",
+            "5.12.34.56.2.-1",
+            "5.12.34.56..1",
+            "5.12.34.56.2.1.",
+            "a.12.34.56.2.1",
+            "5:12:34:56:2:1",
+        ]
+        .iter()
+        {
+            assert_eq!(s.parse::<AmsNetId>(), Err(ParseNetIdError), "{}", s);
+        }
+    }
+
+    #[test]
+    fn request_header() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+        let target = AmsAddr::new(AmsNetId::new([5, 12, 34, 56, 2, 1]), 0x1234);
+        let source = AmsAddr::new(AmsNetId::new([192, 168, 1, 2, 1, 1]), 0x8000);
+        let aoe = c.aoe(1, target, source, 0);
+
+        let mbx = aoe.encode(AdsCommand::ReadWrite, 0x0102_0304, &[0xaa, 0xbb]);
+        assert_eq!(mbx.header().mailbox_type(), Some(MailboxType::AoE));
+        assert_eq!(mbx.header().length() as usize, AMS_HEADER_SIZE + 2);
+        assert_eq!(
+            mbx.data(),
+            [
+                5, 12, 34, 56, 2, 1, 0x34, 0x12, // target
+                192, 168, 1, 2, 1, 1, 0x00, 0x80, // source
+                0x09, 0x00, 0x04, 0x00, // command, state
+                0x02, 0x00, 0x00, 0x00, // length
+                0x00, 0x00, 0x00, 0x00, // error
+                0x04, 0x03, 0x02, 0x01, // invoke ID
+                0xaa, 0xbb,
+            ]
+        );
+    }
+
+    #[test]
+    fn reply_parsing() {
+        let ok = reply(2, AMS_RESPONSE, 0, 7, &[1, 2, 3]);
+        assert_eq!(
+            AoE::decode(&ok, AdsCommand::Read, 7),
+            Ok(Some(vec![1, 2, 3]))
+        );
+
+        let failed = reply(2, AMS_RESPONSE, 0x0702, 7, &[]);
+        assert_eq!(
+            AoE::decode(&failed, AdsCommand::Read, 7),
+            Err(AoEError::Ads(0x0702))
+        );
+
+        let coe = Mailbox::new(MailboxType::CoE, &[0; AMS_HEADER_SIZE]);
+        assert_eq!(
+            AoE::decode(&coe, AdsCommand::Read, 7),
+            Err(AoEError::UnexpectedMailbox(0x03))
+        );
+        let short = Mailbox::new(MailboxType::AoE, &[0; AMS_HEADER_SIZE - 1]);
+        assert_eq!(
+            AoE::decode(&short, AdsCommand::Read, 7),
+            Err(AoEError::TooShort(AMS_HEADER_SIZE - 1))
+        );
+    }
+
+    #[test]
+    fn replies_to_other_requests_are_skipped() {
+        let stale = [
+            // earlier invoke ID, also when it reports an error
+            reply(2, AMS_RESPONSE, 0, 6, &[1]),
+            reply(2, AMS_RESPONSE, 0x0702, 6, &[]),
+            // another command
+            reply(3, AMS_RESPONSE, 0, 7, &[1]),
+            // request instead of a response
+            reply(2, AMS_REQUEST, 0, 7, &[1]),
+        ];
+        for mbx in stale.iter() {
+            assert_eq!(AoE::decode(mbx, AdsCommand::Read, 7), Ok(None));
+        }
+    }
+
+    #[test]
+    fn reply_data() {
+        assert_eq!(AoE::result(&[0, 0, 0, 0], 4), Ok(()));
+        assert_eq!(AoE::result(&[6, 7, 0, 0], 4), Err(AoEError::Ads(0x0706)));
+        assert_eq!(AoE::result(&[0, 0, 0], 4), Err(AoEError::TooShort(3)));
+
+        let data = [0, 0, 0, 0, 2, 0, 0, 0, 0xaa, 0xbb, 0xcc];
+        assert_eq!(AoE::read_data(&data), Ok(vec![0xaa, 0xbb]));
+        let truncated = [0, 0, 0, 0, 4, 0, 0, 0, 0xaa];
+        assert_eq!(AoE::read_data(&truncated), Err(AoEError::TooShort(9)));
+        let huge = [0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(AoE::read_data(&huge), Err(AoEError::TooShort(8)));
+    }
+}
@@ -0,0 +1,485 @@
+use crate::{
+    error::{AsyncError, InitError},
+    Boolean, Context, ERing, ESIBuf, ESIMap, EtherCatState, Group, IdxStack, PDOAssign, PDODesc,
+    Port, ProcessDataResult, SMCommType, Slave, EEPROMFMMU, EEPROMSM,
+};
+use std::{
+    os::raw::c_int,
+    result, thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    mpsc,
+    mpsc::error::{TryRecvError, TrySendError},
+    oneshot,
+};
+
+type Job = Box<dyn FnOnce(&mut Context) + Send>;
+
+enum Command {
+    Run(Job),
+    MapGroup(u8, oneshot::Sender<result::Result<usize, AsyncError>>),
+    Cyclic(Cyclic),
+}
+
+/// Inputs of the slaves captured right after a process data exchange
+#[derive(Debug, Clone)]
+pub struct InputSnapshot {
+    cycle: u64,
+    result: ProcessDataResult,
+    dc_time: i64,
+    inputs: Vec<Vec<u8>>,
+}
+
+impl InputSnapshot {
+    pub const fn cycle(&self) -> u64 {
+        self.cycle
+    }
+    pub const fn result(&self) -> &ProcessDataResult {
+        &self.result
+    }
+    pub const fn dc_time(&self) -> i64 {
+        self.dc_time
+    }
+    /// Input image of every slave in the group, in ring order
+    pub fn inputs(&self) -> &[Vec<u8>] {
+        &self.inputs
+    }
+}
+
+/// Stream of input snapshots, process data exchange stops when it is dropped
+#[derive(Debug)]
+pub struct InputStream(mpsc::Receiver<InputSnapshot>);
+
+impl InputStream {
+    pub async fn next(&mut self) -> Option<InputSnapshot> {
+        self.0.recv().await
+    }
+}
+
+struct Cyclic {
+    group: u8,
+    period: Duration,
+    timeout: c_int,
+    next: Instant,
+    cycle: u64,
+    snapshots: mpsc::Sender<InputSnapshot>,
+}
+
+impl Cyclic {
+    /// Time left until the cycle is due, a late cycle restarts the schedule instead of
+    /// bursting to catch up
+    fn schedule(&mut self, now: Instant) -> Duration {
+        let wait = self.next.saturating_duration_since(now);
+        self.next += self.period;
+        if self.next < now {
+            self.next = now + self.period;
+        }
+        wait
+    }
+
+    fn run(&mut self, c: &mut Context) -> bool {
+        let wait = self.schedule(Instant::now());
+        if wait > Duration::from_secs(0) {
+            thread::sleep(wait);
+        }
+
+        c.send_processdata_group(self.group);
+        let result = match c.receive_processdata_group(self.group, self.timeout) {
+            Some(x) => x,
+            None => return false,
+        };
+        let snapshot = InputSnapshot {
+            cycle: self.cycle,
+            result,
+            dc_time: c.dc_time(),
+            inputs: group_inputs(c.slaves(), self.group),
+        };
+        self.cycle += 1;
+
+        // Slow consumers lose snapshots, the cycle is never held back.
+        !matches!(
+            self.snapshots.try_send(snapshot),
+            Err(TrySendError::Closed(_))
+        )
+    }
+}
+
+/// Inputs of the slaves in the group, group 0 stands for every slave as in SOEM
+fn group_inputs(slaves: &[Slave], group: u8) -> Vec<Vec<u8>> {
+    slaves
+        .iter()
+        .filter(|s| group == 0 || s.group() == group)
+        .map(|s| s.inputs().to_vec())
+        .collect()
+}
+
+fn worker(
+    iface_name: String,
+    max_slaves: usize,
+    max_groups: usize,
+    ready: oneshot::Sender<result::Result<(), InitError>>,
+    commands: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut port: Box<Port> = Default::default();
+    let mut slaves: Vec<Slave> = (0..=max_slaves).map(|_| Default::default()).collect();
+    let mut slavecount: c_int = Default::default();
+    let mut groups: Vec<Group> = (0..max_groups).map(|_| Default::default()).collect();
+    let mut esibuf: Box<ESIBuf> = Default::default();
+    let mut esimap: Box<ESIMap> = Default::default();
+    let mut elist: Box<ERing> = Default::default();
+    let mut idxstack: Box<IdxStack> = Default::default();
+    let mut ecaterror: Boolean = Default::default();
+    let mut dc_time: i64 = Default::default();
+    let mut sm_commtype: Box<SMCommType> = Default::default();
+    let mut pdo_assign: Box<PDOAssign> = Default::default();
+    let mut pdo_desc: Box<PDODesc> = Default::default();
+    let mut eep_sm: Box<EEPROMSM> = Default::default();
+    let mut eep_fmmu: Box<EEPROMFMMU> = Default::default();
+
+    let mut io_map_storage: Vec<Box<[u8; 4096]>> =
+        (0..max_groups).map(|_| Box::new([0; 4096])).collect();
+    let mut io_maps: Vec<Option<&mut [u8; 4096]>> =
+        io_map_storage.iter_mut().map(|x| Some(&mut **x)).collect();
+
+    let mut c = match Context::new(
+        &iface_name,
+        &mut port,
+        &mut slaves,
+        &mut slavecount,
+        &mut groups,
+        &mut esibuf,
+        &mut esimap,
+        &mut elist,
+        &mut idxstack,
+        &mut ecaterror,
+        &mut dc_time,
+        &mut sm_commtype,
+        &mut pdo_assign,
+        &mut pdo_desc,
+        &mut eep_sm,
+        &mut eep_fmmu,
+    ) {
+        Err(err) => {
+            let _ = ready.send(Err(err));
+            return;
+        }
+        Ok(c) => c,
+    };
+
+    if ready.send(Ok(())).is_err() {
+        return;
+    }
+
+    serve(&mut c, &mut io_maps, commands);
+}
+
+/// Serve the commands until every [`AsyncContext`] and the running [`InputStream`] are gone
+fn serve<'a>(
+    c: &mut Context<'a>,
+    io_maps: &mut [Option<&'a mut [u8; 4096]>],
+    mut commands: mpsc::UnboundedReceiver<Command>,
+) {
+    let mut cyclic: Option<Cyclic> = None;
+    let mut connected = true;
+    loop {
+        let command = match cyclic {
+            Some(ref mut cyc) => {
+                let command = if connected {
+                    match commands.try_recv() {
+                        Ok(command) => Some(command),
+                        Err(TryRecvError::Empty) => None,
+                        Err(TryRecvError::Disconnected) => {
+                            connected = false;
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+                match command {
+                    Some(command) => command,
+                    None => {
+                        if !cyc.run(c) {
+                            cyclic = None;
+                        }
+                        continue;
+                    }
+                }
+            }
+            None if connected => match commands.blocking_recv() {
+                Some(command) => command,
+                None => break,
+            },
+            None => break,
+        };
+
+        match command {
+            Command::Run(job) => job(c),
+            Command::MapGroup(group, reply) => {
+                let res = match io_maps.get_mut(group as usize).and_then(Option::take) {
+                    Some(io_map) => c.config_map_group(io_map, group).map_err(AsyncError::from),
+                    None => Err(AsyncError::GroupMapped(group)),
+                };
+                let _ = reply.send(res);
+            }
+            Command::Cyclic(cyc) => cyclic = Some(cyc),
+        }
+    }
+}
+
+/// EtherCat master driven from async code.
+///
+/// SOEM calls are blocking, so the context lives on a dedicated thread and every request is
+/// forwarded there. Requests are served in order, between process data cycles when the cyclic
+/// exchange is running.
+#[derive(Debug, Clone)]
+pub struct AsyncContext {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+impl AsyncContext {
+    pub async fn new(
+        iface_name: &str,
+        max_slaves: usize,
+        max_groups: usize,
+    ) -> result::Result<Self, AsyncError> {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let (ready, ready_receiver) = oneshot::channel();
+        let iface_name = iface_name.to_owned();
+
+        thread::Builder::new()
+            .name("soem".to_owned())
+            .spawn(move || worker(iface_name, max_slaves, max_groups, ready, receiver))
+            .map_err(|err| AsyncError::Init(InitError::IOError(err)))?;
+
+        ready_receiver
+            .await
+            .map_err(|_| AsyncError::Closed)?
+            .map_err(AsyncError::Init)?;
+
+        Ok(AsyncContext { commands })
+    }
+
+    /// Run a closure on the master thread
+    pub async fn run<F, R>(&self, f: F) -> result::Result<R, AsyncError>
+    where
+        F: FnOnce(&mut Context) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (reply, receiver) = oneshot::channel();
+        self.send(Command::Run(Box::new(move |c| {
+            let _ = reply.send(f(c));
+        })))?;
+        receiver.await.map_err(|_| AsyncError::Closed)
+    }
+
+    pub async fn config_init(&self, usetable: bool) -> result::Result<usize, AsyncError> {
+        self.run(move |c| c.config_init(usetable).map_err(AsyncError::from))
+            .await?
+    }
+
+    /// Map the group into the process data image owned by the master thread
+    pub async fn config_map_group(&self, group: u8) -> result::Result<usize, AsyncError> {
+        let (reply, receiver) = oneshot::channel();
+        self.send(Command::MapGroup(group, reply))?;
+        receiver.await.map_err(|_| AsyncError::Closed)?
+    }
+
+    pub async fn config_dc(&self) -> result::Result<bool, AsyncError> {
+        self.run(|c| c.config_dc().map_err(AsyncError::from))
+            .await?
+    }
+
+    pub async fn read_state(&self) -> result::Result<EtherCatState, AsyncError> {
+        self.run(|c| c.read_state()).await
+    }
+
+    pub async fn check_state(
+        &self,
+        slave: u16,
+        state: EtherCatState,
+        timeout: c_int,
+    ) -> result::Result<EtherCatState, AsyncError> {
+        self.run(move |c| c.check_state(slave, state, timeout))
+            .await
+    }
+
+    /// Request the state and wait until the slave reaches it or `timeout` expires
+    pub async fn request_state(
+        &self,
+        slave: u16,
+        state: EtherCatState,
+        timeout: c_int,
+    ) -> result::Result<EtherCatState, AsyncError> {
+        self.run(move |c| {
+            c.set_state(state, slave);
+            c.write_state(slave)?;
+            Ok(c.check_state(slave, state, timeout))
+        })
+        .await?
+    }
+
+    pub async fn write_sdo<T: num::PrimInt + Send + 'static>(
+        &self,
+        slave: u16,
+        index: u16,
+        subindex: u8,
+        value: T,
+        timeout: c_int,
+    ) -> result::Result<(), AsyncError> {
+        self.run(move |c| {
+            c.write_sdo(slave, index, subindex, &value, timeout)
+                .map_err(AsyncError::from)
+        })
+        .await?
+    }
+
+    pub async fn read_sdo<T: num::PrimInt + Send + 'static>(
+        &self,
+        slave: u16,
+        index: u16,
+        subindex: u8,
+        timeout: c_int,
+    ) -> result::Result<T, AsyncError> {
+        self.run(move |c| {
+            c.read_sdo(slave, index, subindex, timeout)
+                .map_err(AsyncError::from)
+        })
+        .await?
+    }
+
+    /// Start cyclic process data exchange of the group.
+    ///
+    /// At most `capacity` snapshots are buffered, newer ones are dropped until the stream
+    /// catches up. Starting another exchange replaces the running one.
+    pub async fn cyclic(
+        &self,
+        group: u8,
+        period: Duration,
+        timeout: c_int,
+        capacity: usize,
+    ) -> result::Result<InputStream, AsyncError> {
+        let (snapshots, receiver) = mpsc::channel(capacity);
+        self.send(Command::Cyclic(Cyclic {
+            group,
+            period,
+            timeout,
+            next: Instant::now(),
+            cycle: 0,
+            snapshots,
+        }))?;
+        Ok(InputStream(receiver))
+    }
+
+    fn send(&self, command: Command) -> result::Result<(), AsyncError> {
+        self.commands.send(command).map_err(|_| AsyncError::Closed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+    use std::ptr;
+
+    fn spawn_serve() -> (mpsc::UnboundedSender<Command>, thread::JoinHandle<()>) {
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let handle = thread::spawn(move || {
+            let mut buffers = MockBuffers::with_slaves(2, 2);
+            let mut c = buffers.context();
+            serve(&mut c, &mut [], receiver);
+        });
+        (commands, handle)
+    }
+
+    #[test]
+    fn serve_runs_jobs_in_order() {
+        let (commands, handle) = spawn_serve();
+
+        let (reply, replies) = std::sync::mpsc::channel();
+        for i in 0..3 {
+            let reply = reply.clone();
+            let job = move |c: &mut Context| reply.send((i, c.slaves().len())).unwrap();
+            assert!(commands.send(Command::Run(Box::new(job))).is_ok());
+        }
+        let (map_reply, map_result) = oneshot::channel();
+        assert!(commands.send(Command::MapGroup(0, map_reply)).is_ok());
+
+        drop(commands);
+        handle.join().unwrap();
+        assert_eq!(
+            replies.try_iter().collect::<Vec<_>>(),
+            [(0, 2), (1, 2), (2, 2)]
+        );
+        assert!(matches!(
+            map_result.blocking_recv(),
+            Ok(Err(AsyncError::GroupMapped(0)))
+        ));
+    }
+
+    #[test]
+    fn serve_outlives_the_contexts_until_the_exchange_ends() {
+        let (commands, handle) = spawn_serve();
+        let (snapshots, mut stream) = mpsc::channel(1);
+        // Group 2 is not there, so the exchange ends on the first cycle
+        let cyclic = Cyclic {
+            group: 2,
+            period: Duration::from_millis(1),
+            timeout: 0,
+            next: Instant::now(),
+            cycle: 0,
+            snapshots,
+        };
+        assert!(commands.send(Command::Cyclic(cyclic)).is_ok());
+        drop(commands);
+        handle.join().unwrap();
+        assert!(stream.blocking_recv().is_none());
+    }
+
+    #[test]
+    fn schedule_resyncs_after_a_stall() {
+        let now = Instant::now();
+        let ms = Duration::from_millis;
+        let (snapshots, _stream) = mpsc::channel(1);
+        let mut cyclic = Cyclic {
+            group: 0,
+            period: ms(10),
+            timeout: 0,
+            next: now,
+            cycle: 0,
+            snapshots,
+        };
+
+        assert_eq!(cyclic.schedule(now), ms(0));
+        assert_eq!(cyclic.schedule(now + ms(4)), ms(6));
+        // Cycles due at 20, 30, 40 and 50 ms are missed and not repeated
+        assert_eq!(cyclic.schedule(now + ms(55)), ms(0));
+        assert_eq!(cyclic.next, now + ms(65));
+        assert_eq!(cyclic.schedule(now + ms(56)), ms(9));
+    }
+
+    #[test]
+    fn group_zero_holds_every_slave() {
+        let mut inputs = [1u8, 2, 3];
+        let slaves: Vec<Slave> = inputs
+            .iter_mut()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut slave = Slave::default();
+                slave.0.group = i as u8 % 2;
+                slave.0.Ibits = 8;
+                slave.0.Ibytes = 1;
+                slave.0.inputs = x;
+                slave
+            })
+            .collect();
+
+        assert_eq!(group_inputs(&slaves, 0), [[1], [2], [3]]);
+        assert_eq!(group_inputs(&slaves, 1), [[2]]);
+
+        let mut empty = Slave::default();
+        empty.0.inputs = ptr::null_mut();
+        assert_eq!(group_inputs(&[empty], 0), [Vec::<u8>::new()]);
+    }
+}
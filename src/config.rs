@@ -0,0 +1,775 @@
+use crate::{
+    error::ConfigError, ConfigMismatch, Context, EtherCatState, RedPort, Transition, EC_TIMEOUTRXM,
+    EC_TIMEOUTSTATE,
+};
+use serde::{de, Deserialize, Deserializer};
+use std::result;
+
+/** Object of the first PDO assignment, 0x1c10 + sync manager */
+const COE_PDO_ASSIGN: u16 = 0x1c10;
+
+impl<'de> Deserialize<'de> for Transition {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| de::Error::custom(format!("invalid transition {}", s)))
+    }
+}
+
+/// Typed value of a [`ConfigSdo`], written little endian
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SdoValue {
+    U8(u8),
+    I8(i8),
+    U16(u16),
+    I16(i16),
+    U32(u32),
+    I32(i32),
+    U64(u64),
+    I64(i64),
+    F32(f32),
+    Bytes(Vec<u8>),
+}
+
+impl SdoValue {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match *self {
+            SdoValue::U8(x) => x.to_le_bytes().to_vec(),
+            SdoValue::I8(x) => x.to_le_bytes().to_vec(),
+            SdoValue::U16(x) => x.to_le_bytes().to_vec(),
+            SdoValue::I16(x) => x.to_le_bytes().to_vec(),
+            SdoValue::U32(x) => x.to_le_bytes().to_vec(),
+            SdoValue::I32(x) => x.to_le_bytes().to_vec(),
+            SdoValue::U64(x) => x.to_le_bytes().to_vec(),
+            SdoValue::I64(x) => x.to_le_bytes().to_vec(),
+            SdoValue::F32(x) => x.to_le_bytes().to_vec(),
+            SdoValue::Bytes(ref x) => x.clone(),
+        }
+    }
+}
+
+/// SDO written on a state transition
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSdo {
+    transition: Transition,
+    index: u16,
+    #[serde(default)]
+    subindex: u8,
+    #[serde(default)]
+    complete_access: bool,
+    value: SdoValue,
+}
+
+impl ConfigSdo {
+    pub const fn transition(&self) -> Transition {
+        self.transition
+    }
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub const fn subindex(&self) -> u8 {
+        self.subindex
+    }
+    pub const fn complete_access(&self) -> bool {
+        self.complete_access
+    }
+    pub const fn value(&self) -> &SdoValue {
+        &self.value
+    }
+}
+
+/// Object mapped into a [`ConfigPdo`]
+#[derive(Debug, PartialEq, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigPdoEntry {
+    index: u16,
+    #[serde(default)]
+    subindex: u8,
+    bit_len: u8,
+}
+
+impl ConfigPdoEntry {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub const fn subindex(&self) -> u8 {
+        self.subindex
+    }
+    pub const fn bit_len(&self) -> u8 {
+        self.bit_len
+    }
+    /// Mapping entry as written to the PDO object
+    pub const fn bits(&self) -> u32 {
+        (self.index as u32) << 16 | (self.subindex as u32) << 8 | self.bit_len as u32
+    }
+}
+
+/// PDO assigned to a sync manager, the mapping of the slave is kept if no entries are given
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigPdo {
+    index: u16,
+    #[serde(default)]
+    entries: Option<Vec<ConfigPdoEntry>>,
+}
+
+impl ConfigPdo {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub fn entries(&self) -> Option<&[ConfigPdoEntry]> {
+        self.entries.as_deref()
+    }
+}
+
+/// PDO assignment of a process data sync manager
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSyncManager {
+    sm: u8,
+    pdos: Vec<ConfigPdo>,
+}
+
+impl ConfigSyncManager {
+    pub const fn sm(&self) -> u8 {
+        self.sm
+    }
+    pub fn pdos(&self) -> &[ConfigPdo] {
+        &self.pdos
+    }
+}
+
+/// Distributed clock SYNC signals of a slave
+#[derive(Debug, Default, PartialEq, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigDc {
+    #[serde(default)]
+    sync0_cycle: Option<u32>,
+    #[serde(default)]
+    sync1_cycle: Option<u32>,
+    #[serde(default)]
+    shift: i32,
+}
+
+impl ConfigDc {
+    /// SYNC0 cycle time in ns, the cycle time of the master by default
+    pub const fn sync0_cycle(&self) -> Option<u32> {
+        self.sync0_cycle
+    }
+    /// Delay of SYNC1 after SYNC0 in ns, SYNC1 is not used by default
+    pub const fn sync1_cycle(&self) -> Option<u32> {
+        self.sync1_cycle
+    }
+    pub const fn shift(&self) -> i32 {
+        self.shift
+    }
+}
+
+/// Slave of a [`MasterConfig`]
+#[derive(Debug, Default, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigSlave {
+    #[serde(default)]
+    position: Option<u16>,
+    #[serde(default)]
+    alias: Option<u16>,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    vendor_id: Option<u32>,
+    #[serde(default)]
+    product_code: Option<u32>,
+    #[serde(default)]
+    revision: Option<u32>,
+    #[serde(default)]
+    group: u8,
+    #[serde(default)]
+    sdo: Vec<ConfigSdo>,
+    #[serde(default)]
+    pdo: Vec<ConfigSyncManager>,
+    #[serde(default)]
+    dc: Option<ConfigDc>,
+}
+
+impl ConfigSlave {
+    /// Slave number starting from 1
+    pub const fn position(&self) -> Option<u16> {
+        self.position
+    }
+    pub const fn alias(&self) -> Option<u16> {
+        self.alias
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub const fn vendor_id(&self) -> Option<u32> {
+        self.vendor_id
+    }
+    pub const fn product_code(&self) -> Option<u32> {
+        self.product_code
+    }
+    pub const fn revision(&self) -> Option<u32> {
+        self.revision
+    }
+    pub const fn group(&self) -> u8 {
+        self.group
+    }
+    pub fn sdo(&self) -> &[ConfigSdo] {
+        &self.sdo
+    }
+    pub fn pdo(&self) -> &[ConfigSyncManager] {
+        &self.pdo
+    }
+    pub const fn dc(&self) -> Option<&ConfigDc> {
+        self.dc.as_ref()
+    }
+}
+
+/// Master setup loaded from TOML or YAML, see [`Context::configure`].
+///
+/// The buffers of SOEM are borrowed from the caller, the port is opened again on the configured
+/// interfaces.
+#[derive(Debug, PartialEq, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MasterConfig {
+    interface: String,
+    #[serde(default)]
+    redundant_interface: Option<String>,
+    cycle_time: u32,
+    #[serde(default)]
+    groups: Vec<u8>,
+    #[serde(default)]
+    slaves: Vec<ConfigSlave>,
+}
+
+impl MasterConfig {
+    pub fn from_toml(text: &str) -> result::Result<Self, ConfigError> {
+        toml::from_str(text).map_err(|err| ConfigError::Parse(err.to_string()))
+    }
+    pub fn from_yaml(text: &str) -> result::Result<Self, ConfigError> {
+        serde_yaml::from_str(text).map_err(|err| ConfigError::Parse(err.to_string()))
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+    /// Interface at the other end of the ring, see [`Context::init_redundant`]
+    pub fn redundant_interface(&self) -> Option<&str> {
+        self.redundant_interface.as_deref()
+    }
+    /// Cycle time in ns
+    pub const fn cycle_time(&self) -> u32 {
+        self.cycle_time
+    }
+    /// Groups to be mapped, group 0 only if none are given
+    pub fn groups(&self) -> &[u8] {
+        &self.groups
+    }
+    pub fn slaves(&self) -> &[ConfigSlave] {
+        &self.slaves
+    }
+
+    /// Groups in mapping order, group 0 already holds every slave so it stands alone
+    fn mapped_groups(&self) -> result::Result<Vec<u8>, ConfigError> {
+        if self.groups.is_empty() {
+            return Ok(vec![0]);
+        }
+        for (i, &x) in self.groups.iter().enumerate() {
+            if (x == 0 && self.groups.len() > 1) || self.groups[..i].contains(&x) {
+                return Err(ConfigError::InvalidGroup(x));
+            }
+        }
+        if self.groups != [0] {
+            if let Some(x) = self.slaves.iter().find(|x| !self.groups.contains(&x.group)) {
+                return Err(ConfigError::InvalidGroup(x.group));
+            }
+        }
+        Ok(self.groups.clone())
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Slave numbers of the configured slaves, found by alias, by position or by their order
+    fn resolve_config_slaves(
+        &self,
+        config: &MasterConfig,
+    ) -> result::Result<Vec<u16>, ConfigError> {
+        let found = self.slaves();
+        let mut res = Vec::new();
+        let mut mismatches = Vec::new();
+
+        for (i, x) in config.slaves.iter().enumerate() {
+            let position = x.position.unwrap_or(i as u16 + 1);
+            let slave = match x.alias {
+                Some(alias) => {
+                    let slave = self
+                        .slave_by_alias(alias)
+                        .ok_or(ConfigError::UnknownAlias(alias))?;
+                    if x.position.is_some_and(|p| p != slave) {
+                        mismatches.push(ConfigMismatch::WrongPosition {
+                            expected: position,
+                            found: slave,
+                        });
+                    }
+                    slave
+                }
+                None => position,
+            };
+            if res.contains(&slave) {
+                mismatches.push(ConfigMismatch::Duplicate(slave));
+                continue;
+            }
+            let s = match found.get((slave as usize).wrapping_sub(1)) {
+                Some(s) => s,
+                None => {
+                    mismatches.push(ConfigMismatch::Missing(slave));
+                    continue;
+                }
+            };
+
+            let expected = (
+                x.vendor_id.unwrap_or_else(|| s.eep_manufacturer()),
+                x.product_code.unwrap_or_else(|| s.eep_id()),
+            );
+            let found = (s.eep_manufacturer(), s.eep_id());
+            if expected != found {
+                mismatches.push(ConfigMismatch::WrongProduct {
+                    slave,
+                    expected,
+                    found,
+                });
+            }
+            match x.revision {
+                Some(r) if r != s.eep_revision() => {
+                    mismatches.push(ConfigMismatch::WrongRevision {
+                        slave,
+                        expected: r,
+                        found: s.eep_revision(),
+                    })
+                }
+                _ => (),
+            }
+            res.push(slave);
+        }
+
+        match mismatches.is_empty() {
+            true => Ok(res),
+            false => Err(ConfigError::Mismatch(mismatches)),
+        }
+    }
+
+    fn write_config_sdo(
+        &mut self,
+        slave: u16,
+        index: u16,
+        subindex: u8,
+        complete_access: bool,
+        data: &[u8],
+    ) -> result::Result<(), ConfigError> {
+        self.write_sdo_bytes(slave, index, subindex, complete_access, data, EC_TIMEOUTRXM)
+            .map_err(|err| ConfigError::ErrorList(slave, err.collect()))
+    }
+
+    /// Write a list object such as a PDO mapping, the count in subindex 0 is written last
+    fn write_config_list<T, F>(
+        &mut self,
+        slave: u16,
+        index: u16,
+        items: &[T],
+        to_bytes: F,
+    ) -> result::Result<(), ConfigError>
+    where
+        F: Fn(&T) -> Vec<u8>,
+    {
+        self.write_config_sdo(slave, index, 0, false, &[0])?;
+        for (i, x) in items.iter().enumerate() {
+            self.write_config_sdo(slave, index, i as u8 + 1, false, &to_bytes(x))?;
+        }
+        self.write_config_sdo(slave, index, 0, false, &[items.len() as u8])
+    }
+
+    fn apply_config_pdo(&mut self, slave: u16, x: &ConfigSlave) -> result::Result<(), ConfigError> {
+        for sm in x.pdo.iter() {
+            for pdo in sm.pdos.iter() {
+                if let Some(ref entries) = pdo.entries {
+                    self.write_config_list(slave, pdo.index, entries, |x| {
+                        x.bits().to_le_bytes().to_vec()
+                    })?;
+                }
+            }
+            self.write_config_list(slave, COE_PDO_ASSIGN + sm.sm as u16, &sm.pdos, |x| {
+                x.index.to_le_bytes().to_vec()
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Write the SDOs configured for the transition, the slaves have to be found by
+    /// [`Context::config_init`] before
+    pub fn apply_config_sdo(
+        &mut self,
+        config: &MasterConfig,
+        transition: Transition,
+    ) -> result::Result<(), ConfigError> {
+        let slaves = self.resolve_config_slaves(config)?;
+        for (&slave, x) in slaves.iter().zip(config.slaves.iter()) {
+            for sdo in x.sdo.iter().filter(|x| x.transition == transition) {
+                self.write_config_sdo(
+                    slave,
+                    sdo.index,
+                    sdo.subindex,
+                    sdo.complete_access,
+                    &sdo.value.to_bytes(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Bring the network up as configured.
+    ///
+    /// The port is opened again on [`MasterConfig::interface`], together with
+    /// [`MasterConfig::redundant_interface`] on `red_port` if one is configured. The slaves are
+    /// found and checked against the configured identities, and once they have reached PreOp the
+    /// PreOp to SafeOp SDOs and the PDO assignment are written. The groups are mapped to
+    /// `io_maps` in the order of [`MasterConfig::groups`]. DC is configured when any slave asks
+    /// for SYNC signals. Returns the total size of the process images, the remaining transitions
+    /// are left to the caller, see [`Context::apply_config_sdo`].
+    pub fn configure<I>(
+        &mut self,
+        config: &MasterConfig,
+        red_port: Option<&'a mut RedPort>,
+        io_maps: I,
+    ) -> result::Result<usize, ConfigError>
+    where
+        I: IntoIterator<Item = &'a mut [u8; 4096]>,
+    {
+        let groups = config.mapped_groups()?;
+        match (config.redundant_interface.as_deref(), red_port) {
+            (Some(redundant), Some(red_port)) => {
+                self.init_redundant(red_port, &config.interface, redundant)
+            }
+            (Some(_), None) => return Err(ConfigError::NoRedPort),
+            (None, _) => self.reinit(&config.interface),
+        }
+        .map_err(ConfigError::InitError)?;
+
+        self.config_init(false)
+            .map_err(ConfigError::EtherCatError)?;
+        let slaves = self.resolve_config_slaves(config)?;
+
+        // config_init only requests PreOp, the mailboxes are usable once it is reached
+        match self.check_state(0, EtherCatState::PreOp, EC_TIMEOUTSTATE) {
+            EtherCatState::PreOp => (),
+            state => return Err(ConfigError::StateError(EtherCatState::PreOp, state)),
+        }
+
+        for (&slave, x) in slaves.iter().zip(config.slaves.iter()) {
+            self.slave_mut(slave).0.group = x.group;
+            self.apply_config_pdo(slave, x)?;
+        }
+        self.apply_config_sdo(
+            config,
+            Transition::new(EtherCatState::PreOp, EtherCatState::SafeOp),
+        )?;
+
+        let mut io_maps = io_maps.into_iter();
+        let mut size = 0;
+        for group in groups {
+            let io_map = io_maps.next().ok_or(ConfigError::NoIoMap(group))?;
+            size += self
+                .config_map_group(io_map, group)
+                .map_err(|err| ConfigError::MapError(group, err.collect()))?;
+        }
+
+        if config.slaves.iter().any(|x| x.dc.is_some()) {
+            self.config_dc()
+                .map_err(|err| ConfigError::DcError(err.collect()))?;
+            for (&slave, x) in slaves.iter().zip(config.slaves.iter()) {
+                let dc = match x.dc {
+                    Some(dc) => dc,
+                    None => continue,
+                };
+                let sync0 = dc.sync0_cycle.unwrap_or(config.cycle_time);
+                match dc.sync1_cycle {
+                    Some(sync1) => self.dc_sync01(slave, true, sync0, sync1, dc.shift),
+                    None => self.dc_sync0(slave, true, sync0, dc.shift),
+                }
+            }
+        }
+
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::MockBuffers, Slave};
+
+    const TOML: &str = r#"
+interface = "eth0"
+redundant_interface = "eth1"
+cycle_time = 1000000
+groups = [1, 2]
+
+[[slaves]]
+position = 1
+vendor_id = 2
+product_code = 0x07d83052
+group = 1
+
+[[slaves.sdo]]
+transition = "PS"
+index = 0x8000
+subindex = 1
+value = { u16 = 5 }
+
+[[slaves.sdo]]
+transition = "ps"
+index = 0x8010
+complete_access = true
+value = { bytes = [1, 2, 3] }
+
+[[slaves.pdo]]
+sm = 2
+pdos = [{ index = 0x1600, entries = [{ index = 0x7000, subindex = 1, bit_len = 1 }] }]
+
+[[slaves.pdo]]
+sm = 3
+pdos = [{ index = 0x1a00 }]
+
+[slaves.dc]
+sync0_cycle = 1000000
+
+[[slaves]]
+alias = 100
+group = 2
+"#;
+
+    const YAML: &str = r#"
+interface: eth0
+cycle_time: 1000000
+slaves:
+  - position: 1
+    sdo:
+      - transition: SO
+        index: 0x8000
+        value: { i32: -2 }
+      - transition: IP
+        index: 0x8001
+        subindex: 3
+        value: { f32: 1.5 }
+    pdo:
+      - sm: 2
+        pdos:
+          - index: 0x1600
+            entries:
+              - { index: 0x7000, bit_len: 16 }
+"#;
+
+    fn master(groups: &[u8]) -> MasterConfig {
+        MasterConfig {
+            interface: "eth0".to_string(),
+            redundant_interface: None,
+            cycle_time: 1_000_000,
+            groups: groups.to_vec(),
+            slaves: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn toml_schema() {
+        let config = MasterConfig::from_toml(TOML).unwrap();
+        assert_eq!(config.interface(), "eth0");
+        assert_eq!(config.redundant_interface(), Some("eth1"));
+        assert_eq!(config.cycle_time(), 1_000_000);
+        assert_eq!(config.groups(), &[1, 2]);
+        assert_eq!(config.slaves().len(), 2);
+
+        let slave = &config.slaves()[0];
+        assert_eq!(slave.position(), Some(1));
+        assert_eq!(slave.alias(), None);
+        assert_eq!(slave.vendor_id(), Some(2));
+        assert_eq!(slave.product_code(), Some(0x07d8_3052));
+        assert_eq!(slave.revision(), None);
+        assert_eq!(slave.group(), 1);
+
+        let sdo = slave.sdo();
+        let ps = Transition::new(EtherCatState::PreOp, EtherCatState::SafeOp);
+        assert_eq!(sdo[0].transition(), ps);
+        assert_eq!((sdo[0].index(), sdo[0].subindex()), (0x8000, 1));
+        assert!(!sdo[0].complete_access());
+        assert_eq!(sdo[0].value(), &SdoValue::U16(5));
+        assert_eq!(sdo[1].transition(), ps);
+        assert_eq!(sdo[1].subindex(), 0);
+        assert!(sdo[1].complete_access());
+        assert_eq!(sdo[1].value(), &SdoValue::Bytes(vec![1, 2, 3]));
+
+        let pdo = slave.pdo();
+        assert_eq!(pdo[0].sm(), 2);
+        let entries = pdo[0].pdos()[0].entries().unwrap();
+        assert_eq!(entries[0].bits(), 0x7000_0101);
+        assert_eq!(pdo[1].pdos()[0].index(), 0x1a00);
+        assert_eq!(pdo[1].pdos()[0].entries(), None);
+
+        let dc = slave.dc().unwrap();
+        assert_eq!(dc.sync0_cycle(), Some(1_000_000));
+        assert_eq!((dc.sync1_cycle(), dc.shift()), (None, 0));
+
+        let slave = &config.slaves()[1];
+        assert_eq!((slave.position(), slave.alias()), (None, Some(100)));
+        assert!(slave.sdo().is_empty() && slave.pdo().is_empty());
+        assert_eq!(slave.dc(), None);
+    }
+
+    #[test]
+    fn yaml_schema() {
+        let config = MasterConfig::from_yaml(YAML).unwrap();
+        assert_eq!(config.redundant_interface(), None);
+        assert!(config.groups().is_empty());
+
+        let slave = &config.slaves()[0];
+        assert_eq!(slave.group(), 0);
+        let sdo = slave.sdo();
+        assert_eq!(
+            sdo[0].transition(),
+            Transition::new(EtherCatState::SafeOp, EtherCatState::Op)
+        );
+        assert_eq!(sdo[0].value(), &SdoValue::I32(-2));
+        assert_eq!(
+            sdo[1].transition(),
+            Transition::new(EtherCatState::Init, EtherCatState::PreOp)
+        );
+        assert_eq!(sdo[1].subindex(), 3);
+        assert_eq!(sdo[1].value(), &SdoValue::F32(1.5));
+
+        let entry = slave.pdo()[0].pdos()[0].entries().unwrap()[0];
+        assert_eq!(
+            (entry.index(), entry.subindex(), entry.bit_len()),
+            (0x7000, 0, 16)
+        );
+    }
+
+    #[test]
+    fn rejected_schema() {
+        let invalid = [
+            // unknown fields
+            "interface = \"eth0\"\ncycle_time = 1\nname = \"x\"",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\nid = 1",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[slaves.dc]\nsync2_cycle = 1",
+            // missing fields
+            "interface = \"eth0\"",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[[slaves.sdo]]\ntransition = \"PS\"\nindex = 1",
+            // invalid transitions and values
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[[slaves.sdo]]\ntransition = \"PX\"\nindex = 1\nvalue = { u8 = 1 }",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[[slaves.sdo]]\ntransition = \"PSO\"\nindex = 1\nvalue = { u8 = 1 }",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[[slaves.sdo]]\ntransition = \"PS\"\nindex = 1\nvalue = { u8 = 256 }",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[[slaves.sdo]]\ntransition = \"PS\"\nindex = 1\nvalue = { u128 = 1 }",
+            "interface = \"eth0\"\ncycle_time = 1\n[[slaves]]\n[[slaves.sdo]]\ntransition = \"PS\"\nindex = 1\nvalue = 1",
+        ];
+        for text in &invalid {
+            assert!(
+                matches!(MasterConfig::from_toml(text), Err(ConfigError::Parse(_))),
+                "{}",
+                text
+            );
+        }
+
+        let yaml = "interface: eth0\ncycle_time: 1\nslaves:\n  - position: 1\n    groups: 1";
+        assert!(matches!(
+            MasterConfig::from_yaml(yaml),
+            Err(ConfigError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn sdo_values_are_little_endian() {
+        assert_eq!(SdoValue::U8(0x12).to_bytes(), [0x12]);
+        assert_eq!(SdoValue::I8(-1).to_bytes(), [0xff]);
+        assert_eq!(SdoValue::U16(0x1234).to_bytes(), [0x34, 0x12]);
+        assert_eq!(SdoValue::I16(-2).to_bytes(), [0xfe, 0xff]);
+        assert_eq!(
+            SdoValue::U32(0x1234_5678).to_bytes(),
+            [0x78, 0x56, 0x34, 0x12]
+        );
+        assert_eq!(SdoValue::I32(-1).to_bytes(), [0xff; 4]);
+        assert_eq!(SdoValue::U64(1).to_bytes(), [1, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(SdoValue::I64(-1).to_bytes(), [0xff; 8]);
+        assert_eq!(SdoValue::F32(1.0).to_bytes(), [0, 0, 0x80, 0x3f]);
+        assert_eq!(SdoValue::Bytes(vec![1, 2]).to_bytes(), [1, 2]);
+    }
+
+    #[test]
+    fn mapped_groups() {
+        assert_eq!(master(&[]).mapped_groups().unwrap(), [0]);
+        assert_eq!(master(&[0]).mapped_groups().unwrap(), [0]);
+        assert_eq!(master(&[2, 1]).mapped_groups().unwrap(), [2, 1]);
+
+        for (groups, group) in &[(&[0, 1][..], 0), (&[1, 0][..], 0), (&[1, 2, 1][..], 1)] {
+            match master(groups).mapped_groups() {
+                Err(ConfigError::InvalidGroup(x)) => assert_eq!(x, *group),
+                x => panic!("{:?} mapped as {:?}", groups, x),
+            }
+        }
+    }
+
+    #[test]
+    fn slave_groups_must_be_mapped() {
+        let slave = |group| ConfigSlave {
+            group,
+            ..Default::default()
+        };
+        let mut config = master(&[1, 2]);
+        config.slaves = vec![slave(2), slave(1)];
+        assert_eq!(config.mapped_groups().unwrap(), [1, 2]);
+
+        for group in &[0, 3] {
+            config.slaves.push(slave(*group));
+            match config.mapped_groups() {
+                Err(ConfigError::InvalidGroup(x)) => assert_eq!(x, *group),
+                x => panic!("group {} mapped as {:?}", group, x),
+            }
+            config.slaves.pop();
+        }
+
+        // Group 0 holds every slave
+        config.groups = vec![0];
+        config.slaves.push(slave(3));
+        assert_eq!(config.mapped_groups().unwrap(), [0]);
+        config.groups.clear();
+        assert_eq!(config.mapped_groups().unwrap(), [0]);
+    }
+
+    #[test]
+    fn slaves_are_configured_once() {
+        let mut slaves: Vec<Slave> = (0..3).map(|_| Default::default()).collect();
+        slaves[2].0.aliasadr = 100;
+        let mut buffers = MockBuffers::new(slaves, 1);
+        let c = buffers.context();
+
+        let mut config = master(&[]);
+        config.slaves = vec![
+            ConfigSlave {
+                position: Some(1),
+                ..Default::default()
+            },
+            ConfigSlave {
+                alias: Some(100),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(c.resolve_config_slaves(&config).unwrap(), [1, 2]);
+
+        config.slaves.push(ConfigSlave {
+            position: Some(2),
+            ..Default::default()
+        });
+        match c.resolve_config_slaves(&config) {
+            Err(ConfigError::Mismatch(x)) => assert_eq!(x, [ConfigMismatch::Duplicate(2)]),
+            x => panic!("resolved as {:?}", x),
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use crate::{
+    error::EtherCatError, AlControl, Context, DatagramError, EtherCatState, FmmuUsage,
+    SyncManagerType, EC_TIMEOUTRET, EC_TIMEOUTSTATE,
+};
+use std::{os::raw::c_char, result};
+
+/** ESC address of the first sync manager */
+const ECT_REG_SM0: u16 = 0x0800;
+
+/// Sync manager setup of a [`SlaveConfig`]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SmConfig {
+    sm_type: SyncManagerType,
+    start_address: u16,
+    length: Option<u16>,
+    flags: u32,
+}
+
+impl SmConfig {
+    /// `flags` holds the control register in the lowest byte and the activation in the third one
+    pub const fn new(sm_type: SyncManagerType, start_address: u16, flags: u32) -> Self {
+        SmConfig {
+            sm_type,
+            start_address,
+            length: None,
+            flags,
+        }
+    }
+
+    pub const fn sm_type(&self) -> SyncManagerType {
+        self.sm_type
+    }
+    pub const fn start_address(&self) -> u16 {
+        self.start_address
+    }
+    pub const fn length(&self) -> Option<u16> {
+        self.length
+    }
+    pub const fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Length in bytes, process data sync managers default to the configured input or output
+    /// bits and mailboxes keep the length found in the SII
+    pub fn set_length(&mut self, length: u16) -> &mut Self {
+        self.length = Some(length);
+        self
+    }
+}
+
+/// Configuration used instead of the SII for the slaves of a product, see
+/// [`Context::push_slave_config`]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SlaveConfig {
+    vendor_id: u32,
+    product_code: u32,
+    name: Option<String>,
+    input_bits: Option<u16>,
+    output_bits: Option<u16>,
+    sync_managers: Vec<(u8, SmConfig)>,
+    fmmu: Vec<(u8, FmmuUsage)>,
+    mailbox_protocols: Option<u16>,
+}
+
+impl SlaveConfig {
+    pub fn new(vendor_id: u32, product_code: u32) -> Self {
+        SlaveConfig {
+            vendor_id,
+            product_code,
+            ..Default::default()
+        }
+    }
+
+    pub const fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+    pub const fn product_code(&self) -> u32 {
+        self.product_code
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+    pub const fn input_bits(&self) -> Option<u16> {
+        self.input_bits
+    }
+    pub const fn output_bits(&self) -> Option<u16> {
+        self.output_bits
+    }
+    pub fn sync_managers(&self) -> &[(u8, SmConfig)] {
+        &self.sync_managers
+    }
+    pub fn fmmu(&self) -> &[(u8, FmmuUsage)] {
+        &self.fmmu
+    }
+    pub const fn mailbox_protocols(&self) -> Option<u16> {
+        self.mailbox_protocols
+    }
+
+    pub fn set_name(&mut self, name: &str) -> &mut Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+    /// Size of the inputs, the PDO mapping is not read from the slave once the size of either
+    /// the inputs or the outputs is given
+    pub fn set_input_bits(&mut self, input_bits: u16) -> &mut Self {
+        self.input_bits = Some(input_bits);
+        self
+    }
+    pub fn set_output_bits(&mut self, output_bits: u16) -> &mut Self {
+        self.output_bits = Some(output_bits);
+        self
+    }
+    pub fn set_sync_manager(&mut self, index: u8, sm: SmConfig) -> &mut Self {
+        self.sync_managers.retain(|&(x, _)| x != index);
+        self.sync_managers.push((index, sm));
+        self
+    }
+    pub fn set_fmmu(&mut self, index: u8, usage: FmmuUsage) -> &mut Self {
+        self.fmmu.retain(|&(x, _)| x != index);
+        self.fmmu.push((index, usage));
+        self
+    }
+    /// Supported mailbox protocols, the same bits as [`Sii::mailbox_protocols`](crate::Sii::mailbox_protocols)
+    pub fn set_mailbox_protocols(&mut self, mailbox_protocols: u16) -> &mut Self {
+        self.mailbox_protocols = Some(mailbox_protocols);
+        self
+    }
+}
+
+fn sm_bytes(start: u16, length: u16, flags: u32) -> Vec<u8> {
+    let mut x = start.to_le_bytes().to_vec();
+    x.extend_from_slice(&length.to_le_bytes());
+    x.extend_from_slice(&flags.to_le_bytes());
+    x
+}
+
+impl<'a> Context<'a> {
+    /// Register a configuration for the slaves with the identity.
+    ///
+    /// [`Context::config_init`] applies it in place of the SII data, which helps with legacy
+    /// slaves carrying a broken SII. The first configuration registered for a product is used.
+    ///
+    /// SOEM has already programmed the mailbox found in the SII and requested PreOp by then, so
+    /// a slave with a broken SII may sit in Init with an error. When the configuration changes the
+    /// mailbox, its sync managers are written again, the error is acknowledged with a new PreOp
+    /// request and `config_init` fails unless the slave reaches PreOp.
+    pub fn push_slave_config(&mut self, config: SlaveConfig) {
+        self.slave_configs.push(config);
+    }
+
+    pub fn clear_slave_configs(&mut self) {
+        self.slave_configs.clear();
+    }
+
+    pub fn slave_configs(&self) -> &[SlaveConfig] {
+        &self.slave_configs
+    }
+
+    pub(crate) fn apply_slave_configs(&mut self) -> result::Result<(), EtherCatError> {
+        if self.slave_configs.is_empty() {
+            return Ok(());
+        }
+
+        let slavecount = self.slaves().len() as u16;
+        for slave in 1..=slavecount {
+            let (vendor_id, product_code) = {
+                let x = self.slave_mut(slave);
+                (x.eep_manufacturer(), x.eep_id())
+            };
+            let index = match self
+                .slave_configs
+                .iter()
+                .position(|x| x.vendor_id == vendor_id && x.product_code == product_code)
+            {
+                Some(x) => x,
+                None => continue,
+            };
+            let config = self.slave_configs[index].clone();
+            if self.apply_slave_config(slave, index, &config) {
+                self.rewrite_mailbox(slave)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the mailbox sync managers are changed
+    fn apply_slave_config(&mut self, slave: u16, index: usize, config: &SlaveConfig) -> bool {
+        let raw = &mut self.slave_mut(slave).0;
+        let mut mailbox_changed = false;
+
+        if let Some(ref name) = config.name {
+            let len = name.len().min(raw.name.len() - 1);
+            for (dst, &src) in raw.name.iter_mut().zip(name.as_bytes()[..len].iter()) {
+                *dst = src as c_char;
+            }
+            raw.name[len] = 0;
+        }
+        if let Some(x) = config.output_bits {
+            raw.Obits = x;
+        }
+        if let Some(x) = config.input_bits {
+            raw.Ibits = x;
+        }
+        // SOEM skips reading the PDO mapping of the slaves found in the configuration table
+        if config.output_bits.is_some() || config.input_bits.is_some() {
+            raw.configindex = index as u16 + 1;
+        }
+        if let Some(x) = config.mailbox_protocols {
+            raw.mbx_proto = x;
+        }
+
+        for &(n, ref sm) in config.sync_managers.iter() {
+            let n = n as usize;
+            if n >= raw.SM.len() {
+                continue;
+            }
+            let length = sm.length.or(match sm.sm_type {
+                SyncManagerType::Outputs => Some(raw.Obits.div_ceil(8)),
+                SyncManagerType::Inputs => Some(raw.Ibits.div_ceil(8)),
+                _ => None,
+            });
+            raw.SM[n].StartAddr = sm.start_address;
+            raw.SM[n].SMflags = sm.flags;
+            if let Some(x) = length {
+                raw.SM[n].SMlength = x;
+            }
+            raw.SMtype[n] = sm.sm_type.into();
+
+            match sm.sm_type {
+                SyncManagerType::MailboxOut => {
+                    raw.mbx_wo = sm.start_address;
+                    raw.mbx_l = raw.SM[n].SMlength;
+                    mailbox_changed = true;
+                }
+                SyncManagerType::MailboxIn => {
+                    raw.mbx_ro = sm.start_address;
+                    raw.mbx_rl = raw.SM[n].SMlength;
+                    mailbox_changed = true;
+                }
+                _ => (),
+            }
+        }
+
+        for &(n, usage) in config.fmmu.iter() {
+            let usage = usage.into();
+            match n {
+                0 => raw.FMMU0func = usage,
+                1 => raw.FMMU1func = usage,
+                2 => raw.FMMU2func = usage,
+                3 => raw.FMMU3func = usage,
+                _ => (),
+            }
+        }
+
+        mailbox_changed && raw.mbx_l > 0
+    }
+
+    /// Program the mailbox sync managers again and repeat the PreOp request of config_init
+    fn rewrite_mailbox(&mut self, slave: u16) -> result::Result<(), EtherCatError> {
+        let (station, sm0, sm1) = {
+            let raw = &self.slave_mut(slave).0;
+            (raw.configadr, raw.SM[0], raw.SM[1])
+        };
+        let mut data = sm_bytes(sm0.StartAddr, sm0.SMlength, sm0.SMflags);
+        data.extend(sm_bytes(sm1.StartAddr, sm1.SMlength, sm1.SMflags));
+
+        // Both sync managers are written in one datagram as SOEM does for old NETX based slaves
+        let written = match self.fpwr(station, ECT_REG_SM0, &data, EC_TIMEOUTRET * 3) {
+            Ok(1) => Ok(()),
+            Ok(wkc) => Err(DatagramError::WorkingCounter(wkc)),
+            Err(err) => Err(err),
+        };
+        written.map_err(|err| EtherCatError::SlaveConfig(slave, err))?;
+        if self.context.manualstatechange != 0 {
+            return Ok(());
+        }
+
+        self.write_register(slave, &AlControl::new(EtherCatState::PreOp, true))
+            .map_err(|err| EtherCatError::SlaveConfig(slave, err))?;
+        match self.check_state(slave, EtherCatState::PreOp, EC_TIMEOUTSTATE) {
+            EtherCatState::PreOp => Ok(()),
+            state => Err(EtherCatError::SlaveConfigState(slave, state)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+
+    fn identify(buffers: &mut MockBuffers, slave: u16, product_code: u32) {
+        let mut c = buffers.context();
+        let raw = &mut c.slave_mut(slave).0;
+        raw.eep_man = 2;
+        raw.eep_id = product_code;
+    }
+
+    #[test]
+    fn process_data_config() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+        c.slave_mut(1).0.SM[2].SMlength = 12;
+
+        let mut config = SlaveConfig::new(2, 1008);
+        config
+            .set_output_bits(12)
+            .set_input_bits(3)
+            .set_sync_manager(
+                2,
+                SmConfig::new(SyncManagerType::Outputs, 0x0f00, 0x0001_0044),
+            )
+            .set_sync_manager(
+                3,
+                SmConfig::new(SyncManagerType::Inputs, 0x1000, 0x0001_0000),
+            )
+            .set_fmmu(0, FmmuUsage::Outputs)
+            .set_fmmu(1, FmmuUsage::Inputs)
+            .set_fmmu(7, FmmuUsage::Inputs);
+        assert!(!c.apply_slave_config(1, 4, &config));
+
+        let raw = &c.slave_mut(1).0;
+        assert_eq!(raw.Obits, 12);
+        assert_eq!(raw.Ibits, 3);
+        assert_eq!(raw.configindex, 5);
+        assert_eq!({ raw.SM[2].StartAddr }, 0x0f00);
+        assert_eq!({ raw.SM[2].SMlength }, 2);
+        assert_eq!({ raw.SM[2].SMflags }, 0x0001_0044);
+        assert_eq!({ raw.SM[3].SMlength }, 1);
+        assert_eq!(raw.SMtype[2], 3);
+        assert_eq!(raw.SMtype[3], 4);
+        assert_eq!((raw.FMMU0func, raw.FMMU1func), (1, 2));
+    }
+
+    #[test]
+    fn explicit_lengths_and_mapping_from_the_slave() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+        c.slave_mut(1).0.Obits = 16;
+
+        let mut sm = SmConfig::new(SyncManagerType::Outputs, 0x0f00, 0x0001_0064);
+        sm.set_length(4);
+        let mut config = SlaveConfig::new(2, 1008);
+        config
+            .set_sync_manager(2, sm)
+            .set_sync_manager(9, SmConfig::new(SyncManagerType::Inputs, 0, 0));
+        c.apply_slave_config(1, 0, &config);
+
+        let raw = &c.slave_mut(1).0;
+        // The mapping is still read from the slave
+        assert_eq!(raw.configindex, 0);
+        assert_eq!(raw.Obits, 16);
+        assert_eq!({ raw.SM[2].SMlength }, 4);
+    }
+
+    #[test]
+    fn mailbox_config() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+
+        let mut out = SmConfig::new(SyncManagerType::MailboxOut, 0x1800, 0x0001_0026);
+        out.set_length(0x80);
+        let mut config = SlaveConfig::new(2, 1008);
+        config.set_mailbox_protocols(0x0c).set_sync_manager(0, out);
+        assert!(c.apply_slave_config(1, 0, &config));
+
+        let raw = &c.slave_mut(1).0;
+        assert_eq!(raw.mbx_wo, 0x1800);
+        assert_eq!(raw.mbx_l, 0x80);
+        assert_eq!(raw.mbx_proto, 0x0c);
+
+        // A mailbox without a length is left alone
+        let mut config = SlaveConfig::new(2, 1008);
+        config.set_sync_manager(1, SmConfig::new(SyncManagerType::MailboxIn, 0x1c00, 0));
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        assert!(!buffers.context().apply_slave_config(1, 0, &config));
+    }
+
+    #[test]
+    fn names_are_truncated() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+
+        let mut config = SlaveConfig::new(2, 1008);
+        config.set_name(&"x".repeat(100));
+        c.apply_slave_config(1, 0, &config);
+        let len = c.slave_mut(1).0.name.len();
+        assert_eq!(c.slaves()[0].name().len(), len - 1);
+
+        config.set_name("EL1008");
+        c.apply_slave_config(1, 0, &config);
+        assert_eq!(c.slaves()[0].name(), "EL1008");
+    }
+
+    #[test]
+    fn configs_match_the_identity() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        identify(&mut buffers, 1, 1008);
+        identify(&mut buffers, 2, 2008);
+        let mut c = buffers.context();
+
+        let mut first = SlaveConfig::new(2, 2008);
+        first.set_output_bits(8);
+        let mut second = SlaveConfig::new(2, 2008);
+        second.set_output_bits(16);
+        c.push_slave_config(first);
+        c.push_slave_config(second);
+        assert_eq!(c.apply_slave_configs(), Ok(()));
+
+        assert_eq!(c.slaves()[0].output_size(), 0);
+        assert_eq!(c.slaves()[1].output_size(), 8);
+        assert_eq!(c.slave_mut(2).0.configindex, 1);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{error::DatagramError, Context};
+use std::{ffi::c_void, os::raw::c_int, result};
+use SOEM_sys::{
+    ecx_APRD, ecx_APWR, ecx_ARMW, ecx_BRD, ecx_BWR, ecx_FPRD, ecx_FPWR, ecx_FRMW, ecx_LRD, ecx_LRW,
+    ecx_LWR,
+};
+
+/** maximum data size of a single datagram frame */
+const EC_MAXLRWDATA: usize = 1518 - 14 - 2 - 10 - 2 - 4;
+/** returned by SOEM when no frame came back */
+const EC_NOFRAME: c_int = -1;
+
+fn check_len(len: usize) -> result::Result<u16, DatagramError> {
+    match len {
+        x if x > EC_MAXLRWDATA => Err(DatagramError::TooLarge(x)),
+        x => Ok(x as u16),
+    }
+}
+
+fn wkc(x: c_int) -> result::Result<u16, DatagramError> {
+    match x {
+        EC_NOFRAME => Err(DatagramError::NoFrame),
+        x if x < 0 => Err(DatagramError::Unknown(x)),
+        x => Ok(x as u16),
+    }
+}
+
+/// Auto increment address of the slave at the ring position, counting from 0
+const fn position_addr(position: u16) -> u16 {
+    0u16.wrapping_sub(position)
+}
+
+impl<'a> Context<'a> {
+    fn read_datagram<F>(
+        &mut self,
+        len: usize,
+        f: F,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError>
+    where
+        F: FnOnce(&mut Self, u16, *mut c_void) -> c_int,
+    {
+        let length = check_len(len)?;
+        let mut data = vec![0u8; len];
+        let wkc = wkc(f(self, length, data.as_mut_ptr() as *mut c_void))?;
+        Ok((data, wkc))
+    }
+
+    fn write_datagram<F>(&mut self, data: &[u8], f: F) -> result::Result<u16, DatagramError>
+    where
+        F: FnOnce(&mut Self, u16, *mut c_void) -> c_int,
+    {
+        let length = check_len(data.len())?;
+        // SOEM copies the data into the frame, the buffer is never written
+        wkc(f(self, length, data.as_ptr() as *mut c_void))
+    }
+
+    /// Broadcast read, every slave ORs its register into the data.
+    ///
+    /// Returns the data and the working counter.
+    pub fn brd(
+        &mut self,
+        ado: u16,
+        len: usize,
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        self.read_datagram(len, |c, length, data| unsafe {
+            ecx_BRD(c.context.port, 0, ado, length, data, timeout)
+        })
+    }
+
+    /// Broadcast write, returns the working counter
+    pub fn bwr(
+        &mut self,
+        ado: u16,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<u16, DatagramError> {
+        self.write_datagram(data, |c, length, data| unsafe {
+            ecx_BWR(c.context.port, 0, ado, length, data, timeout)
+        })
+    }
+
+    /// Auto increment read from the slave at the ring position, counting from 0
+    pub fn aprd(
+        &mut self,
+        position: u16,
+        ado: u16,
+        len: usize,
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        self.read_datagram(len, |c, length, data| unsafe {
+            ecx_APRD(
+                c.context.port,
+                position_addr(position),
+                ado,
+                length,
+                data,
+                timeout,
+            )
+        })
+    }
+
+    /// Auto increment write to the slave at the ring position, counting from 0
+    pub fn apwr(
+        &mut self,
+        position: u16,
+        ado: u16,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<u16, DatagramError> {
+        self.write_datagram(data, |c, length, data| unsafe {
+            ecx_APWR(
+                c.context.port,
+                position_addr(position),
+                ado,
+                length,
+                data,
+                timeout,
+            )
+        })
+    }
+
+    /// Configured address read from the slave with the station address
+    pub fn fprd(
+        &mut self,
+        station: u16,
+        ado: u16,
+        len: usize,
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        self.read_datagram(len, |c, length, data| unsafe {
+            ecx_FPRD(c.context.port, station, ado, length, data, timeout)
+        })
+    }
+
+    /// Configured address write to the slave with the station address
+    pub fn fpwr(
+        &mut self,
+        station: u16,
+        ado: u16,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<u16, DatagramError> {
+        self.write_datagram(data, |c, length, data| unsafe {
+            ecx_FPWR(c.context.port, station, ado, length, data, timeout)
+        })
+    }
+
+    /// Auto increment read multiple write.
+    ///
+    /// The slave at the ring position puts its register into the datagram, all the following
+    /// slaves write it, e.g. to distribute the DC reference time.
+    pub fn armw(
+        &mut self,
+        position: u16,
+        ado: u16,
+        len: usize,
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        self.read_datagram(len, |c, length, data| unsafe {
+            ecx_ARMW(
+                c.context.port,
+                position_addr(position),
+                ado,
+                length,
+                data,
+                timeout,
+            )
+        })
+    }
+
+    /// Configured address read multiple write
+    pub fn frmw(
+        &mut self,
+        station: u16,
+        ado: u16,
+        len: usize,
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        self.read_datagram(len, |c, length, data| unsafe {
+            ecx_FRMW(c.context.port, station, ado, length, data, timeout)
+        })
+    }
+
+    /// Logical memory read through the FMMUs
+    pub fn lrd(
+        &mut self,
+        logical_addr: u32,
+        len: usize,
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        self.read_datagram(len, |c, length, data| unsafe {
+            ecx_LRD(c.context.port, logical_addr, length, data, timeout)
+        })
+    }
+
+    /// Logical memory write through the FMMUs
+    pub fn lwr(
+        &mut self,
+        logical_addr: u32,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<u16, DatagramError> {
+        self.write_datagram(data, |c, length, data| unsafe {
+            ecx_LWR(c.context.port, logical_addr, length, data, timeout)
+        })
+    }
+
+    /// Logical memory read write through the FMMUs, returns the data read back
+    pub fn lrw(
+        &mut self,
+        logical_addr: u32,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<(Vec<u8>, u16), DatagramError> {
+        let mut buf = data.to_vec();
+        let length = check_len(buf.len())?;
+        let wkc = wkc(unsafe {
+            ecx_LRW(
+                self.context.port,
+                logical_addr,
+                length,
+                buf.as_mut_ptr() as *mut c_void,
+                timeout,
+            )
+        })?;
+        Ok((buf, wkc))
+    }
+}
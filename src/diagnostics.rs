@@ -0,0 +1,287 @@
+use crate::{error::DatagramError, Context, ErrorCounters};
+use std::result;
+
+/** Number of ports of an ESC */
+const ESC_PORTS: usize = 4;
+
+/// Error counters of a single port
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct PortCounters {
+    invalid_frame: u32,
+    rx_error: u32,
+    forwarded_rx_error: u32,
+    lost_link: u32,
+}
+
+impl PortCounters {
+    pub const fn invalid_frame(&self) -> u32 {
+        self.invalid_frame
+    }
+    pub const fn rx_error(&self) -> u32 {
+        self.rx_error
+    }
+    pub const fn forwarded_rx_error(&self) -> u32 {
+        self.forwarded_rx_error
+    }
+    pub const fn lost_link(&self) -> u32 {
+        self.lost_link
+    }
+    /// Errors originating on this port, forwarded errors come from further down the line
+    pub const fn local_errors(&self) -> u32 {
+        self.invalid_frame.saturating_add(self.rx_error)
+    }
+    pub const fn is_zero(&self) -> bool {
+        self.invalid_frame == 0
+            && self.rx_error == 0
+            && self.forwarded_rx_error == 0
+            && self.lost_link == 0
+    }
+
+    fn from_esc(x: &ErrorCounters, port: u8) -> Self {
+        PortCounters {
+            invalid_frame: x.invalid_frame(port).unwrap_or(0) as u32,
+            rx_error: x.rx_error(port).unwrap_or(0) as u32,
+            forwarded_rx_error: x.forwarded_rx_error(port).unwrap_or(0) as u32,
+            lost_link: x.lost_link(port).unwrap_or(0) as u32,
+        }
+    }
+
+    fn delta(&self, previous: &PortCounters) -> Self {
+        // A counter lower than before has been cleared in between
+        let d = |new: u32, old: u32| if new >= old { new - old } else { new };
+        PortCounters {
+            invalid_frame: d(self.invalid_frame, previous.invalid_frame),
+            rx_error: d(self.rx_error, previous.rx_error),
+            forwarded_rx_error: d(self.forwarded_rx_error, previous.forwarded_rx_error),
+            lost_link: d(self.lost_link, previous.lost_link),
+        }
+    }
+
+    fn accumulate(&mut self, x: &PortCounters) {
+        self.invalid_frame = self.invalid_frame.saturating_add(x.invalid_frame);
+        self.rx_error = self.rx_error.saturating_add(x.rx_error);
+        self.forwarded_rx_error = self.forwarded_rx_error.saturating_add(x.forwarded_rx_error);
+        self.lost_link = self.lost_link.saturating_add(x.lost_link);
+    }
+}
+
+/// Errors counted on a port since the previous poll
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PortDelta {
+    slave: u16,
+    port: u8,
+    delta: PortCounters,
+    total: PortCounters,
+}
+
+impl PortDelta {
+    pub const fn slave(&self) -> u16 {
+        self.slave
+    }
+    pub const fn port(&self) -> u8 {
+        self.port
+    }
+    pub const fn delta(&self) -> &PortCounters {
+        &self.delta
+    }
+    /// Errors accumulated since the monitor was created
+    pub const fn total(&self) -> &PortCounters {
+        &self.total
+    }
+}
+
+/// Tracks the ESC error counters of every slave, see [`ErrorMonitor::poll`]
+#[derive(Debug, Default)]
+pub struct ErrorMonitor {
+    last: Vec<Option<[PortCounters; ESC_PORTS]>>,
+    totals: Vec<[PortCounters; ESC_PORTS]>,
+    unreachable: Vec<u16>,
+    clear: bool,
+}
+
+impl ErrorMonitor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Clear the ESC counters after every read so that they never saturate at 255
+    pub fn set_clear(&mut self, clear: bool) -> &mut Self {
+        self.clear = clear;
+        self
+    }
+
+    /// Read the error counters of every slave and return the ports with new errors.
+    ///
+    /// The first poll of a slave only takes the reference values unless the counters are cleared.
+    /// Slaves not answering are skipped and listed by [`ErrorMonitor::unreachable`].
+    pub fn poll(&mut self, context: &mut Context) -> Vec<PortDelta> {
+        let count = context.slaves().len();
+        self.last.resize(count + 1, None);
+        self.totals.resize(count + 1, Default::default());
+        self.unreachable.clear();
+
+        let mut res = Vec::new();
+        for slave in 1..=count as u16 {
+            let counters = match context.read_error_counters(slave) {
+                Ok(x) => x,
+                Err(_) => {
+                    self.unreachable.push(slave);
+                    continue;
+                }
+            };
+            let cleared = self.clear && context.clear_error_counters(slave).is_ok();
+            self.update(slave, &counters, cleared, &mut res);
+        }
+
+        res
+    }
+
+    /// Compare the counters read from the slave with the previous poll
+    fn update(
+        &mut self,
+        slave: u16,
+        counters: &ErrorCounters,
+        cleared: bool,
+        res: &mut Vec<PortDelta>,
+    ) {
+        let mut current = [PortCounters::default(); ESC_PORTS];
+        for (port, x) in current.iter_mut().enumerate() {
+            *x = PortCounters::from_esc(counters, port as u8);
+        }
+
+        let previous = match self.last[slave as usize] {
+            Some(x) => x,
+            None if self.clear => Default::default(),
+            None => current,
+        };
+        self.last[slave as usize] = if cleared {
+            Some(Default::default())
+        } else {
+            Some(current)
+        };
+
+        for port in 0..ESC_PORTS {
+            let delta = current[port].delta(&previous[port]);
+            if delta.is_zero() {
+                continue;
+            }
+            let total = &mut self.totals[slave as usize][port];
+            total.accumulate(&delta);
+            res.push(PortDelta {
+                slave,
+                port: port as u8,
+                delta,
+                total: *total,
+            });
+        }
+    }
+
+    /// Slaves which did not answer during the last poll
+    pub fn unreachable(&self) -> &[u16] {
+        &self.unreachable
+    }
+
+    /// Errors accumulated on the port since the monitor was created
+    pub fn total(&self, slave: u16, port: u8) -> Option<&PortCounters> {
+        self.totals
+            .get(slave as usize)
+            .and_then(|x| x.get(port as usize))
+    }
+}
+
+impl<'a> Context<'a> {
+    pub fn read_error_counters(
+        &mut self,
+        slave: u16,
+    ) -> result::Result<ErrorCounters, DatagramError> {
+        self.read_register(slave)
+    }
+
+    /// Reset the error counters of the slave
+    pub fn clear_error_counters(&mut self, slave: u16) -> result::Result<(), DatagramError> {
+        self.write_register(slave, &ErrorCounters::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EscRegister;
+
+    fn counters(invalid_frame: u8, rx_error: u8) -> ErrorCounters {
+        let mut bytes = [0; ErrorCounters::SIZE];
+        bytes[0] = invalid_frame;
+        bytes[1] = rx_error;
+        ErrorCounters::from_bytes(&bytes)
+    }
+
+    fn monitor(clear: bool) -> ErrorMonitor {
+        let mut monitor = ErrorMonitor::new();
+        monitor.set_clear(clear);
+        monitor.last.resize(2, None);
+        monitor.totals.resize(2, Default::default());
+        monitor
+    }
+
+    fn update(monitor: &mut ErrorMonitor, x: ErrorCounters, cleared: bool) -> Vec<PortDelta> {
+        let mut res = Vec::new();
+        monitor.update(1, &x, cleared, &mut res);
+        res
+    }
+
+    #[test]
+    fn local_errors_saturate() {
+        let x = PortCounters {
+            invalid_frame: u32::MAX,
+            rx_error: 1,
+            ..Default::default()
+        };
+        assert_eq!(x.local_errors(), u32::MAX);
+    }
+
+    #[test]
+    fn first_poll_takes_the_reference() {
+        let mut monitor = monitor(false);
+        assert!(update(&mut monitor, counters(3, 1), false).is_empty());
+
+        let res = update(&mut monitor, counters(5, 1), false);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].slave(), 1);
+        assert_eq!(res[0].port(), 0);
+        assert_eq!(res[0].delta().invalid_frame(), 2);
+        assert_eq!(res[0].delta().rx_error(), 0);
+        assert_eq!(res[0].total().invalid_frame(), 2);
+
+        // Cleared by someone else in between
+        let res = update(&mut monitor, counters(1, 0), false);
+        assert_eq!(res[0].delta().invalid_frame(), 1);
+        assert_eq!(res[0].total().invalid_frame(), 3);
+        assert_eq!(monitor.total(1, 0).unwrap().local_errors(), 3);
+        assert!(update(&mut monitor, counters(1, 0), false).is_empty());
+    }
+
+    #[test]
+    fn cleared_counters_count_from_zero() {
+        let mut monitor = monitor(true);
+        let res = update(&mut monitor, counters(3, 1), true);
+        assert_eq!(res[0].delta().local_errors(), 4);
+
+        let res = update(&mut monitor, counters(2, 0), true);
+        assert_eq!(res[0].delta().invalid_frame(), 2);
+        assert_eq!(res[0].total().invalid_frame(), 5);
+
+        // Clearing failed, the next poll continues from the value read
+        assert_eq!(update(&mut monitor, counters(1, 0), false).len(), 1);
+        let res = update(&mut monitor, counters(4, 0), true);
+        assert_eq!(res[0].delta().invalid_frame(), 3);
+    }
+
+    #[test]
+    fn totals_saturate() {
+        let mut monitor = monitor(false);
+        monitor.totals[1][0].invalid_frame = u32::MAX - 1;
+        update(&mut monitor, counters(0, 0), false);
+        let res = update(&mut monitor, counters(0xff, 0), false);
+        assert_eq!(res[0].total().invalid_frame(), u32::MAX);
+    }
+}
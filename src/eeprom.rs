@@ -0,0 +1,165 @@
+use crate::{
+    error::EepromError, Context, SiiInterface, EC_MAXEEPBITMAP, EC_MAXEEPBUF, EC_TIMEOUTEEP,
+};
+use std::{result, slice};
+use SOEM_sys::{
+    ecx_eeprom2master, ecx_eeprom2pdi, ecx_readeeprom, ecx_siigetbyte, ecx_writeeeprom,
+};
+
+impl<'a> Context<'a> {
+    /// Give control over the EEPROM to the master
+    pub fn eeprom_to_master(&mut self, slave: u16) -> result::Result<(), EepromError> {
+        self.check_eeprom_slave(slave)?;
+        match unsafe { ecx_eeprom2master(&mut self.context, slave) } {
+            x if x > 0 => Ok(()),
+            _ => Err(EepromError::NoResponse),
+        }
+    }
+
+    /// Give control over the EEPROM to the slave application
+    pub fn eeprom_to_pdi(&mut self, slave: u16) -> result::Result<(), EepromError> {
+        self.check_eeprom_slave(slave)?;
+        match unsafe { ecx_eeprom2pdi(&mut self.context, slave) } {
+            x if x > 0 => Ok(()),
+            _ => Err(EepromError::NoResponse),
+        }
+    }
+
+    pub(crate) fn check_eeprom_slave(&self, slave: u16) -> result::Result<(), EepromError> {
+        match self.has_slave(slave) {
+            true => Ok(()),
+            false => Err(EepromError::UnknownSlave(slave)),
+        }
+    }
+
+    fn with_eeprom<R, F>(&mut self, slave: u16, f: F) -> result::Result<R, EepromError>
+    where
+        F: FnOnce(&mut Self) -> result::Result<R, EepromError>,
+    {
+        self.check_eeprom_slave(slave)?;
+        let pdi = self.slave_mut(slave).0.eep_pdi != 0;
+        self.eeprom_to_master(slave)?;
+        let res = f(self);
+        if pdi {
+            // Give the EEPROM back to the slave application as it was before, an error of the
+            // access itself is reported first
+            let restore = self.eeprom_to_pdi(slave);
+            return res.and_then(|x| restore.map(|_| x));
+        }
+        res
+    }
+
+    /// SOEM reads 0 when the access fails, so a zero is only trusted if the ESC reports the
+    /// EEPROM idle and without error
+    fn check_eeprom_read(&mut self, slave: u16, word_addr: u16) -> result::Result<(), EepromError> {
+        let status = self
+            .read_register::<SiiInterface>(slave)
+            .map_err(|_| EepromError::NoResponse)?;
+        if status.busy() || status.error() != 0 {
+            return Err(EepromError::ReadFailed(word_addr));
+        }
+        Ok(())
+    }
+
+    /// Read `len` bytes starting from the word address, bypassing the SII cache
+    pub fn read_eeprom(
+        &mut self,
+        slave: u16,
+        word_addr: u16,
+        len: usize,
+    ) -> result::Result<Vec<u8>, EepromError> {
+        if word_addr as usize + len.div_ceil(2) > 0x10000 {
+            return Err(EepromError::OutOfRange);
+        }
+
+        self.with_eeprom(slave, |c| {
+            let mut data = Vec::with_capacity(len + 3);
+            let mut addr = word_addr;
+            while data.len() < len {
+                let x = unsafe { ecx_readeeprom(&mut c.context, slave, addr, EC_TIMEOUTEEP) };
+                if x == 0 {
+                    c.check_eeprom_read(slave, addr)?;
+                }
+                data.extend_from_slice(&x.to_le_bytes());
+                addr = addr.wrapping_add(2);
+            }
+            data.truncate(len);
+            Ok(data)
+        })
+    }
+
+    /// Write `data` starting from the word address.
+    ///
+    /// Every word is verified by the ESC, the SII cache of the context is discarded afterwards.
+    pub fn write_eeprom(
+        &mut self,
+        slave: u16,
+        word_addr: u16,
+        data: &[u8],
+    ) -> result::Result<(), EepromError> {
+        self.check_eeprom_slave(slave)?;
+        if data.len() % 2 != 0 {
+            return Err(EepromError::OddLength(data.len()));
+        }
+        if word_addr as usize + data.len() / 2 > 0x10000 {
+            return Err(EepromError::OutOfRange);
+        }
+
+        let res = self.with_eeprom(slave, |c| {
+            data.chunks(2).enumerate().try_for_each(|(i, x)| {
+                let addr = word_addr.wrapping_add(i as u16);
+                let word = u16::from_le_bytes([x[0], x[1]]);
+                match unsafe { ecx_writeeeprom(&mut c.context, slave, addr, word, EC_TIMEOUTEEP) } {
+                    x if x > 0 => Ok(()),
+                    _ => Err(EepromError::WriteFailed(addr)),
+                }
+            })
+        });
+
+        self.clear_sii_cache();
+        res
+    }
+
+    /// Read a byte of the SII through the cache of the context.
+    ///
+    /// The cache holds the EEPROM of a single slave, reading another slave discards it.
+    pub fn sii_byte(&mut self, slave: u16, address: u16) -> result::Result<u8, EepromError> {
+        self.check_eeprom_slave(slave)?;
+        if address as usize >= EC_MAXEEPBUF {
+            return Err(EepromError::OutOfRange);
+        }
+        Ok(unsafe { ecx_siigetbyte(&mut self.context, slave, address) })
+    }
+
+    /// Discard the SII cache, e.g. after the EEPROM has been written
+    pub fn clear_sii_cache(&mut self) {
+        let esimap = unsafe { slice::from_raw_parts_mut(self.context.esimap, EC_MAXEEPBITMAP) };
+        esimap.iter_mut().for_each(|x| *x = 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::SiiError, tests::MockBuffers};
+
+    #[test]
+    fn unknown_slaves_are_rejected() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        let mut c = buffers.context();
+
+        for &slave in [0, 3, 0xffff].iter() {
+            let err = EepromError::UnknownSlave(slave);
+            assert_eq!(c.eeprom_to_master(slave).unwrap_err(), err);
+            assert_eq!(c.eeprom_to_pdi(slave).unwrap_err(), err);
+            assert_eq!(c.read_eeprom(slave, 0, 2).unwrap_err(), err);
+            assert_eq!(c.write_eeprom(slave, 0, &[0, 0]).unwrap_err(), err);
+            assert_eq!(c.sii_byte(slave, 0).unwrap_err(), err);
+            assert_eq!(c.read_sii_image(slave).unwrap_err(), err);
+            assert_eq!(
+                c.sii(slave),
+                Err(SiiError::EepromError(EepromError::UnknownSlave(slave)))
+            );
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use crate::{error::drain_errors, Context};
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use SOEM_sys::{ec_err_type_EC_ERR_TYPE_EMERGENCY, ec_errort, ecx_iserror};
+
+/// Error code classes as defined in CiA 301
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EmergencyClass {
+    /// Error reset or no error
+    NoError,
+    Generic,
+    Current,
+    CurrentInput,
+    CurrentInternal,
+    CurrentOutput,
+    Voltage,
+    VoltageMains,
+    VoltageInternal,
+    VoltageOutput,
+    Temperature,
+    TemperatureAmbient,
+    TemperatureDevice,
+    Hardware,
+    Software,
+    SoftwareInternal,
+    SoftwareUser,
+    SoftwareDataSet,
+    AdditionalModules,
+    Monitoring,
+    Communication,
+    ProtocolError,
+    External,
+    AdditionalFunctions,
+    DeviceSpecific,
+    /// Error code outside of the ranges defined by CiA 301
+    Unknown,
+}
+
+impl EmergencyClass {
+    pub fn from_error_code(code: u16) -> EmergencyClass {
+        match code >> 8 {
+            0x00 => EmergencyClass::NoError,
+            0x10 => EmergencyClass::Generic,
+            0x20 => EmergencyClass::Current,
+            0x21 => EmergencyClass::CurrentInput,
+            0x22 => EmergencyClass::CurrentInternal,
+            0x23 => EmergencyClass::CurrentOutput,
+            0x30 => EmergencyClass::Voltage,
+            0x31 => EmergencyClass::VoltageMains,
+            0x32 => EmergencyClass::VoltageInternal,
+            0x33 => EmergencyClass::VoltageOutput,
+            0x40 => EmergencyClass::Temperature,
+            0x41 => EmergencyClass::TemperatureAmbient,
+            0x42 => EmergencyClass::TemperatureDevice,
+            0x50 => EmergencyClass::Hardware,
+            0x60 => EmergencyClass::Software,
+            0x61 => EmergencyClass::SoftwareInternal,
+            0x62 => EmergencyClass::SoftwareUser,
+            0x63 => EmergencyClass::SoftwareDataSet,
+            0x70 => EmergencyClass::AdditionalModules,
+            0x81 => EmergencyClass::Communication,
+            0x82 => EmergencyClass::ProtocolError,
+            0x80..=0x8f => EmergencyClass::Monitoring,
+            0x90 => EmergencyClass::External,
+            0xf0 => EmergencyClass::AdditionalFunctions,
+            0xff => EmergencyClass::DeviceSpecific,
+            _ => EmergencyClass::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for EmergencyClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EmergencyClass::NoError => write!(f, "Error reset or no error"),
+            EmergencyClass::Generic => write!(f, "Generic error"),
+            EmergencyClass::Current => write!(f, "Current"),
+            EmergencyClass::CurrentInput => write!(f, "Current, device input side"),
+            EmergencyClass::CurrentInternal => write!(f, "Current, inside the device"),
+            EmergencyClass::CurrentOutput => write!(f, "Current, device output side"),
+            EmergencyClass::Voltage => write!(f, "Voltage"),
+            EmergencyClass::VoltageMains => write!(f, "Mains voltage"),
+            EmergencyClass::VoltageInternal => write!(f, "Voltage inside the device"),
+            EmergencyClass::VoltageOutput => write!(f, "Output voltage"),
+            EmergencyClass::Temperature => write!(f, "Temperature"),
+            EmergencyClass::TemperatureAmbient => write!(f, "Ambient temperature"),
+            EmergencyClass::TemperatureDevice => write!(f, "Device temperature"),
+            EmergencyClass::Hardware => write!(f, "Device hardware"),
+            EmergencyClass::Software => write!(f, "Device software"),
+            EmergencyClass::SoftwareInternal => write!(f, "Internal software"),
+            EmergencyClass::SoftwareUser => write!(f, "User software"),
+            EmergencyClass::SoftwareDataSet => write!(f, "Data set"),
+            EmergencyClass::AdditionalModules => write!(f, "Additional modules"),
+            EmergencyClass::Monitoring => write!(f, "Monitoring"),
+            EmergencyClass::Communication => write!(f, "Communication"),
+            EmergencyClass::ProtocolError => write!(f, "Protocol error"),
+            EmergencyClass::External => write!(f, "External error"),
+            EmergencyClass::AdditionalFunctions => write!(f, "Additional functions"),
+            EmergencyClass::DeviceSpecific => write!(f, "Device specific"),
+            EmergencyClass::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// CoE emergency message
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Emergency {
+    time: SystemTime,
+    slave: u16,
+    error_code: u16,
+    error_register: u8,
+    data: [u8; 5],
+}
+
+impl Emergency {
+    fn new(err: &ec_errort) -> Self {
+        let emcy = unsafe { err.__bindgen_anon_1.__bindgen_anon_1 };
+        let mut data = [0u8; 5];
+        data[0] = emcy.b1;
+        data[1..3].copy_from_slice(&emcy.w1.to_le_bytes());
+        data[3..5].copy_from_slice(&emcy.w2.to_le_bytes());
+
+        Emergency {
+            time: UNIX_EPOCH + Duration::new(err.Time.sec.into(), err.Time.usec * 1000),
+            slave: err.Slave,
+            error_code: emcy.ErrorCode,
+            error_register: emcy.ErrorReg,
+            data,
+        }
+    }
+
+    pub const fn time(&self) -> SystemTime {
+        self.time
+    }
+    pub const fn slave(&self) -> u16 {
+        self.slave
+    }
+    pub const fn error_code(&self) -> u16 {
+        self.error_code
+    }
+    pub fn class(&self) -> EmergencyClass {
+        EmergencyClass::from_error_code(self.error_code)
+    }
+    /// Error register, object 0x1001
+    pub const fn error_register(&self) -> u8 {
+        self.error_register
+    }
+    /// Manufacturer specific error field
+    pub const fn data(&self) -> &[u8; 5] {
+        &self.data
+    }
+}
+
+impl fmt::Display for Emergency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Emergency slave:{} error:{:04x} ({}) register:{:02x} data:",
+            self.slave,
+            self.error_code,
+            self.class(),
+            self.error_register
+        )?;
+        self.data.iter().try_for_each(|x| write!(f, "{:02x}", x))
+    }
+}
+
+pub(crate) struct EmergencyHandler<'a>(Box<dyn FnMut(&Emergency) + Send + 'a>);
+
+impl<'a> fmt::Debug for EmergencyHandler<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EmergencyHandler")
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Deliver CoE emergencies to `handler` instead of the error list.
+    ///
+    /// SOEM collects emergencies while serving mailboxes, they are handed over as soon as the
+    /// context checks for errors, i.e. after every mailbox request. The mailboxes are not read
+    /// during process data exchange, so a cyclic loop without mailbox traffic receives no
+    /// emergency until the next mailbox request, e.g. an SDO read.
+    pub fn on_emergency<F: FnMut(&Emergency) + Send + 'a>(&mut self, handler: F) {
+        self.emergency_handler = Some(EmergencyHandler(Box::new(handler)));
+    }
+
+    pub fn clear_emergency_handler(&mut self) {
+        self.emergency_handler = None;
+    }
+
+    pub(crate) fn dispatch_emergencies(&mut self) {
+        let handler = match self.emergency_handler {
+            Some(ref mut handler) => handler,
+            None => return,
+        };
+
+        if unsafe { ecx_iserror(&mut self.context) } == 0 {
+            return;
+        }
+
+        // Everything else stays in the list for ErrorIterator
+        drain_errors(&mut self.context, |err| {
+            if err.Etype != ec_err_type_EC_ERR_TYPE_EMERGENCY {
+                return false;
+            }
+            (handler.0)(&Emergency::new(err));
+            true
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_error_codes() {
+        assert_eq!(
+            EmergencyClass::from_error_code(0x0000),
+            EmergencyClass::NoError
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x1000),
+            EmergencyClass::Generic
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x2310),
+            EmergencyClass::CurrentOutput
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x3220),
+            EmergencyClass::VoltageInternal
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x4210),
+            EmergencyClass::TemperatureDevice
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x6320),
+            EmergencyClass::SoftwareDataSet
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x8110),
+            EmergencyClass::Communication
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x8210),
+            EmergencyClass::ProtocolError
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x8611),
+            EmergencyClass::Monitoring
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x9000),
+            EmergencyClass::External
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0xff01),
+            EmergencyClass::DeviceSpecific
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0x2400),
+            EmergencyClass::Unknown
+        );
+        assert_eq!(
+            EmergencyClass::from_error_code(0xa000),
+            EmergencyClass::Unknown
+        );
+    }
+}
@@ -0,0 +1,987 @@
+use crate::{
+    error::{EniError, XmlError},
+    xml, Context, ElementFlags, ExpectedNetwork, ExpectedSlave, Idn, Transition, EC_TIMEOUTRET,
+    EC_TIMEOUTRXM,
+};
+use roxmltree::{Document, Node};
+use std::{
+    os::raw::c_int,
+    result, thread,
+    time::{Duration, Instant},
+};
+
+/// Entry of a PDO
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniPdoEntry {
+    index: u16,
+    subindex: u8,
+    bit_len: u16,
+    name: String,
+    data_type: Option<String>,
+}
+
+impl EniPdoEntry {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub const fn subindex(&self) -> u8 {
+        self.subindex
+    }
+    pub const fn bit_len(&self) -> u16 {
+        self.bit_len
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn data_type(&self) -> Option<&str> {
+        self.data_type.as_deref()
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EniPdoEntry {
+            index: xml::number(node, "Index")?,
+            subindex: xml::opt_number(node, "SubIndex")?.unwrap_or(0),
+            bit_len: xml::number(node, "BitLen")?,
+            name: xml::text(node, "Name").unwrap_or_default().to_owned(),
+            data_type: xml::text(node, "DataType").map(str::to_owned),
+        })
+    }
+}
+
+/// TxPDO or RxPDO of a slave
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniPdo {
+    index: u16,
+    name: String,
+    sm: Option<u8>,
+    fixed: bool,
+    entries: Vec<EniPdoEntry>,
+}
+
+impl EniPdo {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Sync manager the PDO is assigned to, `None` if not assigned
+    pub const fn sm(&self) -> Option<u8> {
+        self.sm
+    }
+    /// Mapping of the PDO cannot be changed
+    pub const fn fixed(&self) -> bool {
+        self.fixed
+    }
+    pub fn entries(&self) -> &[EniPdoEntry] {
+        &self.entries
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EniPdo {
+            index: xml::number(node, "Index")?,
+            name: xml::text(node, "Name").unwrap_or_default().to_owned(),
+            sm: xml::opt_attribute(node, "Sm")?,
+            fixed: node.attribute("Fixed").is_some_and(xml::parse_bool),
+            entries: xml::children(node, "Entry")
+                .map(EniPdoEntry::parse)
+                .collect::<result::Result<_, _>>()?,
+        })
+    }
+}
+
+/// Sync manager of a slave with the assigned PDOs
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniSyncManager {
+    number: u8,
+    start_address: u16,
+    size: u16,
+    control_byte: u8,
+    enable: bool,
+    pdos: Vec<u16>,
+}
+
+impl EniSyncManager {
+    pub const fn number(&self) -> u8 {
+        self.number
+    }
+    pub const fn start_address(&self) -> u16 {
+        self.start_address
+    }
+    pub const fn size(&self) -> u16 {
+        self.size
+    }
+    pub const fn control_byte(&self) -> u8 {
+        self.control_byte
+    }
+    pub const fn enable(&self) -> bool {
+        self.enable
+    }
+    /// Indices of the PDOs assigned to the sync manager
+    pub fn pdos(&self) -> &[u16] {
+        &self.pdos
+    }
+
+    fn parse(number: u8, node: Node) -> result::Result<Self, XmlError> {
+        Ok(EniSyncManager {
+            number,
+            start_address: xml::opt_number(node, "StartAddress")?.unwrap_or(0),
+            size: xml::opt_number(node, "Size")?.unwrap_or(0),
+            control_byte: xml::opt_number(node, "ControlByte")?.unwrap_or(0),
+            enable: xml::text(node, "Enable").is_some_and(xml::parse_bool),
+            pdos: xml::children(node, "Pdo")
+                .filter_map(|x| x.text())
+                .map(|x| {
+                    xml::parse_number(x)
+                        .ok_or_else(|| XmlError::InvalidValue("Pdo".into(), x.into()))
+                })
+                .collect::<result::Result<_, _>>()?,
+        })
+    }
+}
+
+/// Data expected to be read back by a register init command
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniValidate {
+    data: Vec<u8>,
+    mask: Option<Vec<u8>>,
+    timeout: Option<u32>,
+}
+
+impl EniValidate {
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    /// Bits to compare, every bit is compared without a mask
+    pub fn mask(&self) -> Option<&[u8]> {
+        self.mask.as_deref()
+    }
+    /// Time in ms to repeat the read until the data matches, read once without a timeout
+    pub const fn timeout(&self) -> Option<u32> {
+        self.timeout
+    }
+
+    /// Compare the masked data, bytes beyond the mask are compared as a whole
+    pub fn matches(&self, read: &[u8]) -> bool {
+        let mask = self.mask.as_deref().unwrap_or_default();
+        read.len() == self.data.len()
+            && read
+                .iter()
+                .zip(self.data.iter())
+                .enumerate()
+                .all(|(i, (x, y))| {
+                    let m = mask.get(i).copied().unwrap_or(0xff);
+                    x & m == y & m
+                })
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        let mask = match xml::hex_data(node, "DataMask")? {
+            x if x.is_empty() => None,
+            x => Some(x),
+        };
+        Ok(EniValidate {
+            data: xml::hex_data(node, "Data")?,
+            mask,
+            timeout: xml::opt_number(node, "Timeout")?.filter(|&x| x != 0),
+        })
+    }
+}
+
+/// Command of an [`EniInitCmd`]
+#[derive(Debug, PartialEq, Clone)]
+pub enum EniCommand {
+    /// ESC register access, `cmd` is the EtherCat command type such as 5 for FPWR
+    Register {
+        cmd: u8,
+        adp: u16,
+        ado: u16,
+        data: Vec<u8>,
+        /// Expected working counter
+        cnt: Option<u16>,
+        /// Data expected to be read back
+        validate: Option<EniValidate>,
+    },
+    /// SDO access, `ccs` 1 for download and 2 for upload
+    CoE {
+        ccs: u8,
+        index: u16,
+        subindex: u8,
+        complete_access: bool,
+        data: Vec<u8>,
+    },
+    /// IDN access, `op_code` 3 for write
+    SoE {
+        op_code: u8,
+        drive: u8,
+        idn: u16,
+        elements: u8,
+        data: Vec<u8>,
+    },
+}
+
+/// Command executed on a state transition
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniInitCmd {
+    transitions: Vec<Transition>,
+    comment: Option<String>,
+    timeout: Option<u32>,
+    command: EniCommand,
+}
+
+impl EniInitCmd {
+    pub fn transitions(&self) -> &[Transition] {
+        &self.transitions
+    }
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+    /// Timeout in ms
+    pub const fn timeout(&self) -> Option<u32> {
+        self.timeout
+    }
+    pub const fn command(&self) -> &EniCommand {
+        &self.command
+    }
+
+    fn parse(node: Node, command: EniCommand) -> result::Result<Self, XmlError> {
+        Ok(EniInitCmd {
+            transitions: xml::children(node, "Transition")
+                .filter_map(|x| x.text())
+                .map(|x| {
+                    x.parse()
+                        .map_err(|_| XmlError::InvalidValue("Transition".into(), x.into()))
+                })
+                .collect::<result::Result<_, _>>()?,
+            comment: xml::text(node, "Comment").map(str::to_owned),
+            timeout: xml::opt_number(node, "Timeout")?.filter(|&x| x != 0),
+            command,
+        })
+    }
+
+    fn parse_register(node: Node) -> result::Result<Self, XmlError> {
+        let mut data = xml::hex_data(node, "Data")?;
+        if data.is_empty() {
+            data.resize(xml::opt_number(node, "DataLength")?.unwrap_or(0), 0);
+        }
+        let validate = match xml::child(node, "Validate") {
+            Some(x) => Some(EniValidate::parse(x)?),
+            None => None,
+        };
+        let command = EniCommand::Register {
+            cmd: xml::number(node, "Cmd")?,
+            adp: xml::opt_number::<i32>(node, "Adp")?.unwrap_or(0) as u16,
+            ado: xml::number(node, "Ado")?,
+            data,
+            cnt: xml::opt_number(node, "Cnt")?,
+            validate,
+        };
+        EniInitCmd::parse(node, command)
+    }
+
+    fn parse_coe(node: Node) -> result::Result<Self, XmlError> {
+        let command = EniCommand::CoE {
+            ccs: xml::number(node, "Ccs")?,
+            index: xml::number(node, "Index")?,
+            subindex: xml::opt_number(node, "SubIndex")?.unwrap_or(0),
+            complete_access: node
+                .attribute("CompleteAccess")
+                .is_some_and(xml::parse_bool),
+            data: xml::hex_data(node, "Data")?,
+        };
+        EniInitCmd::parse(node, command)
+    }
+
+    fn parse_soe(node: Node) -> result::Result<Self, XmlError> {
+        let command = EniCommand::SoE {
+            op_code: xml::number(node, "OpCode")?,
+            drive: xml::opt_number(node, "DriveNo")?.unwrap_or(0),
+            idn: xml::number(node, "IDN")?,
+            elements: xml::opt_number(node, "Elements")?.unwrap_or(ElementFlags::VALUE.bits()),
+            data: xml::hex_data(node, "Data")?,
+        };
+        EniInitCmd::parse(node, command)
+    }
+}
+
+/// Distributed clock settings of a slave
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct EniDc {
+    reference_clock: bool,
+    cycle_time0: u32,
+    cycle_time1: u32,
+    shift_time: i32,
+}
+
+impl EniDc {
+    pub const fn reference_clock(&self) -> bool {
+        self.reference_clock
+    }
+    /// SYNC0 cycle time in ns, 0 if SYNC0 is not used
+    pub const fn cycle_time0(&self) -> u32 {
+        self.cycle_time0
+    }
+    /// Delay of SYNC1 after SYNC0 in ns
+    pub const fn cycle_time1(&self) -> u32 {
+        self.cycle_time1
+    }
+    pub const fn shift_time(&self) -> i32 {
+        self.shift_time
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EniDc {
+            reference_clock: xml::text(node, "ReferenceClock").is_some_and(xml::parse_bool),
+            cycle_time0: xml::opt_number(node, "CycleTime0")?.unwrap_or(0),
+            cycle_time1: xml::opt_number(node, "CycleTime1")?.unwrap_or(0),
+            shift_time: xml::opt_number(node, "ShiftTime")?.unwrap_or(0),
+        })
+    }
+}
+
+/// Slave of the ENI in the processing order
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniSlave {
+    name: String,
+    phys_addr: u16,
+    auto_inc_addr: u16,
+    vendor_id: u32,
+    product_code: u32,
+    revision: Option<u32>,
+    serial_number: Option<u32>,
+    output_bit_start: Option<u32>,
+    output_bit_length: u32,
+    input_bit_start: Option<u32>,
+    input_bit_length: u32,
+    sync_managers: Vec<EniSyncManager>,
+    tx_pdos: Vec<EniPdo>,
+    rx_pdos: Vec<EniPdo>,
+    init_cmds: Vec<EniInitCmd>,
+    dc: Option<EniDc>,
+}
+
+impl EniSlave {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Configured station address
+    pub const fn phys_addr(&self) -> u16 {
+        self.phys_addr
+    }
+    pub const fn auto_inc_addr(&self) -> u16 {
+        self.auto_inc_addr
+    }
+    pub const fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+    pub const fn product_code(&self) -> u32 {
+        self.product_code
+    }
+    pub const fn revision(&self) -> Option<u32> {
+        self.revision
+    }
+    pub const fn serial_number(&self) -> Option<u32> {
+        self.serial_number
+    }
+    /// Bit offset of the outputs in the output process image, see
+    /// [`Context::verify_eni_process_image`]
+    pub const fn output_bit_start(&self) -> Option<u32> {
+        self.output_bit_start
+    }
+    pub const fn output_bit_length(&self) -> u32 {
+        self.output_bit_length
+    }
+    /// Bit offset of the inputs in the input process image
+    pub const fn input_bit_start(&self) -> Option<u32> {
+        self.input_bit_start
+    }
+    pub const fn input_bit_length(&self) -> u32 {
+        self.input_bit_length
+    }
+    pub fn sync_managers(&self) -> &[EniSyncManager] {
+        &self.sync_managers
+    }
+    pub fn tx_pdos(&self) -> &[EniPdo] {
+        &self.tx_pdos
+    }
+    pub fn rx_pdos(&self) -> &[EniPdo] {
+        &self.rx_pdos
+    }
+    /// Register, CoE and SoE init commands in the order of the ENI
+    pub fn init_cmds(&self) -> &[EniInitCmd] {
+        &self.init_cmds
+    }
+    pub const fn dc(&self) -> Option<&EniDc> {
+        self.dc.as_ref()
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        let info = xml::child(node, "Info").ok_or_else(|| XmlError::Missing("Info".into()))?;
+        let mut slave = EniSlave {
+            name: xml::text(info, "Name").unwrap_or_default().to_owned(),
+            phys_addr: xml::opt_number(info, "PhysAddr")?.unwrap_or(0),
+            auto_inc_addr: xml::opt_number::<i32>(info, "AutoIncAddr")?.unwrap_or(0) as u16,
+            vendor_id: xml::number(info, "VendorId")?,
+            product_code: xml::number(info, "ProductCode")?,
+            revision: xml::opt_number(info, "RevisionNo")?,
+            serial_number: xml::opt_number(info, "SerialNo")?,
+            output_bit_start: None,
+            output_bit_length: 0,
+            input_bit_start: None,
+            input_bit_length: 0,
+            sync_managers: Vec::new(),
+            tx_pdos: Vec::new(),
+            rx_pdos: Vec::new(),
+            init_cmds: Vec::new(),
+            dc: None,
+        };
+
+        if let Some(pd) = xml::child(node, "ProcessData") {
+            if let Some(x) = xml::child(pd, "Send") {
+                slave.output_bit_start = xml::opt_number(x, "BitStart")?;
+                slave.output_bit_length = xml::opt_number(x, "BitLength")?.unwrap_or(0);
+            }
+            if let Some(x) = xml::child(pd, "Recv") {
+                slave.input_bit_start = xml::opt_number(x, "BitStart")?;
+                slave.input_bit_length = xml::opt_number(x, "BitLength")?.unwrap_or(0);
+            }
+            for x in pd.children().filter(|x| x.is_element()) {
+                let tag = x.tag_name().name();
+                match tag {
+                    "TxPdo" => slave.tx_pdos.push(EniPdo::parse(x)?),
+                    "RxPdo" => slave.rx_pdos.push(EniPdo::parse(x)?),
+                    _ => {
+                        if let Some(n) = tag.strip_prefix("Sm").and_then(|n| n.parse().ok()) {
+                            slave.sync_managers.push(EniSyncManager::parse(n, x)?);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(cmds) = xml::child(node, "InitCmds") {
+            for x in xml::children(cmds, "InitCmd") {
+                slave.init_cmds.push(EniInitCmd::parse_register(x)?);
+            }
+        }
+        if let Some(mbx) = xml::child(node, "Mailbox") {
+            let coe = xml::child(mbx, "CoE").and_then(|x| xml::child(x, "InitCmds"));
+            for x in coe.iter().flat_map(|&x| xml::children(x, "InitCmd")) {
+                slave.init_cmds.push(EniInitCmd::parse_coe(x)?);
+            }
+            let soe = xml::child(mbx, "SoE").and_then(|x| xml::child(x, "InitCmds"));
+            for x in soe.iter().flat_map(|&x| xml::children(x, "InitCmd")) {
+                slave.init_cmds.push(EniInitCmd::parse_soe(x)?);
+            }
+        }
+
+        if let Some(x) = xml::child(node, "DC") {
+            slave.dc = Some(EniDc::parse(x)?);
+        }
+
+        Ok(slave)
+    }
+}
+
+/// Variable of the process image
+#[derive(Debug, PartialEq, Clone)]
+pub struct EniVariable {
+    name: String,
+    data_type: Option<String>,
+    bit_size: u32,
+    bit_offset: u32,
+}
+
+impl EniVariable {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn data_type(&self) -> Option<&str> {
+        self.data_type.as_deref()
+    }
+    pub const fn bit_size(&self) -> u32 {
+        self.bit_size
+    }
+    /// Offset in the input or output process image
+    pub const fn bit_offset(&self) -> u32 {
+        self.bit_offset
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EniVariable {
+            name: xml::text(node, "Name").unwrap_or_default().to_owned(),
+            data_type: xml::text(node, "DataType").map(str::to_owned),
+            bit_size: xml::number(node, "BitSize")?,
+            bit_offset: xml::number(node, "BitOffs")?,
+        })
+    }
+}
+
+/// EtherCat network information as exported by engineering tools
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Eni {
+    slaves: Vec<EniSlave>,
+    input_size: u32,
+    output_size: u32,
+    inputs: Vec<EniVariable>,
+    outputs: Vec<EniVariable>,
+}
+
+impl Eni {
+    pub fn slaves(&self) -> &[EniSlave] {
+        &self.slaves
+    }
+    /// Size of the input process image in bytes
+    pub const fn input_size(&self) -> u32 {
+        self.input_size
+    }
+    pub const fn output_size(&self) -> u32 {
+        self.output_size
+    }
+    pub fn inputs(&self) -> &[EniVariable] {
+        &self.inputs
+    }
+    pub fn outputs(&self) -> &[EniVariable] {
+        &self.outputs
+    }
+
+    pub fn parse(text: &str) -> result::Result<Eni, XmlError> {
+        let doc = Document::parse(text).map_err(|err| XmlError::Parse(err.to_string()))?;
+        let root = doc.root_element();
+        let config = match root.tag_name().name() {
+            "Config" => root,
+            _ => xml::child(root, "Config").ok_or_else(|| XmlError::Missing("Config".into()))?,
+        };
+
+        let mut eni = Eni {
+            slaves: xml::children(config, "Slave")
+                .map(EniSlave::parse)
+                .collect::<result::Result<_, _>>()?,
+            ..Default::default()
+        };
+
+        if let Some(image) = xml::child(config, "ProcessImage") {
+            if let Some(x) = xml::child(image, "Inputs") {
+                eni.input_size = xml::opt_number(x, "ByteSize")?.unwrap_or(0);
+                eni.inputs = xml::children(x, "Variable")
+                    .map(EniVariable::parse)
+                    .collect::<result::Result<_, _>>()?;
+            }
+            if let Some(x) = xml::child(image, "Outputs") {
+                eni.output_size = xml::opt_number(x, "ByteSize")?.unwrap_or(0);
+                eni.outputs = xml::children(x, "Variable")
+                    .map(EniVariable::parse)
+                    .collect::<result::Result<_, _>>()?;
+            }
+        }
+
+        Ok(eni)
+    }
+
+    /// Slave list of the ENI to be checked by [`Context::verify_config`]
+    pub fn expected_network(&self) -> ExpectedNetwork {
+        let mut res = ExpectedNetwork::new();
+        for x in self.slaves.iter() {
+            let mut slave = ExpectedSlave::new(x.vendor_id, x.product_code);
+            slave.set_name(&x.name);
+            if let Some(revision) = x.revision {
+                slave.set_revision(revision);
+            }
+            res.push(slave);
+        }
+        res
+    }
+}
+
+/// Bit offset of the slave data from the start of the group image
+fn bit_offset(data: *const u8, start: *const u8, start_bit: u8) -> Option<u32> {
+    if data.is_null() || start.is_null() {
+        return None;
+    }
+    let bytes = (data as usize).checked_sub(start as usize)?;
+    Some(bytes as u32 * 8 + start_bit as u32)
+}
+
+fn timeout(cmd: &EniInitCmd, default: c_int) -> c_int {
+    cmd.timeout
+        .map_or(default, |x| (x as c_int).saturating_mul(1000))
+}
+
+impl<'a> Context<'a> {
+    fn check_eni_slaves(&self, eni: &Eni) -> result::Result<(), EniError> {
+        let slaves = self.slaves();
+        for (i, x) in eni.slaves.iter().enumerate() {
+            let same = slaves.get(i).is_some_and(|s| {
+                s.eep_manufacturer() == x.vendor_id && s.eep_id() == x.product_code
+            });
+            if !same {
+                return Err(EniError::SlaveMismatch(i as u16 + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the CoE and SoE init commands of the transition.
+    ///
+    /// PDO assignment and mapping are part of the PS commands, so they have to be applied in
+    /// PreOp before [`Context::config_map_group`] reads the mapping back.
+    pub fn apply_eni(&mut self, eni: &Eni, transition: Transition) -> result::Result<(), EniError> {
+        self.check_eni_slaves(eni)?;
+
+        for (i, x) in eni.slaves.iter().enumerate() {
+            let slave = i as u16 + 1;
+            for cmd in x
+                .init_cmds
+                .iter()
+                .filter(|c| c.transitions.contains(&transition))
+            {
+                match cmd.command {
+                    EniCommand::CoE {
+                        ccs: 1,
+                        index,
+                        subindex,
+                        complete_access,
+                        ref data,
+                    } => self
+                        .write_sdo_bytes(
+                            slave,
+                            index,
+                            subindex,
+                            complete_access,
+                            data,
+                            timeout(cmd, EC_TIMEOUTRXM),
+                        )
+                        .map_err(|err| EniError::ErrorList(slave, err.collect()))?,
+                    EniCommand::SoE {
+                        op_code: 3,
+                        drive,
+                        idn,
+                        elements,
+                        ref data,
+                    } => self
+                        .soe_write(
+                            slave,
+                            drive,
+                            ElementFlags::from_bits(elements),
+                            Idn::from(idn),
+                            data,
+                            timeout(cmd, EC_TIMEOUTRXM),
+                        )
+                        .map_err(|err| EniError::SoEError(slave, err))?,
+                    _ => (),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Run the register init commands of the transition.
+    ///
+    /// SOEM sets up sync managers, FMMUs and states on its own, so these commands are useful for
+    /// slave specific registers only. Reads are executed when the ENI gives data to validate,
+    /// they are repeated until the masked data matches or the validation timeout expires.
+    pub fn apply_eni_registers(
+        &mut self,
+        eni: &Eni,
+        transition: Transition,
+    ) -> result::Result<(), EniError> {
+        self.check_eni_slaves(eni)?;
+
+        for (i, x) in eni.slaves.iter().enumerate() {
+            let slave = i as u16 + 1;
+            for cmd in x
+                .init_cmds
+                .iter()
+                .filter(|c| c.transitions.contains(&transition))
+            {
+                let (kind, adp, ado, data, cnt, validate) = match cmd.command {
+                    EniCommand::Register {
+                        cmd,
+                        adp,
+                        ado,
+                        ref data,
+                        cnt,
+                        ref validate,
+                    } => (cmd, adp, ado, data, cnt, validate),
+                    _ => continue,
+                };
+                let t = timeout(cmd, EC_TIMEOUTRET);
+                let position = 0u16.wrapping_sub(adp);
+                let start = Instant::now();
+
+                loop {
+                    let res = match (kind, validate) {
+                        (1, Some(v)) => self.aprd(position, ado, v.data.len(), t),
+                        (4, Some(v)) => self.fprd(adp, ado, v.data.len(), t),
+                        (7, Some(v)) => self.brd(ado, v.data.len(), t),
+                        (2, _) => self.apwr(position, ado, data, t).map(|w| (Vec::new(), w)),
+                        (5, _) => self.fpwr(adp, ado, data, t).map(|w| (Vec::new(), w)),
+                        (8, _) => self.bwr(ado, data, t).map(|w| (Vec::new(), w)),
+                        _ => break,
+                    };
+                    let (read, wkc) = res.map_err(|err| EniError::DatagramError(slave, err))?;
+
+                    match cnt {
+                        Some(c) if c != wkc => {
+                            return Err(EniError::DatagramError(
+                                slave,
+                                crate::DatagramError::WorkingCounter(wkc),
+                            ))
+                        }
+                        _ => (),
+                    }
+                    match validate {
+                        Some(v) if [1, 4, 7].contains(&kind) && !v.matches(&read) => {
+                            let limit = Duration::from_millis(v.timeout.unwrap_or(0).into());
+                            if start.elapsed() >= limit {
+                                return Err(EniError::ValidationFailed(slave));
+                            }
+                            thread::sleep(Duration::from_millis(1));
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare the process data offsets and sizes of the ENI with the IO map laid out by
+    /// [`Context::config_map_group`].
+    ///
+    /// SOEM maps the slaves on its own, so the ENI offsets cannot be applied. Offsets are relative
+    /// to the outputs and inputs of the group of the slave.
+    pub fn verify_eni_process_image(&self, eni: &Eni) -> result::Result<(), EniError> {
+        self.check_eni_slaves(eni)?;
+
+        let groups = self.groups();
+        for (i, (x, s)) in eni.slaves.iter().zip(self.slaves().iter()).enumerate() {
+            let group = match groups.get(s.group() as usize) {
+                Some(g) => g,
+                None => return Err(EniError::ProcessImageMismatch(i as u16 + 1)),
+            };
+            let outputs = bit_offset(s.0.outputs, group.0.outputs, s.0.Ostartbit);
+            let inputs = bit_offset(s.0.inputs, group.0.inputs, s.0.Istartbit);
+            let same = x.output_bit_length == s.output_size() as u32
+                && x.input_bit_length == s.input_size() as u32
+                && x.output_bit_start.map_or(true, |b| Some(b) == outputs)
+                && x.input_bit_start.map_or(true, |b| Some(b) == inputs);
+            if !same {
+                return Err(EniError::ProcessImageMismatch(i as u16 + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Configure SYNC0 and SYNC1 of the slaves as given by the ENI, after [`Context::config_dc`]
+    pub fn apply_eni_dc(&mut self, eni: &Eni) -> result::Result<(), EniError> {
+        self.check_eni_slaves(eni)?;
+
+        for (i, x) in eni.slaves.iter().enumerate() {
+            let slave = i as u16 + 1;
+            match x.dc {
+                Some(dc) if dc.cycle_time0 != 0 && dc.cycle_time1 != 0 => {
+                    self.dc_sync01(slave, true, dc.cycle_time0, dc.cycle_time1, dc.shift_time)
+                }
+                Some(dc) if dc.cycle_time0 != 0 => {
+                    self.dc_sync0(slave, true, dc.cycle_time0, dc.shift_time)
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::MockBuffers, EtherCatState, Slave};
+
+    const ENI: &str = include_str!("testdata/eni.xml");
+
+    #[test]
+    fn parse_slaves() {
+        let eni = Eni::parse(ENI).unwrap();
+        assert_eq!(eni.slaves().len(), 2);
+
+        let coupler = &eni.slaves()[0];
+        assert_eq!(coupler.name(), "Term 1 (EK1100)");
+        assert_eq!(coupler.phys_addr(), 1001);
+        assert_eq!(coupler.product_code(), 0x044c_2c52);
+        assert_eq!(coupler.revision(), Some(0x0012_0000));
+        assert_eq!(coupler.output_bit_start(), None);
+        assert!(coupler.dc().is_none());
+
+        let terminal = &eni.slaves()[1];
+        assert_eq!(terminal.auto_inc_addr(), 0xffff);
+        assert_eq!(terminal.vendor_id(), 2);
+        assert_eq!(terminal.product_code(), 0x07d8_3052);
+        assert_eq!(terminal.serial_number(), None);
+        assert_eq!(terminal.output_bit_start(), Some(0));
+        assert_eq!(terminal.output_bit_length(), 8);
+        assert_eq!(terminal.input_bit_length(), 0);
+
+        let dc = terminal.dc().unwrap();
+        assert!(!dc.reference_clock());
+        assert_eq!(dc.cycle_time0(), 1_000_000);
+        assert_eq!(dc.shift_time(), -500);
+    }
+
+    #[test]
+    fn parse_process_data() {
+        let eni = Eni::parse(ENI).unwrap();
+        let terminal = &eni.slaves()[1];
+
+        let sm = &terminal.sync_managers()[0];
+        assert_eq!(sm.number(), 0);
+        assert_eq!(sm.start_address(), 0x0f00);
+        assert_eq!(sm.control_byte(), 0x44);
+        assert!(sm.enable());
+        assert_eq!(sm.pdos(), &[0x1600]);
+
+        let pdo = &terminal.rx_pdos()[0];
+        assert_eq!(pdo.index(), 0x1600);
+        assert_eq!(pdo.sm(), Some(0));
+        assert!(pdo.fixed());
+        assert_eq!(pdo.entries().len(), 2);
+        assert_eq!(pdo.entries()[0].index(), 0x7000);
+        assert_eq!(pdo.entries()[0].data_type(), Some("BOOL"));
+        assert_eq!(pdo.entries()[1].subindex(), 0);
+        assert_eq!(pdo.entries()[1].bit_len(), 7);
+
+        assert_eq!(eni.output_size(), 1);
+        assert_eq!(eni.outputs()[0].name(), "Term 2 (EL2008).Channel 1.Output");
+        assert_eq!(eni.outputs()[0].bit_size(), 1);
+        assert!(eni.inputs().is_empty());
+    }
+
+    #[test]
+    fn parse_init_cmds() {
+        let eni = Eni::parse(ENI).unwrap();
+
+        let cmd = &eni.slaves()[0].init_cmds()[0];
+        assert_eq!(
+            cmd.transitions(),
+            &[Transition::new(EtherCatState::Init, EtherCatState::PreOp)]
+        );
+        assert_eq!(cmd.timeout(), None);
+        let validate = match cmd.command() {
+            EniCommand::Register {
+                cmd: 1,
+                adp: 0,
+                ado: 0x0502,
+                cnt: Some(1),
+                validate: Some(v),
+                ..
+            } => v,
+            x => panic!("unexpected command {:?}", x),
+        };
+        assert_eq!(validate.mask(), Some(&[0x00, 0x80][..]));
+        assert_eq!(validate.timeout(), Some(100));
+
+        let cmd = &eni.slaves()[1].init_cmds()[0];
+        assert_eq!(cmd.comment(), Some("clear sm pdos (0x1C12)"));
+        assert_eq!(cmd.timeout(), None);
+        assert_eq!(
+            cmd.command(),
+            &EniCommand::CoE {
+                ccs: 1,
+                index: 0x1c12,
+                subindex: 0,
+                complete_access: true,
+                data: vec![0x01, 0x00, 0x00, 0x16],
+            }
+        );
+    }
+
+    #[test]
+    fn validation_mask() {
+        let v = EniValidate {
+            data: vec![0x00, 0x00],
+            mask: Some(vec![0x00, 0x80]),
+            timeout: None,
+        };
+        assert!(v.matches(&[0x12, 0x7f]));
+        assert!(!v.matches(&[0x00, 0x80]));
+        assert!(!v.matches(&[0x00]));
+
+        let v = EniValidate {
+            data: vec![0x08, 0x02],
+            mask: Some(vec![0x0f]),
+            timeout: None,
+        };
+        assert!(v.matches(&[0x18, 0x02]));
+        assert!(!v.matches(&[0x18, 0x03]));
+    }
+
+    #[test]
+    fn expected_network() {
+        let eni = Eni::parse(ENI).unwrap();
+        let expected = eni.expected_network();
+        assert_eq!(expected.slaves().len(), 2);
+        assert_eq!(expected.slaves()[0].name(), Some("Term 1 (EK1100)"));
+        assert_eq!(expected.slaves()[0].product_code(), 0x044c_2c52);
+        assert_eq!(expected.slaves()[1].revision(), Some(0x0010_0000));
+        assert_eq!(expected.slaves()[1].parent(), None);
+    }
+
+    #[test]
+    fn malformed_eni() {
+        assert!(matches!(
+            Eni::parse("<Config><Slave></Config>"),
+            Err(XmlError::Parse(_))
+        ));
+        assert_eq!(
+            Eni::parse("<Root/>"),
+            Err(XmlError::Missing("Config".into()))
+        );
+        assert_eq!(
+            Eni::parse("<Config><Slave><Info><VendorId>-2</VendorId></Info></Slave></Config>"),
+            Err(XmlError::InvalidValue("VendorId".into(), "-2".into()))
+        );
+    }
+
+    fn mapped_slaves(outputs: *mut u8) -> Vec<Slave> {
+        let mut slaves: Vec<Slave> = (0..3).map(|_| Default::default()).collect();
+        for (slave, &id) in slaves[1..]
+            .iter_mut()
+            .zip([0x044c_2c52, 0x07d8_3052].iter())
+        {
+            slave.0.eep_man = 2;
+            slave.0.eep_id = id;
+        }
+        slaves[2].0.Obits = 8;
+        slaves[2].0.Obytes = 1;
+        slaves[2].0.outputs = outputs;
+        slaves
+    }
+
+    #[test]
+    fn process_image_offsets() {
+        let eni = Eni::parse(ENI).unwrap();
+        let mut io_map = [0u8; 2];
+        let base = io_map.as_mut_ptr();
+
+        let mut buffers = MockBuffers::new(mapped_slaves(base), 1);
+        buffers.groups_mut()[0].0.outputs = base;
+        assert!(buffers.context().verify_eni_process_image(&eni).is_ok());
+
+        // Mapped a byte further than the ENI expects
+        let mut buffers = MockBuffers::new(mapped_slaves(base.wrapping_add(1)), 1);
+        buffers.groups_mut()[0].0.outputs = base;
+        assert!(matches!(
+            buffers.context().verify_eni_process_image(&eni),
+            Err(EniError::ProcessImageMismatch(2))
+        ));
+
+        let mut slaves = mapped_slaves(base);
+        slaves[2].0.Obits = 4;
+        let mut buffers = MockBuffers::new(slaves, 1);
+        buffers.groups_mut()[0].0.outputs = base;
+        assert!(matches!(
+            buffers.context().verify_eni_process_image(&eni),
+            Err(EniError::ProcessImageMismatch(2))
+        ));
+    }
+}
@@ -0,0 +1,412 @@
+use crate::{error::EoEError, Context};
+use std::{
+    any::Any,
+    collections::HashMap,
+    ffi::c_void,
+    fmt,
+    mem::zeroed,
+    net::Ipv4Addr,
+    os::raw::{c_char, c_int},
+    panic,
+    panic::AssertUnwindSafe,
+    result,
+    sync::Mutex,
+};
+use SOEM_sys::{
+    ec_mbxbuft, ecx_EOEdefinehook, ecx_EOEgetIp, ecx_EOEreadfragment, ecx_EOErecv, ecx_EOEsend,
+    ecx_EOEsetIp, ecx_contextt, eoe_param_t,
+};
+
+/** Largest frame the EoE header is able to describe */
+const EOE_FRAME_MAX: usize = 0x3f << 5;
+/** Length of the DNS name in the IP parameters */
+const EOE_DNS_NAME_LENGTH: usize = 32;
+
+/// IP parameters of an EoE port, unset ones are neither written nor reported by the slave
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct EoEParam {
+    mac: Option<[u8; 6]>,
+    ip: Option<Ipv4Addr>,
+    subnet: Option<Ipv4Addr>,
+    default_gateway: Option<Ipv4Addr>,
+    dns_ip: Option<Ipv4Addr>,
+    dns_name: Option<String>,
+}
+
+fn ip_to_raw(ip: Ipv4Addr) -> u32 {
+    // SOEM keeps addresses in network byte order
+    u32::from_ne_bytes(ip.octets())
+}
+
+fn ip_from_raw(addr: u32) -> Ipv4Addr {
+    Ipv4Addr::from(addr.to_ne_bytes())
+}
+
+impl EoEParam {
+    pub const fn mac(&self) -> Option<[u8; 6]> {
+        self.mac
+    }
+    pub const fn ip(&self) -> Option<Ipv4Addr> {
+        self.ip
+    }
+    pub const fn subnet(&self) -> Option<Ipv4Addr> {
+        self.subnet
+    }
+    pub const fn default_gateway(&self) -> Option<Ipv4Addr> {
+        self.default_gateway
+    }
+    pub const fn dns_ip(&self) -> Option<Ipv4Addr> {
+        self.dns_ip
+    }
+    pub fn dns_name(&self) -> Option<&str> {
+        self.dns_name.as_deref()
+    }
+
+    pub fn set_mac(&mut self, mac: [u8; 6]) -> &mut Self {
+        self.mac = Some(mac);
+        self
+    }
+    pub fn set_ip(&mut self, ip: Ipv4Addr) -> &mut Self {
+        self.ip = Some(ip);
+        self
+    }
+    pub fn set_subnet(&mut self, subnet: Ipv4Addr) -> &mut Self {
+        self.subnet = Some(subnet);
+        self
+    }
+    pub fn set_default_gateway(&mut self, default_gateway: Ipv4Addr) -> &mut Self {
+        self.default_gateway = Some(default_gateway);
+        self
+    }
+    pub fn set_dns_ip(&mut self, dns_ip: Ipv4Addr) -> &mut Self {
+        self.dns_ip = Some(dns_ip);
+        self
+    }
+    /// The name is truncated to 32 bytes
+    pub fn set_dns_name(&mut self, dns_name: &str) -> &mut Self {
+        self.dns_name = Some(dns_name.to_owned());
+        self
+    }
+
+    fn to_raw(&self) -> eoe_param_t {
+        let mut raw: eoe_param_t = unsafe { zeroed() };
+
+        if let Some(mac) = self.mac {
+            raw.set_mac_set(1);
+            raw.mac.addr = mac;
+        }
+        if let Some(ip) = self.ip {
+            raw.set_ip_set(1);
+            raw.ip.addr = ip_to_raw(ip);
+        }
+        if let Some(subnet) = self.subnet {
+            raw.set_subnet_set(1);
+            raw.subnet.addr = ip_to_raw(subnet);
+        }
+        if let Some(default_gateway) = self.default_gateway {
+            raw.set_default_gateway_set(1);
+            raw.default_gateway.addr = ip_to_raw(default_gateway);
+        }
+        if let Some(dns_ip) = self.dns_ip {
+            raw.set_dns_ip_set(1);
+            raw.dns_ip.addr = ip_to_raw(dns_ip);
+        }
+        if let Some(ref dns_name) = self.dns_name {
+            raw.set_dns_name_set(1);
+            raw.dns_name
+                .iter_mut()
+                .zip(dns_name.bytes().take(EOE_DNS_NAME_LENGTH))
+                .for_each(|(x, y)| *x = y as c_char);
+        }
+
+        raw
+    }
+
+    fn from_raw(raw: &eoe_param_t) -> Self {
+        let dns_name = raw
+            .dns_name
+            .iter()
+            .map(|&x| x as u8)
+            .take_while(|&x| x != 0)
+            .collect::<Vec<_>>();
+
+        EoEParam {
+            mac: (raw.mac_set() != 0).then_some(raw.mac.addr),
+            ip: (raw.ip_set() != 0).then(|| ip_from_raw(raw.ip.addr)),
+            subnet: (raw.subnet_set() != 0).then(|| ip_from_raw(raw.subnet.addr)),
+            default_gateway: (raw.default_gateway_set() != 0)
+                .then(|| ip_from_raw(raw.default_gateway.addr)),
+            dns_ip: (raw.dns_ip_set() != 0).then(|| ip_from_raw(raw.dns_ip.addr)),
+            dns_name: (raw.dns_name_set() != 0)
+                .then(|| String::from_utf8_lossy(&dns_name).into_owned()),
+        }
+    }
+}
+
+fn eoe_result(x: c_int) -> result::Result<c_int, EoEError> {
+    match EoEError::from_code(x) {
+        Ok(err) => Err(err),
+        Err(wkc) => Ok(wkc),
+    }
+}
+
+/// Ethernet over EtherCat port of a slave
+#[derive(Debug)]
+pub struct EoE<'c, 'a> {
+    context: &'c mut Context<'a>,
+    slave: u16,
+    port: u8,
+}
+
+impl<'c, 'a> EoE<'c, 'a> {
+    pub const fn slave(&self) -> u16 {
+        self.slave
+    }
+    pub const fn port(&self) -> u8 {
+        self.port
+    }
+
+    pub fn set_ip(&mut self, param: &EoEParam, timeout: c_int) -> result::Result<(), EoEError> {
+        let mut raw = param.to_raw();
+        let (slave, port) = (self.slave, self.port);
+        eoe_result(self.context.with_eoe_hook(|c| unsafe {
+            ecx_EOEsetIp(&mut c.context, slave, port, &mut raw, timeout)
+        }))
+        .map(|_| ())
+    }
+
+    pub fn ip(&mut self, timeout: c_int) -> result::Result<EoEParam, EoEError> {
+        let mut raw: eoe_param_t = unsafe { zeroed() };
+        let (slave, port) = (self.slave, self.port);
+        eoe_result(self.context.with_eoe_hook(|c| unsafe {
+            ecx_EOEgetIp(&mut c.context, slave, port, &mut raw, timeout)
+        }))
+        .map(|_| EoEParam::from_raw(&raw))
+    }
+
+    /// Send an Ethernet frame, it is fragmented to fit the mailbox
+    pub fn send(&mut self, frame: &[u8], timeout: c_int) -> result::Result<(), EoEError> {
+        if frame.len() > EOE_FRAME_MAX {
+            return Err(EoEError::FrameTooLarge(frame.len()));
+        }
+
+        let (slave, port) = (self.slave, self.port);
+        eoe_result(self.context.with_eoe_hook(|c| unsafe {
+            ecx_EOEsend(
+                &mut c.context,
+                slave,
+                port,
+                frame.len() as c_int,
+                frame.as_ptr() as *mut c_void,
+                timeout,
+            )
+        }))
+        .map(|_| ())
+    }
+
+    /// Receive an Ethernet frame into `buf`, returns the frame size.
+    ///
+    /// Nothing is received here while a handler set by [`Context::on_eoe_frame`] is active.
+    pub fn recv(&mut self, buf: &mut [u8], timeout: c_int) -> result::Result<usize, EoEError> {
+        let mut psize = buf.len() as c_int;
+        let (slave, port) = (self.slave, self.port);
+        eoe_result(self.context.with_eoe_hook(|c| unsafe {
+            ecx_EOErecv(
+                &mut c.context,
+                slave,
+                port,
+                &mut psize,
+                buf.as_mut_ptr() as *mut c_void,
+                timeout,
+            )
+        }))
+        .map(|_| psize as usize)
+    }
+}
+
+struct Reassembly {
+    fragmentno: u8,
+    framesize: u16,
+    frameoffset: u16,
+    frameno: u16,
+    buf: Vec<u8>,
+}
+
+impl Reassembly {
+    fn new() -> Self {
+        Reassembly {
+            fragmentno: 0,
+            framesize: 0,
+            frameoffset: 0,
+            frameno: 0,
+            buf: vec![0; EOE_FRAME_MAX],
+        }
+    }
+}
+
+type Handler<'a> = dyn FnMut(u16, &[u8]) + Send + 'a;
+
+pub(crate) struct EoEHook<'a> {
+    handler: Box<Handler<'a>>,
+    frames: HashMap<u16, Reassembly>,
+    // Panic of the handler, unwinding through SOEM is undefined behaviour
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<'a> fmt::Debug for EoEHook<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EoEHook")
+    }
+}
+
+impl<'a> EoEHook<'a> {
+    fn fragment(&mut self, slave: u16, mbx: *mut ec_mbxbuft) {
+        let frame = self.frames.entry(slave).or_insert_with(Reassembly::new);
+        let mut psize = frame.buf.len() as c_int;
+
+        let ret = unsafe {
+            ecx_EOEreadfragment(
+                mbx,
+                &mut frame.fragmentno,
+                &mut frame.framesize,
+                &mut frame.frameoffset,
+                &mut frame.frameno,
+                &mut psize,
+                frame.buf.as_mut_ptr() as *mut c_void,
+            )
+        };
+
+        if ret > 0 {
+            (self.handler)(slave, &frame.buf[..psize as usize]);
+        }
+    }
+}
+
+// SOEM passes the hook nothing but the context which moves together with `Context`, so the
+// handlers are looked up by the port buffer borrowed for the whole lifetime of the context.
+static HOOKS: Mutex<Vec<(usize, usize)>> = Mutex::new(Vec::new());
+
+unsafe extern "C" fn eoe_fragment(
+    context: *mut ecx_contextt,
+    slave: u16,
+    eoembx: *mut c_void,
+) -> c_int {
+    let key = (*context).port as usize;
+    let hook = HOOKS
+        .lock()
+        .ok()
+        .and_then(|hooks| hooks.iter().find(|x| x.0 == key).map(|x| x.1));
+
+    match (hook.unwrap_or(0) as *mut EoEHook).as_mut() {
+        Some(hook) if hook.panic.is_none() => {
+            let res = panic::catch_unwind(AssertUnwindSafe(|| {
+                hook.fragment(slave, eoembx as *mut ec_mbxbuft)
+            }));
+            if let Err(payload) = res {
+                hook.panic = Some(payload);
+            }
+            // Broken fragments are dropped as well, nobody else expects them
+            1
+        }
+        // Fragments are dropped until the panic is resumed
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Access the EoE port of the slave
+    pub fn eoe(&mut self, slave: u16, port: u8) -> EoE<'_, 'a> {
+        EoE {
+            context: self,
+            slave,
+            port,
+        }
+    }
+
+    /// Deliver Ethernet frames tunneled by the slaves to `handler`.
+    ///
+    /// SOEM passes EoE fragments to the handler whenever it reads a mailbox, e.g. while serving
+    /// SDO requests, the handler receives the slave and the reassembled frame. When the handler
+    /// panics, fragments are dropped until that SOEM call returns and the panic is resumed.
+    pub fn on_eoe_frame<F: FnMut(u16, &[u8]) + Send + 'a>(&mut self, handler: F) {
+        self.clear_eoe_handler();
+
+        let mut hook = Box::new(EoEHook {
+            handler: Box::new(handler),
+            frames: HashMap::new(),
+            panic: None,
+        });
+        let key = self.context.port as usize;
+        let ptr = &mut *hook as *mut EoEHook as usize;
+
+        if let Ok(mut hooks) = HOOKS.lock() {
+            hooks.push((key, ptr));
+        }
+        self.eoe_hook = Some(hook);
+        unsafe { ecx_EOEdefinehook(&mut self.context, eoe_fragment as *mut c_void) };
+    }
+
+    /// Run a SOEM call which may read a mailbox, a panic of the handler is resumed afterwards
+    pub(crate) fn with_eoe_hook<R, F: FnOnce(&mut Self) -> R>(&mut self, f: F) -> R {
+        let res = f(self);
+        self.resume_eoe_panic();
+        res
+    }
+
+    pub(crate) fn resume_eoe_panic(&mut self) {
+        if let Some(payload) = self.eoe_hook.as_mut().and_then(|x| x.panic.take()) {
+            panic::resume_unwind(payload);
+        }
+    }
+
+    pub fn clear_eoe_handler(&mut self) {
+        if self.eoe_hook.is_none() {
+            return;
+        }
+
+        let key = self.context.port as usize;
+        if let Ok(mut hooks) = HOOKS.lock() {
+            hooks.retain(|x| x.0 != key);
+        }
+        self.context.EOEhook = None;
+        self.eoe_hook = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+    use std::ptr;
+
+    #[test]
+    fn handler_panic_is_resumed_after_soem() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+        let mut hook = Box::new(EoEHook {
+            handler: Box::new(|_, _| ()),
+            frames: HashMap::new(),
+            panic: Some(Box::new("handler failed")),
+        });
+        let key = c.context.port as usize;
+        HOOKS
+            .lock()
+            .unwrap()
+            .push((key, &mut *hook as *mut EoEHook as usize));
+        c.eoe_hook = Some(hook);
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            c.with_eoe_hook(|c| unsafe {
+                // Pending panic, the fragment is dropped without reading it
+                eoe_fragment(&mut c.context, 1, ptr::null_mut())
+            })
+        }));
+        HOOKS.lock().unwrap().retain(|x| x.0 != key);
+
+        let payload = res.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"handler failed"));
+        assert!(c.eoe_hook.as_ref().unwrap().panic.is_none());
+        assert_eq!(c.with_eoe_hook(|_| 1), 1);
+    }
+}
@@ -1,4 +1,8 @@
-use std::{error, ffi::NulError, fmt, io};
+#[cfg(feature = "serde")]
+use crate::ConfigMismatch;
+use crate::EtherCatState;
+use std::{error, ffi::NulError, fmt, io, mem::zeroed};
+use SOEM_sys::{ec_errort, ecx_context, ecx_poperror, ecx_pusherror};
 
 pub trait ErrorGenerator: fmt::Debug {
     fn iserror(&mut self) -> bool;
@@ -40,6 +44,21 @@ impl<'a> fmt::Display for ErrorIterator<'a> {
 
 impl<'a> error::Error for ErrorIterator<'a> {}
 
+/// Pop the whole SOEM error list, the entries `take` returns false for are put back in order
+pub(crate) fn drain_errors<F: FnMut(&ec_errort) -> bool>(context: &mut ecx_context, mut take: F) {
+    let mut others = Vec::new();
+    let mut err: ec_errort = unsafe { zeroed() };
+    while unsafe { ecx_poperror(context, &mut err) } != 0 {
+        if !take(&err) {
+            others.push(err);
+        }
+    }
+
+    for err in others.iter() {
+        unsafe { ecx_pusherror(context, err) };
+    }
+}
+
 #[derive(Debug)]
 pub enum InitError {
     CStringError(NulError),
@@ -69,6 +88,13 @@ pub enum EtherCatError {
     NoFrame,
     OtherFrame,
     Error,
+    SlaveCountExceeded,
+    Timeout,
+    /// Mailbox sync managers of a [`SlaveConfig`](crate::SlaveConfig) could not be written to the
+    /// slave
+    SlaveConfig(u16, DatagramError),
+    /// Slave did not reach PreOp once its [`SlaveConfig`](crate::SlaveConfig) was applied
+    SlaveConfigState(u16, EtherCatState),
 }
 
 impl EtherCatError {
@@ -77,6 +103,8 @@ impl EtherCatError {
             -1 => Ok(EtherCatError::NoFrame),
             -2 => Ok(EtherCatError::OtherFrame),
             -3 => Ok(EtherCatError::Error),
+            -4 => Ok(EtherCatError::SlaveCountExceeded),
+            -5 => Ok(EtherCatError::Timeout),
             x => Err(x),
         }
     }
@@ -88,8 +116,728 @@ impl fmt::Display for EtherCatError {
             EtherCatError::NoFrame => write!(f, "No frame received"),
             EtherCatError::OtherFrame => write!(f, "Unkown frame received"),
             EtherCatError::Error => write!(f, "General EtherCat error"),
+            EtherCatError::SlaveCountExceeded => write!(f, "Too many slaves"),
+            EtherCatError::Timeout => write!(f, "Request timeout"),
+            EtherCatError::SlaveConfig(x, ref err) => {
+                write!(f, "Slave config of slave {} not written: {}", x, err)
+            }
+            EtherCatError::SlaveConfigState(x, state) => {
+                write!(f, "Slave {} is in {} after its slave config", x, state)
+            }
         }
     }
 }
 
 impl error::Error for EtherCatError {}
+
+#[derive(Debug)]
+pub enum FoEError {
+    CStringError(NulError),
+    IOError(io::Error),
+    NoResponse,
+    UnexpectedMailbox,
+    SlaveError,
+    BufferTooSmall,
+    PacketNumber,
+    FileNotFound,
+    Unknown(i32),
+}
+
+impl FoEError {
+    pub fn from_code(x: i32) -> Result<FoEError, i32> {
+        match x {
+            0 => Ok(FoEError::NoResponse),
+            -3 => Ok(FoEError::UnexpectedMailbox),
+            -5 => Ok(FoEError::SlaveError),
+            -6 => Ok(FoEError::BufferTooSmall),
+            -7 => Ok(FoEError::PacketNumber),
+            -10 => Ok(FoEError::FileNotFound),
+            x if x < 0 => Ok(FoEError::Unknown(x)),
+            x => Err(x),
+        }
+    }
+}
+
+impl fmt::Display for FoEError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FoEError::CStringError(ref err) => write!(f, "CString error: {}", err),
+            FoEError::IOError(ref err) => write!(f, "I/O error: {}", err),
+            FoEError::NoResponse => write!(f, "No response from slave"),
+            FoEError::UnexpectedMailbox => write!(f, "Unexpected mailbox received"),
+            FoEError::SlaveError => write!(f, "FoE error reported by slave"),
+            FoEError::BufferTooSmall => write!(f, "File buffer too small"),
+            FoEError::PacketNumber => write!(f, "Unexpected packet number"),
+            FoEError::FileNotFound => write!(f, "File not found"),
+            FoEError::Unknown(x) => write!(f, "Unknown FoE error {}", x),
+        }
+    }
+}
+
+impl error::Error for FoEError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FoEError::CStringError(ref err) => Some(err),
+            FoEError::IOError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EoEError {
+    NoResponse,
+    UnspecifiedError,
+    UnsupportedFrameType,
+    UnexpectedMailbox,
+    MailboxError,
+    InvalidRxData,
+    NoIpSupport,
+    NoDhcpSupport,
+    NoFilterSupport,
+    /// Frame exceeds the size the EoE header is able to describe
+    FrameTooLarge(usize),
+    Unknown(i32),
+}
+
+impl EoEError {
+    pub fn from_code(x: i32) -> Result<EoEError, i32> {
+        match x {
+            0 => Ok(EoEError::NoResponse),
+            -0x0001 => Ok(EoEError::UnspecifiedError),
+            -0x0002 => Ok(EoEError::UnsupportedFrameType),
+            -3 => Ok(EoEError::UnexpectedMailbox),
+            -9 => Ok(EoEError::MailboxError),
+            -11 => Ok(EoEError::InvalidRxData),
+            -0x0201 => Ok(EoEError::NoIpSupport),
+            -0x0202 => Ok(EoEError::NoDhcpSupport),
+            -0x0401 => Ok(EoEError::NoFilterSupport),
+            x if x < 0 => Ok(EoEError::Unknown(x)),
+            x => Err(x),
+        }
+    }
+}
+
+impl fmt::Display for EoEError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EoEError::NoResponse => write!(f, "No response from slave"),
+            EoEError::UnspecifiedError => write!(f, "Unspecified error"),
+            EoEError::UnsupportedFrameType => write!(f, "Unsupported frame type"),
+            EoEError::UnexpectedMailbox => write!(f, "Unexpected mailbox received"),
+            EoEError::MailboxError => write!(f, "Malformed response"),
+            EoEError::InvalidRxData => write!(f, "Invalid fragment received"),
+            EoEError::NoIpSupport => write!(f, "IP is not supported"),
+            EoEError::NoDhcpSupport => write!(f, "DHCP is not supported"),
+            EoEError::NoFilterSupport => write!(f, "Address filter is not supported"),
+            EoEError::FrameTooLarge(x) => write!(f, "Frame of {} bytes is too large", x),
+            EoEError::Unknown(x) => write!(f, "Unknown EoE error {}", x),
+        }
+    }
+}
+
+impl error::Error for EoEError {}
+
+#[derive(Debug, PartialEq)]
+pub enum SoEError {
+    NoResponse,
+    UnexpectedMailbox,
+    /// Error code reported by the slave for the IDN
+    Idn(u16),
+    /// Neither MDT nor AT mapping was found
+    NoMapping,
+    /// Operation data shorter than the requested value, the received size is given
+    ShortRead(usize),
+}
+
+impl SoEError {
+    pub fn message(code: u16) -> &'static str {
+        match code {
+            0x0000 => "No error",
+            0x1001 => "No IDN",
+            0x1009 => "Invalid access to element 1",
+            0x2001 => "No Name",
+            0x2002 => "Name transmission too short",
+            0x2003 => "Name transmission too long",
+            0x2004 => "Name cannot be changed (read only)",
+            0x2005 => "Name is write-protected at this time",
+            0x3002 => "Attribute transmission too short",
+            0x3003 => "Attribute transmission too long",
+            0x3004 => "Attribute cannot be changed (read only)",
+            0x3005 => "Attribute is write-protected at this time",
+            0x4001 => "No units",
+            0x4002 => "Unit transmission too short",
+            0x4003 => "Unit transmission too long",
+            0x4004 => "Unit cannot be changed (read only)",
+            0x4005 => "Unit is write-protected at this time",
+            0x5001 => "No minimum input value",
+            0x5002 => "Minimum input value transmission too short",
+            0x5003 => "Minimum input value transmission too long",
+            0x5004 => "Minimum input value cannot be changed (read only)",
+            0x5005 => "Minimum input value is write-protected at this time",
+            0x6001 => "No maximum input value",
+            0x6002 => "Maximum input value transmission too short",
+            0x6003 => "Maximum input value transmission too long",
+            0x6004 => "Maximum input value cannot be changed (read only)",
+            0x6005 => "Maximum input value is write-protected at this time",
+            0x7002 => "Operation data transmission too short",
+            0x7003 => "Operation data transmission too long",
+            0x7004 => "Operation data cannot be changed (read only)",
+            0x7005 => "Operation data is write-protected at this time (state)",
+            0x7006 => "Operation data is smaller than the minimum input value",
+            0x7007 => "Operation data is greater than the maximum input value",
+            0x7008 => "Invalid operation data: configured IDN will not be supported",
+            0x7009 => "Operation data write protected by a password",
+            0x700a => "Operation data is write protected, it is configured cyclically",
+            0x700b => "Invalid indirect addressing (e.g., data container, list handling)",
+            0x700c => "Operation data is write protected, due to other settings",
+            0x7010 => "Procedure command already active",
+            0x7011 => "Procedure command not interruptible",
+            0x7012 => "Procedure command at this time not executable (state)",
+            0x7013 => "Procedure command not executable (invalid or false parameters)",
+            0x7014 => "No data state",
+            0x8001 => "No default value",
+            0x8002 => "Default value transmission too long",
+            0x8004 => "Default value cannot be changed, read only",
+            0x800a => "Invalid drive number",
+            _ => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for SoEError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SoEError::NoResponse => write!(f, "No response from slave"),
+            SoEError::UnexpectedMailbox => write!(f, "Unexpected mailbox received"),
+            SoEError::Idn(x) => write!(f, "SoE error {:04x}: {}", x, SoEError::message(x)),
+            SoEError::NoMapping => write!(f, "No IDN mapping found"),
+            SoEError::ShortRead(x) => write!(f, "Only {} bytes of operation data received", x),
+        }
+    }
+}
+
+impl error::Error for SoEError {}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseIdnError;
+
+impl fmt::Display for ParseIdnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IDN is expected in S-x-yyyy or P-x-yyyy notation")
+    }
+}
+
+impl error::Error for ParseIdnError {}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseTransitionError;
+
+impl fmt::Display for ParseTransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Transition is expected as two state letters of I, P, S, O, B"
+        )
+    }
+}
+
+impl error::Error for ParseTransitionError {}
+
+#[derive(Debug, PartialEq)]
+pub enum MailboxError {
+    NoResponse,
+    /// Message size and the mailbox size of the slave
+    TooLarge(usize, usize),
+    UnknownSlave(u16),
+}
+
+impl fmt::Display for MailboxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MailboxError::NoResponse => write!(f, "No response from slave"),
+            MailboxError::TooLarge(size, max) => write!(
+                f,
+                "Message of {} bytes does not fit mailbox of {} bytes",
+                size, max
+            ),
+            MailboxError::UnknownSlave(x) => write!(f, "No slave {} found", x),
+        }
+    }
+}
+
+impl error::Error for MailboxError {}
+
+#[derive(Debug, PartialEq)]
+pub enum VoEError {
+    MailboxError(MailboxError),
+    /// Mailbox of another type received, the raw type is given
+    UnexpectedMailbox(u8),
+    /// Mailbox too short to hold the VoE header
+    TooShort(usize),
+    /// Reply from another vendor received
+    VendorMismatch(u32),
+}
+
+impl fmt::Display for VoEError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VoEError::MailboxError(ref err) => write!(f, "Mailbox error: {}", err),
+            VoEError::UnexpectedMailbox(x) => write!(f, "Unexpected mailbox type {} received", x),
+            VoEError::TooShort(x) => write!(f, "VoE mailbox of {} bytes is too short", x),
+            VoEError::VendorMismatch(x) => write!(f, "Unexpected vendor {:08x}", x),
+        }
+    }
+}
+
+impl error::Error for VoEError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            VoEError::MailboxError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AoEError {
+    MailboxError(MailboxError),
+    /// Mailbox of another type received, the raw type is given
+    UnexpectedMailbox(u8),
+    /// Reply too short for the request
+    TooShort(usize),
+    /// ADS error code returned by the device
+    Ads(u32),
+}
+
+impl AoEError {
+    pub fn message(code: u32) -> &'static str {
+        match code {
+            0x0000 => "No error",
+            0x0001 => "Internal error",
+            0x0006 => "Target port not found",
+            0x0007 => "Target machine not found",
+            0x0008 => "Unknown command ID",
+            0x000b => "Invalid AMS length",
+            0x0012 => "Port disabled",
+            0x0013 => "Port already connected",
+            0x0700 => "General device error",
+            0x0701 => "Service is not supported by the server",
+            0x0702 => "Invalid index group",
+            0x0703 => "Invalid index offset",
+            0x0704 => "Reading or writing not permitted",
+            0x0705 => "Parameter size not correct",
+            0x0706 => "Invalid data values",
+            0x0707 => "Device is not ready to operate",
+            0x0708 => "Device is busy",
+            0x0709 => "Invalid operating system context",
+            0x070a => "Insufficient memory",
+            0x070b => "Invalid parameter values",
+            0x070c => "Not found",
+            0x070d => "Syntax error in command or file",
+            0x070e => "Objects do not match",
+            0x070f => "Object already exists",
+            0x0710 => "Symbol not found",
+            0x0711 => "Invalid symbol version",
+            0x0712 => "Device is in an invalid state",
+            0x0713 => "AdsTransMode not supported",
+            0x0714 => "Notification handle is invalid",
+            0x0715 => "Notification client not registered",
+            0x0716 => "No further notification handle available",
+            0x0717 => "Notification size too large",
+            0x0718 => "Device not initialized",
+            0x0719 => "Device has a timeout",
+            0x071a => "Interface query failed",
+            0x071b => "Wrong interface requested",
+            0x071c => "Class ID is invalid",
+            0x071d => "Object ID is invalid",
+            0x071e => "Request pending",
+            0x071f => "Request aborted",
+            0x0720 => "Signal warning",
+            0x0721 => "Invalid array index",
+            0x0722 => "Symbol not active",
+            0x0723 => "Access denied",
+            0x0740 => "Client error",
+            0x0741 => "Service contains an invalid parameter",
+            0x0742 => "Polling list is empty",
+            0x0743 => "Var connection already in use",
+            0x0744 => "Invoke ID in use",
+            0x0745 => "Timeout elapsed",
+            0x0750 => "Internal error in ADS sync",
+            0x0754 => "Invalid response received",
+            _ => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for AoEError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AoEError::MailboxError(ref err) => write!(f, "Mailbox error: {}", err),
+            AoEError::UnexpectedMailbox(x) => write!(f, "Unexpected mailbox type {} received", x),
+            AoEError::TooShort(x) => write!(f, "ADS reply of {} bytes is too short", x),
+            AoEError::Ads(x) => write!(f, "ADS error {:04x}: {}", x, AoEError::message(x)),
+        }
+    }
+}
+
+impl error::Error for AoEError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AoEError::MailboxError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseNetIdError;
+
+impl fmt::Display for ParseNetIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AMS NetId is expected as six dot separated octets")
+    }
+}
+
+impl error::Error for ParseNetIdError {}
+
+#[derive(Debug, PartialEq)]
+pub enum EepromError {
+    NoResponse,
+    /// Reading the word at the address failed
+    ReadFailed(u16),
+    /// Writing the word at the address failed
+    WriteFailed(u16),
+    /// EEPROM is written by words, the length is given
+    OddLength(usize),
+    /// Access beyond the address space of the EEPROM
+    OutOfRange,
+    UnknownSlave(u16),
+}
+
+impl fmt::Display for EepromError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EepromError::NoResponse => write!(f, "No response from slave"),
+            EepromError::ReadFailed(x) => write!(f, "Cannot read EEPROM word {:04x}", x),
+            EepromError::WriteFailed(x) => write!(f, "Cannot write EEPROM word {:04x}", x),
+            EepromError::OddLength(x) => write!(f, "Length {} is not a whole number of words", x),
+            EepromError::OutOfRange => write!(f, "Address out of EEPROM range"),
+            EepromError::UnknownSlave(x) => write!(f, "No slave {} found", x),
+        }
+    }
+}
+
+impl error::Error for EepromError {}
+
+#[derive(Debug, PartialEq)]
+pub enum DatagramError {
+    /// No frame returned within the timeout
+    NoFrame,
+    /// Data of the given size does not fit a single frame
+    TooLarge(usize),
+    /// Working counter other than expected
+    WorkingCounter(u16),
+    /// Instance of a register beyond the ESC address space or of a register without instances
+    RegisterIndex(u16),
+    UnknownSlave(u16),
+    Unknown(i32),
+}
+
+impl fmt::Display for DatagramError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DatagramError::NoFrame => write!(f, "No frame returned"),
+            DatagramError::TooLarge(x) => {
+                write!(f, "Datagram of {} bytes does not fit a frame", x)
+            }
+            DatagramError::WorkingCounter(x) => write!(f, "Unexpected working counter {}", x),
+            DatagramError::RegisterIndex(x) => write!(f, "Invalid register instance {}", x),
+            DatagramError::UnknownSlave(x) => write!(f, "No slave {} found", x),
+            DatagramError::Unknown(x) => write!(f, "Unknown datagram error: {}", x),
+        }
+    }
+}
+
+impl error::Error for DatagramError {}
+
+#[derive(Debug, PartialEq)]
+pub enum SiiError {
+    EepromError(EepromError),
+    /// Image is shorter than the fixed SII area, the length is given
+    TooShort(usize),
+    /// Category of the given type runs past the end of the image
+    Truncated(u16),
+    /// Category of the given type holds more entries or bytes than its counters can express
+    TooLarge(u16),
+}
+
+impl fmt::Display for SiiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SiiError::EepromError(ref err) => write!(f, "EEPROM error: {}", err),
+            SiiError::TooShort(x) => write!(f, "SII image of {} bytes is too short", x),
+            SiiError::Truncated(x) => write!(f, "SII category {} is truncated", x),
+            SiiError::TooLarge(x) => write!(f, "SII category {} is too large", x),
+        }
+    }
+}
+
+impl error::Error for SiiError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            SiiError::EepromError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+#[derive(Debug, PartialEq)]
+pub enum XmlError {
+    /// Document is not well-formed
+    Parse(String),
+    /// Required element is missing
+    Missing(String),
+    /// Element or attribute and its value
+    InvalidValue(String, String),
+}
+
+#[cfg(feature = "xml")]
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            XmlError::Parse(ref x) => write!(f, "XML parse error: {}", x),
+            XmlError::Missing(ref x) => write!(f, "Element {} is missing", x),
+            XmlError::InvalidValue(ref x, ref value) => {
+                write!(f, "Invalid value of {}: {}", x, value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+impl error::Error for XmlError {}
+
+#[cfg(feature = "xml")]
+#[derive(Debug)]
+pub enum EniError {
+    XmlError(XmlError),
+    /// Slave at the position differs from the configured one
+    SlaveMismatch(u16),
+    /// Init command failed, the slave position and the error list of the context are given
+    ErrorList(u16, Vec<String>),
+    DatagramError(u16, DatagramError),
+    SoEError(u16, SoEError),
+    /// Data read by a register init command differs from the expected one
+    ValidationFailed(u16),
+    /// Process data offsets or sizes of the slave differ from the IO map
+    ProcessImageMismatch(u16),
+}
+
+#[cfg(feature = "xml")]
+impl fmt::Display for EniError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            EniError::XmlError(ref err) => write!(f, "ENI error: {}", err),
+            EniError::SlaveMismatch(x) => write!(f, "Slave {} differs from the ENI", x),
+            EniError::ErrorList(x, ref errors) => {
+                writeln!(f, "Init command of slave {} failed:", x)?;
+                errors.iter().try_for_each(|x| writeln!(f, "{}", x))
+            }
+            EniError::DatagramError(x, ref err) => {
+                write!(f, "Init command of slave {} failed: {}", x, err)
+            }
+            EniError::SoEError(x, ref err) => {
+                write!(f, "Init command of slave {} failed: {}", x, err)
+            }
+            EniError::ValidationFailed(x) => {
+                write!(f, "Init command of slave {} read unexpected data", x)
+            }
+            EniError::ProcessImageMismatch(x) => {
+                write!(f, "Process data of slave {} differs from the ENI", x)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "xml")]
+impl error::Error for EniError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            EniError::XmlError(ref err) => Some(err),
+            EniError::DatagramError(_, ref err) => Some(err),
+            EniError::SoEError(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// TOML or YAML document does not match the schema
+    Parse(String),
+    EtherCatError(EtherCatError),
+    /// Found slaves differ from the configured ones
+    Mismatch(Vec<ConfigMismatch>),
+    /// No slave carries the configured alias
+    UnknownAlias(u16),
+    /// Startup command of the slave failed with the error list of the context
+    ErrorList(u16, Vec<String>),
+    /// No process image buffer is given for the group
+    NoIoMap(u8),
+    /// Mapping of the group failed with the error list of the context
+    MapError(u8, Vec<String>),
+    DcError(Vec<String>),
+    /// Opening the configured interfaces failed
+    InitError(InitError),
+    /// Redundant interface is configured but no [`RedPort`](crate::RedPort) is given
+    NoRedPort,
+    /// Group is listed twice, group 0 which holds every slave is listed with others, or a slave
+    /// belongs to a group that is not listed
+    InvalidGroup(u8),
+    /// Requested state was not reached, the actual one is given
+    StateError(EtherCatState, EtherCatState),
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Parse(ref x) => write!(f, "Invalid configuration: {}", x),
+            ConfigError::EtherCatError(ref err) => write!(f, "{}", err),
+            ConfigError::Mismatch(ref x) => {
+                writeln!(f, "Network differs from the configuration:")?;
+                x.iter().try_for_each(|x| writeln!(f, "{}", x))
+            }
+            ConfigError::UnknownAlias(x) => write!(f, "No slave with alias {}", x),
+            ConfigError::ErrorList(x, ref errors) => {
+                writeln!(f, "Startup of slave {} failed:", x)?;
+                errors.iter().try_for_each(|x| writeln!(f, "{}", x))
+            }
+            ConfigError::NoIoMap(x) => write!(f, "No process image for group {}", x),
+            ConfigError::MapError(x, ref errors) => {
+                writeln!(f, "Mapping of group {} failed:", x)?;
+                errors.iter().try_for_each(|x| writeln!(f, "{}", x))
+            }
+            ConfigError::DcError(ref errors) => {
+                writeln!(f, "DC configuration failed:")?;
+                errors.iter().try_for_each(|x| writeln!(f, "{}", x))
+            }
+            ConfigError::InitError(ref err) => write!(f, "{}", err),
+            ConfigError::NoRedPort => write!(f, "No port given for the redundant interface"),
+            ConfigError::InvalidGroup(x) => write!(f, "Group {} cannot be mapped", x),
+            ConfigError::StateError(expected, found) => {
+                write!(f, "Slaves are in {}, expected {}", found, expected)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            ConfigError::EtherCatError(ref err) => Some(err),
+            ConfigError::InitError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FirmwareError {
+    IOError(io::Error),
+    EtherCatError(EtherCatError),
+    FoEError(FoEError),
+    /// Requested state was not reached, the actual one is given
+    StateError(EtherCatState, EtherCatState),
+    /// Revision expected after the update and the one found in the SII
+    RevisionMismatch(u32, u32),
+    NoResponse,
+    UnknownSlave(u16),
+}
+
+impl fmt::Display for FirmwareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FirmwareError::IOError(ref err) => write!(f, "I/O error: {}", err),
+            FirmwareError::EtherCatError(ref err) => write!(f, "EtherCat error: {}", err),
+            FirmwareError::FoEError(ref err) => write!(f, "FoE error: {}", err),
+            FirmwareError::StateError(requested, actual) => write!(
+                f,
+                "Cannot reach {} state, slave is in {} state",
+                requested, actual
+            ),
+            FirmwareError::RevisionMismatch(expected, found) => write!(
+                f,
+                "Revision {:08x} expected after the update, found {:08x}",
+                expected, found
+            ),
+            FirmwareError::NoResponse => write!(f, "No response from slave"),
+            FirmwareError::UnknownSlave(x) => write!(f, "No slave {} found", x),
+        }
+    }
+}
+
+impl error::Error for FirmwareError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FirmwareError::IOError(ref err) => Some(err),
+            FirmwareError::EtherCatError(ref err) => Some(err),
+            FirmwareError::FoEError(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub enum AsyncError {
+    Init(InitError),
+    EtherCat(EtherCatError),
+    ErrorList(Vec<String>),
+    GroupMapped(u8),
+    Closed,
+}
+
+#[cfg(feature = "tokio")]
+impl From<EtherCatError> for AsyncError {
+    fn from(err: EtherCatError) -> Self {
+        AsyncError::EtherCat(err)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<'a> From<ErrorIterator<'a>> for AsyncError {
+    fn from(errors: ErrorIterator<'a>) -> Self {
+        AsyncError::ErrorList(errors.collect())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl fmt::Display for AsyncError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AsyncError::Init(ref err) => write!(f, "Init error: {}", err),
+            AsyncError::EtherCat(ref err) => write!(f, "EtherCat error: {}", err),
+            AsyncError::ErrorList(ref errors) => {
+                errors.iter().try_for_each(|x| writeln!(f, "{}", x))
+            }
+            AsyncError::GroupMapped(group) => write!(f, "Group {} is already mapped", group),
+            AsyncError::Closed => write!(f, "Master thread is gone"),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl error::Error for AsyncError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AsyncError::Init(ref err) => Some(err),
+            AsyncError::EtherCat(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
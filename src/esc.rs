@@ -0,0 +1,863 @@
+use crate::{error::DatagramError, Context, EtherCatState, EC_TIMEOUTRET};
+use std::{convert::TryInto, result};
+
+/** Number of ports of an ESC */
+const ESC_PORTS: u8 = 4;
+
+fn u16_at(x: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(x[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(x: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(x[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(x: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(x[offset..offset + 8].try_into().unwrap())
+}
+
+/// Register block of the EtherCat slave controller, see [`Context::read_register`]
+pub trait EscRegister: Sized {
+    /// Address of the first instance of the register
+    const ADDRESS: u16;
+    /// Size of the register in bytes
+    const SIZE: usize;
+    /// Distance between the instances of repeated registers such as FMMUs and sync managers
+    const STRIDE: u16 = 0;
+
+    /// Decode the register, `x` holds exactly `SIZE` bytes
+    fn from_bytes(x: &[u8]) -> Self;
+}
+
+/// Register the master may write, see [`Context::write_register`]
+pub trait WritableRegister: EscRegister {
+    fn to_bytes(&self) -> Vec<u8>;
+}
+
+/// ESC information, 0x0000
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct EscInfo {
+    esc_type: u8,
+    revision: u8,
+    build: u16,
+    fmmu_count: u8,
+    sm_count: u8,
+    ram_size: u8,
+    port_descriptor: u8,
+    features: u16,
+}
+
+impl EscInfo {
+    pub const fn esc_type(&self) -> u8 {
+        self.esc_type
+    }
+    pub const fn revision(&self) -> u8 {
+        self.revision
+    }
+    pub const fn build(&self) -> u16 {
+        self.build
+    }
+    pub const fn fmmu_count(&self) -> u8 {
+        self.fmmu_count
+    }
+    pub const fn sm_count(&self) -> u8 {
+        self.sm_count
+    }
+    /// Process data RAM size in KiB
+    pub const fn ram_size(&self) -> u8 {
+        self.ram_size
+    }
+    /// Physical layer of the ports, two bits per port
+    pub const fn port_descriptor(&self) -> u8 {
+        self.port_descriptor
+    }
+    pub const fn features(&self) -> u16 {
+        self.features
+    }
+}
+
+impl EscRegister for EscInfo {
+    const ADDRESS: u16 = 0x0000;
+    const SIZE: usize = 10;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        EscInfo {
+            esc_type: x[0],
+            revision: x[1],
+            build: u16_at(x, 2),
+            fmmu_count: x[4],
+            sm_count: x[5],
+            ram_size: x[6],
+            port_descriptor: x[7],
+            features: u16_at(x, 8),
+        }
+    }
+}
+
+/// Station address, 0x0010
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct StationAddress {
+    configured: u16,
+    alias: u16,
+}
+
+impl StationAddress {
+    pub const fn new(configured: u16, alias: u16) -> Self {
+        StationAddress { configured, alias }
+    }
+    pub const fn configured(&self) -> u16 {
+        self.configured
+    }
+    /// Station alias, loaded from the SII at power up
+    pub const fn alias(&self) -> u16 {
+        self.alias
+    }
+}
+
+impl EscRegister for StationAddress {
+    const ADDRESS: u16 = 0x0010;
+    const SIZE: usize = 4;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        StationAddress {
+            configured: u16_at(x, 0),
+            alias: u16_at(x, 2),
+        }
+    }
+}
+
+impl WritableRegister for StationAddress {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = self.configured.to_le_bytes().to_vec();
+        x.extend_from_slice(&self.alias.to_le_bytes());
+        x
+    }
+}
+
+/// DL control, 0x0100
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct DlControl(u32);
+
+impl DlControl {
+    pub const fn new(x: u32) -> Self {
+        DlControl(x)
+    }
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+    /// Loop setting of the port: 0 auto, 1 auto close, 2 open, 3 closed
+    pub const fn loop_port(&self, port: u8) -> Option<u8> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(((self.0 >> (8 + 2 * port as u32)) & 0x3) as u8)
+    }
+}
+
+impl EscRegister for DlControl {
+    const ADDRESS: u16 = 0x0100;
+    const SIZE: usize = 4;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        DlControl(u32_at(x, 0))
+    }
+}
+
+impl WritableRegister for DlControl {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+}
+
+/// DL status, 0x0110
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct DlStatus(u16);
+
+impl DlStatus {
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+    /// PDI is operational, the SII has been loaded
+    pub const fn pdi_operational(&self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+    /// DLS user watchdog is not expired
+    pub const fn watchdog(&self) -> bool {
+        self.0 & 0x0002 != 0
+    }
+    const fn port_bit(&self, port: u8, first: u8, step: u8) -> Option<bool> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(self.0 & (1 << (first + step * port)) != 0)
+    }
+    /// Physical link detected on the port, `None` for ports the ESC cannot have
+    pub const fn link(&self, port: u8) -> Option<bool> {
+        self.port_bit(port, 4, 1)
+    }
+    /// Loop of the port is closed
+    pub const fn loop_closed(&self, port: u8) -> Option<bool> {
+        self.port_bit(port, 8, 2)
+    }
+    /// Stable communication on the port
+    pub const fn communication(&self, port: u8) -> Option<bool> {
+        self.port_bit(port, 9, 2)
+    }
+}
+
+impl EscRegister for DlStatus {
+    const ADDRESS: u16 = 0x0110;
+    const SIZE: usize = 2;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        DlStatus(u16_at(x, 0))
+    }
+}
+
+/// AL control, 0x0120
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct AlControl(u16);
+
+impl AlControl {
+    /// Request the state, `ack` acknowledges the pending AL error
+    pub const fn new(state: EtherCatState, ack: bool) -> Self {
+        AlControl(state as u16 | if ack { 0x0010 } else { 0 })
+    }
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+    pub fn state(&self) -> Option<EtherCatState> {
+        num::FromPrimitive::from_u16(self.0 & 0x000f)
+    }
+    pub const fn ack(&self) -> bool {
+        self.0 & 0x0010 != 0
+    }
+}
+
+impl EscRegister for AlControl {
+    const ADDRESS: u16 = 0x0120;
+    const SIZE: usize = 2;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        AlControl(u16_at(x, 0))
+    }
+}
+
+impl WritableRegister for AlControl {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+}
+
+/// AL status and AL status code, 0x0130
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct AlStatus {
+    status: u16,
+    status_code: u16,
+}
+
+impl AlStatus {
+    /// Current state, `None` for values unknown to the crate
+    pub fn state(&self) -> Option<EtherCatState> {
+        num::FromPrimitive::from_u16(self.status & 0x000f)
+    }
+    pub const fn raw_status(&self) -> u16 {
+        self.status
+    }
+    /// State change failed, see the status code
+    pub const fn error(&self) -> bool {
+        self.status & 0x0010 != 0
+    }
+    pub const fn status_code(&self) -> u16 {
+        self.status_code
+    }
+}
+
+impl EscRegister for AlStatus {
+    const ADDRESS: u16 = 0x0130;
+    const SIZE: usize = 6;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        AlStatus {
+            status: u16_at(x, 0),
+            status_code: u16_at(x, 4),
+        }
+    }
+}
+
+/// Error counters, 0x0300.
+///
+/// Counters saturate at 0xff and are cleared by writing any of them.
+/// The port accessors return `None` for ports the ESC cannot have.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct ErrorCounters {
+    invalid_frame: [u8; 4],
+    rx_error: [u8; 4],
+    forwarded_rx_error: [u8; 4],
+    processing_unit_error: u8,
+    pdi_error: u8,
+    lost_link: [u8; 4],
+}
+
+impl ErrorCounters {
+    /// Frames with an invalid CRC or length received on the port
+    pub const fn invalid_frame(&self, port: u8) -> Option<u8> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(self.invalid_frame[port as usize])
+    }
+    /// Physical layer errors on the port
+    pub const fn rx_error(&self, port: u8) -> Option<u8> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(self.rx_error[port as usize])
+    }
+    /// Frames already marked as erroneous by a previous slave
+    pub const fn forwarded_rx_error(&self, port: u8) -> Option<u8> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(self.forwarded_rx_error[port as usize])
+    }
+    pub const fn processing_unit_error(&self) -> u8 {
+        self.processing_unit_error
+    }
+    pub const fn pdi_error(&self) -> u8 {
+        self.pdi_error
+    }
+    pub const fn lost_link(&self, port: u8) -> Option<u8> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(self.lost_link[port as usize])
+    }
+}
+
+impl EscRegister for ErrorCounters {
+    const ADDRESS: u16 = 0x0300;
+    const SIZE: usize = 0x14;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        let mut res = ErrorCounters {
+            processing_unit_error: x[0x0c],
+            pdi_error: x[0x0d],
+            ..Default::default()
+        };
+        for port in 0..4 {
+            res.invalid_frame[port] = x[2 * port];
+            res.rx_error[port] = x[2 * port + 1];
+            res.forwarded_rx_error[port] = x[0x08 + port];
+            res.lost_link[port] = x[0x10 + port];
+        }
+        res
+    }
+}
+
+impl WritableRegister for ErrorCounters {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = vec![0; Self::SIZE];
+        for port in 0..4 {
+            x[2 * port] = self.invalid_frame[port];
+            x[2 * port + 1] = self.rx_error[port];
+            x[0x08 + port] = self.forwarded_rx_error[port];
+            x[0x10 + port] = self.lost_link[port];
+        }
+        x[0x0c] = self.processing_unit_error;
+        x[0x0d] = self.pdi_error;
+        x
+    }
+}
+
+/// Watchdog divider, 0x0400
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct WatchdogDivider(u16);
+
+impl WatchdogDivider {
+    pub const fn new(x: u16) -> Self {
+        WatchdogDivider(x)
+    }
+    /// Number of 40 ns ticks minus 2 per watchdog increment
+    pub const fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl EscRegister for WatchdogDivider {
+    const ADDRESS: u16 = 0x0400;
+    const SIZE: usize = 2;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        WatchdogDivider(u16_at(x, 0))
+    }
+}
+
+impl WritableRegister for WatchdogDivider {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+}
+
+/// Watchdog times, instance 0 at 0x0410 for PDI and instance 1 at 0x0420 for process data
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct WatchdogTime(u16);
+
+impl WatchdogTime {
+    pub const fn new(x: u16) -> Self {
+        WatchdogTime(x)
+    }
+    /// Time in watchdog increments, 0 disables the watchdog
+    pub const fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+impl EscRegister for WatchdogTime {
+    const ADDRESS: u16 = 0x0410;
+    const SIZE: usize = 2;
+    const STRIDE: u16 = 0x10;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        WatchdogTime(u16_at(x, 0))
+    }
+}
+
+impl WritableRegister for WatchdogTime {
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_le_bytes().to_vec()
+    }
+}
+
+/// Watchdog status and counters, 0x0440
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct WatchdogStatus {
+    status: u16,
+    process_data_counter: u8,
+    pdi_counter: u8,
+}
+
+impl WatchdogStatus {
+    /// Process data watchdog has not expired
+    pub const fn process_data_active(&self) -> bool {
+        self.status & 0x0001 != 0
+    }
+    pub const fn process_data_counter(&self) -> u8 {
+        self.process_data_counter
+    }
+    pub const fn pdi_counter(&self) -> u8 {
+        self.pdi_counter
+    }
+}
+
+impl EscRegister for WatchdogStatus {
+    const ADDRESS: u16 = 0x0440;
+    const SIZE: usize = 4;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        WatchdogStatus {
+            status: u16_at(x, 0),
+            process_data_counter: x[2],
+            pdi_counter: x[3],
+        }
+    }
+}
+
+impl WritableRegister for WatchdogStatus {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = self.status.to_le_bytes().to_vec();
+        x.extend_from_slice(&[self.process_data_counter, self.pdi_counter]);
+        x
+    }
+}
+
+/// SII interface, 0x0500
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SiiInterface {
+    config: u8,
+    pdi_access: u8,
+    control: u16,
+    address: u32,
+    data: u64,
+}
+
+impl SiiInterface {
+    pub const fn config(&self) -> u8 {
+        self.config
+    }
+    /// EEPROM is controlled by the PDI
+    pub const fn pdi_access(&self) -> bool {
+        self.pdi_access & 0x01 != 0
+    }
+    pub const fn control(&self) -> u16 {
+        self.control
+    }
+    pub const fn busy(&self) -> bool {
+        self.control & 0x8000 != 0
+    }
+    /// Error bits 11 to 14 of the control/status register
+    pub const fn error(&self) -> u16 {
+        (self.control >> 11) & 0x0f
+    }
+    pub const fn address(&self) -> u32 {
+        self.address
+    }
+    pub const fn data(&self) -> u64 {
+        self.data
+    }
+}
+
+impl EscRegister for SiiInterface {
+    const ADDRESS: u16 = 0x0500;
+    const SIZE: usize = 16;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        SiiInterface {
+            config: x[0],
+            pdi_access: x[1],
+            control: u16_at(x, 2),
+            address: u32_at(x, 4),
+            data: u64_at(x, 8),
+        }
+    }
+}
+
+impl WritableRegister for SiiInterface {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = vec![self.config, self.pdi_access];
+        x.extend_from_slice(&self.control.to_le_bytes());
+        x.extend_from_slice(&self.address.to_le_bytes());
+        x.extend_from_slice(&self.data.to_le_bytes());
+        x
+    }
+}
+
+/// FMMU, 0x0600 + 0x10 * n
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Fmmu {
+    logical_start: u32,
+    length: u16,
+    logical_start_bit: u8,
+    logical_end_bit: u8,
+    physical_start: u16,
+    physical_start_bit: u8,
+    fmmu_type: u8,
+    activate: u8,
+}
+
+impl Fmmu {
+    pub const fn logical_start(&self) -> u32 {
+        self.logical_start
+    }
+    pub const fn length(&self) -> u16 {
+        self.length
+    }
+    pub const fn logical_start_bit(&self) -> u8 {
+        self.logical_start_bit
+    }
+    pub const fn logical_end_bit(&self) -> u8 {
+        self.logical_end_bit
+    }
+    pub const fn physical_start(&self) -> u16 {
+        self.physical_start
+    }
+    pub const fn physical_start_bit(&self) -> u8 {
+        self.physical_start_bit
+    }
+    /// Bit 0 maps reads, bit 1 maps writes
+    pub const fn fmmu_type(&self) -> u8 {
+        self.fmmu_type
+    }
+    pub const fn active(&self) -> bool {
+        self.activate & 0x01 != 0
+    }
+}
+
+impl EscRegister for Fmmu {
+    const ADDRESS: u16 = 0x0600;
+    const SIZE: usize = 16;
+    const STRIDE: u16 = 0x10;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        Fmmu {
+            logical_start: u32_at(x, 0),
+            length: u16_at(x, 4),
+            logical_start_bit: x[6],
+            logical_end_bit: x[7],
+            physical_start: u16_at(x, 8),
+            physical_start_bit: x[10],
+            fmmu_type: x[11],
+            activate: x[12],
+        }
+    }
+}
+
+impl WritableRegister for Fmmu {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = self.logical_start.to_le_bytes().to_vec();
+        x.extend_from_slice(&self.length.to_le_bytes());
+        x.extend_from_slice(&[self.logical_start_bit, self.logical_end_bit]);
+        x.extend_from_slice(&self.physical_start.to_le_bytes());
+        x.extend_from_slice(&[
+            self.physical_start_bit,
+            self.fmmu_type,
+            self.activate,
+            0,
+            0,
+            0,
+        ]);
+        x
+    }
+}
+
+/// Sync manager, 0x0800 + 0x08 * n
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SyncManager {
+    physical_start: u16,
+    length: u16,
+    control: u8,
+    status: u8,
+    activate: u8,
+    pdi_control: u8,
+}
+
+impl SyncManager {
+    pub const fn physical_start(&self) -> u16 {
+        self.physical_start
+    }
+    pub const fn length(&self) -> u16 {
+        self.length
+    }
+    pub const fn control(&self) -> u8 {
+        self.control
+    }
+    pub const fn status(&self) -> u8 {
+        self.status
+    }
+    pub const fn active(&self) -> bool {
+        self.activate & 0x01 != 0
+    }
+    /// Sync manager is deactivated by the PDI
+    pub const fn pdi_disabled(&self) -> bool {
+        self.pdi_control & 0x01 != 0
+    }
+}
+
+impl EscRegister for SyncManager {
+    const ADDRESS: u16 = 0x0800;
+    const SIZE: usize = 8;
+    const STRIDE: u16 = 0x08;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        SyncManager {
+            physical_start: u16_at(x, 0),
+            length: u16_at(x, 2),
+            control: x[4],
+            status: x[5],
+            activate: x[6],
+            pdi_control: x[7],
+        }
+    }
+}
+
+impl WritableRegister for SyncManager {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut x = self.physical_start.to_le_bytes().to_vec();
+        x.extend_from_slice(&self.length.to_le_bytes());
+        x.extend_from_slice(&[self.control, self.status, self.activate, self.pdi_control]);
+        x
+    }
+}
+
+/// Distributed clock receive and system times, 0x0900
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct DcTimes {
+    receive_time: [u32; 4],
+    system_time: u64,
+    receive_time_pu: u64,
+    system_time_offset: u64,
+    system_time_delay: u32,
+    system_time_difference: u32,
+}
+
+impl DcTimes {
+    /// Local time the frame was received on the port, latched by a write to 0x0900
+    pub const fn receive_time(&self, port: u8) -> Option<u32> {
+        if port >= ESC_PORTS {
+            return None;
+        }
+        Some(self.receive_time[port as usize])
+    }
+    pub const fn system_time(&self) -> u64 {
+        self.system_time
+    }
+    /// Local time the frame reached the processing unit
+    pub const fn receive_time_pu(&self) -> u64 {
+        self.receive_time_pu
+    }
+    pub const fn system_time_offset(&self) -> u64 {
+        self.system_time_offset
+    }
+    pub const fn system_time_delay(&self) -> u32 {
+        self.system_time_delay
+    }
+    /// Deviation from the reference clock in ns, bit 31 is the sign
+    pub const fn system_time_difference(&self) -> u32 {
+        self.system_time_difference
+    }
+}
+
+impl EscRegister for DcTimes {
+    const ADDRESS: u16 = 0x0900;
+    const SIZE: usize = 0x30;
+
+    fn from_bytes(x: &[u8]) -> Self {
+        DcTimes {
+            receive_time: [u32_at(x, 0), u32_at(x, 4), u32_at(x, 8), u32_at(x, 12)],
+            system_time: u64_at(x, 0x10),
+            receive_time_pu: u64_at(x, 0x18),
+            system_time_offset: u64_at(x, 0x20),
+            system_time_delay: u32_at(x, 0x28),
+            system_time_difference: u32_at(x, 0x2c),
+        }
+    }
+}
+
+/// Address of the `index`-th instance, registers without a stride have a single instance
+fn register_address<R: EscRegister>(index: u16) -> result::Result<u16, DatagramError> {
+    match (index, R::STRIDE) {
+        (0, _) => Some(R::ADDRESS),
+        (_, 0) => None,
+        (index, stride) => index
+            .checked_mul(stride)
+            .and_then(|x| x.checked_add(R::ADDRESS)),
+    }
+    .ok_or(DatagramError::RegisterIndex(index))
+}
+
+impl<'a> Context<'a> {
+    fn station_addr(&mut self, slave: u16) -> result::Result<u16, DatagramError> {
+        match self.has_slave(slave) {
+            true => Ok(self.slave_mut(slave).configured_addr()),
+            false => Err(DatagramError::UnknownSlave(slave)),
+        }
+    }
+
+    /// Read the register of the slave by its configured address
+    pub fn read_register<R: EscRegister>(
+        &mut self,
+        slave: u16,
+    ) -> result::Result<R, DatagramError> {
+        self.read_register_at(slave, 0)
+    }
+
+    /// Read the `index`-th instance of a repeated register, e.g. an FMMU or a sync manager
+    pub fn read_register_at<R: EscRegister>(
+        &mut self,
+        slave: u16,
+        index: u16,
+    ) -> result::Result<R, DatagramError> {
+        let station = self.station_addr(slave)?;
+        let ado = register_address::<R>(index)?;
+        match self.fprd(station, ado, R::SIZE, EC_TIMEOUTRET)? {
+            (data, 1) => Ok(R::from_bytes(&data)),
+            (_, wkc) => Err(DatagramError::WorkingCounter(wkc)),
+        }
+    }
+
+    /// Write the register of the slave by its configured address
+    pub fn write_register<R: WritableRegister>(
+        &mut self,
+        slave: u16,
+        value: &R,
+    ) -> result::Result<(), DatagramError> {
+        self.write_register_at(slave, 0, value)
+    }
+
+    pub fn write_register_at<R: WritableRegister>(
+        &mut self,
+        slave: u16,
+        index: u16,
+        value: &R,
+    ) -> result::Result<(), DatagramError> {
+        let station = self.station_addr(slave)?;
+        let ado = register_address::<R>(index)?;
+        match self.fpwr(station, ado, &value.to_bytes(), EC_TIMEOUTRET)? {
+            1 => Ok(()),
+            wkc => Err(DatagramError::WorkingCounter(wkc)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+
+    #[test]
+    fn unknown_slaves_are_rejected() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        let mut c = buffers.context();
+
+        for &slave in [0, 3, 0xffff].iter() {
+            let err = DatagramError::UnknownSlave(slave);
+            assert_eq!(c.read_register::<AlStatus>(slave).unwrap_err(), err);
+            assert_eq!(c.read_register_at::<Fmmu>(slave, 1).unwrap_err(), err);
+            let value = WatchdogTime::default();
+            assert_eq!(c.write_register(slave, &value).unwrap_err(), err);
+            assert_eq!(c.write_register_at(slave, 1, &value).unwrap_err(), err);
+            assert_eq!(c.read_error_counters(slave).unwrap_err(), err);
+            assert_eq!(c.clear_error_counters(slave).unwrap_err(), err);
+        }
+    }
+
+    #[test]
+    fn register_instances() {
+        assert_eq!(register_address::<Fmmu>(0), Ok(0x0600));
+        assert_eq!(register_address::<Fmmu>(2), Ok(0x0620));
+        assert_eq!(register_address::<SyncManager>(3), Ok(0x0818));
+        assert_eq!(register_address::<WatchdogTime>(1), Ok(0x0420));
+        assert_eq!(
+            register_address::<AlStatus>(1),
+            Err(DatagramError::RegisterIndex(1))
+        );
+        assert_eq!(
+            register_address::<SyncManager>(0x2000),
+            Err(DatagramError::RegisterIndex(0x2000))
+        );
+        assert_eq!(
+            register_address::<Fmmu>(0x0fa0),
+            Err(DatagramError::RegisterIndex(0x0fa0))
+        );
+    }
+
+    #[test]
+    fn port_accessors_are_bounded() {
+        let status = DlStatus::from_bytes(&[0x30, 0x0a]);
+        assert_eq!(status.link(0), Some(true));
+        assert_eq!(status.link(2), Some(false));
+        assert_eq!(status.communication(0), Some(true));
+        assert_eq!(status.communication(1), Some(true));
+        assert_eq!(status.link(4), None);
+        assert_eq!(status.communication(200), None);
+
+        let control = DlControl::new(0x0000_3c00);
+        assert_eq!(control.loop_port(0), Some(0));
+        assert_eq!(control.loop_port(1), Some(3));
+        assert_eq!(control.loop_port(2), Some(3));
+        assert_eq!(control.loop_port(4), None);
+
+        let mut bytes = [0; ErrorCounters::SIZE];
+        bytes[6] = 5;
+        bytes[0x13] = 1;
+        let counters = ErrorCounters::from_bytes(&bytes);
+        assert_eq!(counters.invalid_frame(3), Some(5));
+        assert_eq!(counters.lost_link(3), Some(1));
+        assert_eq!(counters.rx_error(4), None);
+        assert_eq!(counters.to_bytes(), bytes);
+
+        let times = DcTimes::from_bytes(&[0; DcTimes::SIZE]);
+        assert_eq!(times.receive_time(3), Some(0));
+        assert_eq!(times.receive_time(4), None);
+    }
+}
@@ -0,0 +1,876 @@
+use crate::{error::XmlError, xml, Slave, SyncManagerType};
+use roxmltree::{Document, Node};
+use std::result;
+
+/** Locale of the names preferred over the other ones */
+const ESI_LCID_EN: &str = "1033";
+/** Mailbox protocol bits as used by the SII */
+const MBX_AOE: u16 = 0x01;
+const MBX_EOE: u16 = 0x02;
+const MBX_COE: u16 = 0x04;
+const MBX_FOE: u16 = 0x08;
+const MBX_SOE: u16 = 0x10;
+const MBX_VOE: u16 = 0x20;
+
+/// English name if present, the first one otherwise
+fn name(node: Node) -> String {
+    let mut names = xml::children(node, "Name");
+    let first = names.next();
+    first
+        .into_iter()
+        .chain(names)
+        .find(|x| x.attribute("LcId") == Some(ESI_LCID_EN))
+        .or(first)
+        .and_then(|x| x.text())
+        .map(|x| x.trim().to_owned())
+        .unwrap_or_default()
+}
+
+fn attribute_bool(node: Node, name: &str) -> bool {
+    node.attribute(name).is_some_and(xml::parse_bool)
+}
+
+/// Entry of a PDO
+#[derive(Debug, PartialEq, Clone)]
+pub struct EsiPdoEntry {
+    index: u16,
+    subindex: u8,
+    bit_len: u16,
+    name: String,
+    data_type: Option<String>,
+}
+
+impl EsiPdoEntry {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub const fn subindex(&self) -> u8 {
+        self.subindex
+    }
+    pub const fn bit_len(&self) -> u16 {
+        self.bit_len
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn data_type(&self) -> Option<&str> {
+        self.data_type.as_deref()
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EsiPdoEntry {
+            index: xml::number(node, "Index")?,
+            subindex: xml::opt_number(node, "SubIndex")?.unwrap_or(0),
+            bit_len: xml::number(node, "BitLen")?,
+            name: name(node),
+            data_type: xml::text(node, "DataType").map(str::to_owned),
+        })
+    }
+}
+
+/// TxPDO or RxPDO as described by the vendor
+#[derive(Debug, PartialEq, Clone)]
+pub struct EsiPdo {
+    index: u16,
+    name: String,
+    sm: Option<u8>,
+    fixed: bool,
+    mandatory: bool,
+    entries: Vec<EsiPdoEntry>,
+    exclude: Vec<u16>,
+}
+
+impl EsiPdo {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Sync manager the PDO is assigned to by default, `None` if not assigned
+    pub const fn sm(&self) -> Option<u8> {
+        self.sm
+    }
+    /// Mapping of the PDO cannot be changed
+    pub const fn fixed(&self) -> bool {
+        self.fixed
+    }
+    pub const fn mandatory(&self) -> bool {
+        self.mandatory
+    }
+    pub fn entries(&self) -> &[EsiPdoEntry] {
+        &self.entries
+    }
+    /// PDOs which cannot be assigned together with this one
+    pub fn exclude(&self) -> &[u16] {
+        &self.exclude
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EsiPdo {
+            index: xml::number(node, "Index")?,
+            name: name(node),
+            sm: xml::opt_attribute(node, "Sm")?,
+            fixed: attribute_bool(node, "Fixed"),
+            mandatory: attribute_bool(node, "Mandatory"),
+            entries: xml::children(node, "Entry")
+                .map(EsiPdoEntry::parse)
+                .collect::<result::Result<_, _>>()?,
+            exclude: xml::children(node, "Exclude")
+                .filter_map(|x| x.text())
+                .map(|x| {
+                    xml::parse_number(x)
+                        .ok_or_else(|| XmlError::InvalidValue("Exclude".into(), x.into()))
+                })
+                .collect::<result::Result<_, _>>()?,
+        })
+    }
+}
+
+/// Sync manager as described by the vendor
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EsiSyncManager {
+    sm_type: SyncManagerType,
+    start_address: u16,
+    control_byte: u8,
+    default_size: Option<u16>,
+    enable: bool,
+}
+
+impl EsiSyncManager {
+    pub const fn sm_type(&self) -> SyncManagerType {
+        self.sm_type
+    }
+    pub const fn start_address(&self) -> u16 {
+        self.start_address
+    }
+    pub const fn control_byte(&self) -> u8 {
+        self.control_byte
+    }
+    pub const fn default_size(&self) -> Option<u16> {
+        self.default_size
+    }
+    pub const fn enable(&self) -> bool {
+        self.enable
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        let sm_type = match node.text().map(str::trim) {
+            Some("MBoxOut") => SyncManagerType::MailboxOut,
+            Some("MBoxIn") => SyncManagerType::MailboxIn,
+            Some("Outputs") => SyncManagerType::Outputs,
+            Some("Inputs") => SyncManagerType::Inputs,
+            _ => SyncManagerType::Unused,
+        };
+        Ok(EsiSyncManager {
+            sm_type,
+            start_address: xml::opt_attribute(node, "StartAddress")?.unwrap_or(0),
+            control_byte: xml::opt_attribute(node, "ControlByte")?.unwrap_or(0),
+            default_size: xml::opt_attribute(node, "DefaultSize")?,
+            enable: attribute_bool(node, "Enable"),
+        })
+    }
+}
+
+/// Distributed clock operation mode
+#[derive(Debug, PartialEq, Clone)]
+pub struct EsiOpMode {
+    name: String,
+    desc: Option<String>,
+    assign_activate: u16,
+    cycle_time_sync0: Option<u32>,
+    cycle_time_sync0_factor: i32,
+    shift_time_sync0: i32,
+    cycle_time_sync1: Option<u32>,
+    cycle_time_sync1_factor: i32,
+    shift_time_sync1: i32,
+}
+
+impl EsiOpMode {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn desc(&self) -> Option<&str> {
+        self.desc.as_deref()
+    }
+    /// Value of the DC activation register 0x980
+    pub const fn assign_activate(&self) -> u16 {
+        self.assign_activate
+    }
+    /// SYNC0 cycle time in ns, 0 stands for the cycle time of the master
+    pub const fn cycle_time_sync0(&self) -> Option<u32> {
+        self.cycle_time_sync0
+    }
+    /// Multiple of the master cycle time used as SYNC0 cycle time
+    pub const fn cycle_time_sync0_factor(&self) -> i32 {
+        self.cycle_time_sync0_factor
+    }
+    pub const fn shift_time_sync0(&self) -> i32 {
+        self.shift_time_sync0
+    }
+    pub const fn cycle_time_sync1(&self) -> Option<u32> {
+        self.cycle_time_sync1
+    }
+    pub const fn cycle_time_sync1_factor(&self) -> i32 {
+        self.cycle_time_sync1_factor
+    }
+    pub const fn shift_time_sync1(&self) -> i32 {
+        self.shift_time_sync1
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        let factor = |name: &str| -> result::Result<i32, XmlError> {
+            match xml::child(node, name) {
+                Some(x) => Ok(xml::opt_attribute(x, "Factor")?.unwrap_or(0)),
+                None => Ok(0),
+            }
+        };
+        Ok(EsiOpMode {
+            name: xml::text(node, "Name").unwrap_or_default().to_owned(),
+            desc: xml::text(node, "Desc").map(str::to_owned),
+            assign_activate: xml::opt_number(node, "AssignActivate")?.unwrap_or(0),
+            cycle_time_sync0: xml::opt_number(node, "CycleTimeSync0")?,
+            cycle_time_sync0_factor: factor("CycleTimeSync0")?,
+            shift_time_sync0: xml::opt_number(node, "ShiftTimeSync0")?.unwrap_or(0),
+            cycle_time_sync1: xml::opt_number(node, "CycleTimeSync1")?,
+            cycle_time_sync1_factor: factor("CycleTimeSync1")?,
+            shift_time_sync1: xml::opt_number(node, "ShiftTimeSync1")?.unwrap_or(0),
+        })
+    }
+}
+
+/// Mailbox protocols supported by the device
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct EsiMailbox {
+    protocols: u16,
+    sdo_info: bool,
+    pdo_assign: bool,
+    pdo_config: bool,
+    complete_access: bool,
+}
+
+impl EsiMailbox {
+    /// Supported mailbox protocols, the same bits as [`Sii::mailbox_protocols`](crate::Sii::mailbox_protocols)
+    pub const fn protocols(&self) -> u16 {
+        self.protocols
+    }
+    pub const fn sdo_info(&self) -> bool {
+        self.sdo_info
+    }
+    /// PDO assignment may be changed by CoE
+    pub const fn pdo_assign(&self) -> bool {
+        self.pdo_assign
+    }
+    /// PDO mapping may be changed by CoE
+    pub const fn pdo_config(&self) -> bool {
+        self.pdo_config
+    }
+    pub const fn complete_access(&self) -> bool {
+        self.complete_access
+    }
+
+    fn parse(node: Node) -> Self {
+        let mut res = EsiMailbox::default();
+        for x in node.children().filter(|x| x.is_element()) {
+            res.protocols |= match x.tag_name().name() {
+                "AoE" => MBX_AOE,
+                "EoE" => MBX_EOE,
+                "CoE" => {
+                    res.sdo_info = attribute_bool(x, "SdoInfo");
+                    res.pdo_assign = attribute_bool(x, "PdoAssign");
+                    res.pdo_config = attribute_bool(x, "PdoConfig");
+                    res.complete_access = attribute_bool(x, "CompleteAccess");
+                    MBX_COE
+                }
+                "FoE" => MBX_FOE,
+                "SoE" => MBX_SOE,
+                "VoE" => MBX_VOE,
+                _ => 0,
+            };
+        }
+        res
+    }
+}
+
+/// EEPROM content as provided by the vendor
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct EsiEeprom {
+    byte_size: Option<u32>,
+    config_data: Vec<u8>,
+    boot_strap: Vec<u8>,
+}
+
+impl EsiEeprom {
+    pub const fn byte_size(&self) -> Option<u32> {
+        self.byte_size
+    }
+    /// Beginning of the SII config area, see [`Sii::config_checksum`](crate::Sii::config_checksum)
+    pub fn config_data(&self) -> &[u8] {
+        &self.config_data
+    }
+    pub fn boot_strap(&self) -> &[u8] {
+        &self.boot_strap
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        Ok(EsiEeprom {
+            byte_size: xml::opt_number(node, "ByteSize")?,
+            config_data: xml::hex_data(node, "ConfigData")?,
+            boot_strap: xml::hex_data(node, "BootStrap")?,
+        })
+    }
+}
+
+/// Subindex of a CoE object
+#[derive(Debug, PartialEq, Clone)]
+pub struct EsiSubItem {
+    subindex: u8,
+    name: String,
+    data_type: Option<String>,
+    bit_size: Option<u16>,
+}
+
+impl EsiSubItem {
+    pub const fn subindex(&self) -> u8 {
+        self.subindex
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn data_type(&self) -> Option<&str> {
+        self.data_type.as_deref()
+    }
+    pub const fn bit_size(&self) -> Option<u16> {
+        self.bit_size
+    }
+}
+
+/// CoE object of the device dictionary
+#[derive(Debug, PartialEq, Clone)]
+pub struct EsiObject {
+    index: u16,
+    name: String,
+    data_type: Option<String>,
+    bit_size: Option<u16>,
+    sub_items: Vec<EsiSubItem>,
+}
+
+impl EsiObject {
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn data_type(&self) -> Option<&str> {
+        self.data_type.as_deref()
+    }
+    pub const fn bit_size(&self) -> Option<u16> {
+        self.bit_size
+    }
+    /// Subindices of records and arrays, empty for simple objects
+    pub fn sub_items(&self) -> &[EsiSubItem] {
+        &self.sub_items
+    }
+    pub fn sub_item(&self, subindex: u8) -> Option<&EsiSubItem> {
+        self.sub_items.iter().find(|x| x.subindex == subindex)
+    }
+
+    fn parse(node: Node, data_types: &[Node]) -> result::Result<Self, XmlError> {
+        let data_type = xml::text(node, "Type");
+        let mut sub_items = Vec::new();
+
+        // Subindex numbers are given by the data type, the object gives the names only
+        let dt = data_type.and_then(|t| {
+            data_types
+                .iter()
+                .find(|&&x| xml::text(x, "Name") == Some(t))
+        });
+        if let Some(&dt) = dt {
+            for x in xml::children(dt, "SubItem") {
+                let subindex = match xml::opt_number(x, "SubIdx")? {
+                    Some(i) => i,
+                    None => continue,
+                };
+                sub_items.push(EsiSubItem {
+                    subindex,
+                    name: name(x),
+                    data_type: xml::text(x, "Type").map(str::to_owned),
+                    bit_size: xml::opt_number(x, "BitSize")?,
+                });
+            }
+        }
+        let info = xml::child(node, "Info");
+        for (i, x) in info
+            .iter()
+            .flat_map(|&x| xml::children(x, "SubItem"))
+            .enumerate()
+        {
+            let n = name(x);
+            match sub_items.get_mut(i) {
+                Some(item) if !n.is_empty() => item.name = n,
+                Some(_) => (),
+                None => sub_items.push(EsiSubItem {
+                    subindex: i as u8,
+                    name: n,
+                    data_type: None,
+                    bit_size: None,
+                }),
+            }
+        }
+
+        Ok(EsiObject {
+            index: xml::number(node, "Index")?,
+            name: name(node),
+            data_type: data_type.map(str::to_owned),
+            bit_size: xml::opt_number(node, "BitSize")?,
+            sub_items,
+        })
+    }
+}
+
+/// Device described by an ESI file
+#[derive(Debug, PartialEq, Clone)]
+pub struct EsiDevice {
+    type_name: String,
+    product_code: u32,
+    revision: u32,
+    name: String,
+    group_type: Option<String>,
+    sync_managers: Vec<EsiSyncManager>,
+    tx_pdos: Vec<EsiPdo>,
+    rx_pdos: Vec<EsiPdo>,
+    op_modes: Vec<EsiOpMode>,
+    mailbox: Option<EsiMailbox>,
+    eeprom: Option<EsiEeprom>,
+    objects: Vec<EsiObject>,
+}
+
+impl EsiDevice {
+    /// Short type name such as EL1008
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+    pub const fn product_code(&self) -> u32 {
+        self.product_code
+    }
+    pub const fn revision(&self) -> u32 {
+        self.revision
+    }
+    /// Descriptive name of the device
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn group_type(&self) -> Option<&str> {
+        self.group_type.as_deref()
+    }
+    pub fn sync_managers(&self) -> &[EsiSyncManager] {
+        &self.sync_managers
+    }
+    pub fn tx_pdos(&self) -> &[EsiPdo] {
+        &self.tx_pdos
+    }
+    pub fn rx_pdos(&self) -> &[EsiPdo] {
+        &self.rx_pdos
+    }
+    /// PDOs assigned to sync managers by default
+    pub fn default_pdos(&self) -> impl Iterator<Item = &EsiPdo> {
+        self.rx_pdos
+            .iter()
+            .chain(self.tx_pdos.iter())
+            .filter(|x| x.sm.is_some())
+    }
+    /// Distributed clock operation modes, empty if DC is not supported
+    pub fn op_modes(&self) -> &[EsiOpMode] {
+        &self.op_modes
+    }
+    pub const fn mailbox(&self) -> Option<&EsiMailbox> {
+        self.mailbox.as_ref()
+    }
+    pub const fn eeprom(&self) -> Option<&EsiEeprom> {
+        self.eeprom.as_ref()
+    }
+    pub fn objects(&self) -> &[EsiObject] {
+        &self.objects
+    }
+    pub fn object(&self, index: u16) -> Option<&EsiObject> {
+        self.objects.iter().find(|x| x.index == index)
+    }
+    /// Name of the object or of its subindex, PDO entries are looked up if the dictionary
+    /// is not given
+    pub fn object_name(&self, index: u16, subindex: u8) -> Option<&str> {
+        let from_dictionary = self
+            .object(index)
+            .and_then(|x| match x.sub_items.is_empty() {
+                true if subindex == 0 => Some(x.name()),
+                true => None,
+                false => x.sub_item(subindex).map(EsiSubItem::name),
+            });
+        from_dictionary.or_else(|| {
+            self.rx_pdos
+                .iter()
+                .chain(self.tx_pdos.iter())
+                .flat_map(|x| x.entries.iter())
+                .find(|x| x.index == index && x.subindex == subindex)
+                .map(EsiPdoEntry::name)
+        })
+    }
+
+    fn parse(node: Node) -> result::Result<Self, XmlError> {
+        let type_node = xml::child(node, "Type").ok_or_else(|| XmlError::Missing("Type".into()))?;
+        let mut device = EsiDevice {
+            type_name: type_node.text().unwrap_or_default().trim().to_owned(),
+            product_code: xml::opt_attribute(type_node, "ProductCode")?
+                .ok_or_else(|| XmlError::Missing("ProductCode".into()))?,
+            revision: xml::opt_attribute(type_node, "RevisionNo")?.unwrap_or(0),
+            name: name(node),
+            group_type: xml::text(node, "GroupType").map(str::to_owned),
+            sync_managers: Vec::new(),
+            tx_pdos: Vec::new(),
+            rx_pdos: Vec::new(),
+            op_modes: Vec::new(),
+            mailbox: None,
+            eeprom: None,
+            objects: Vec::new(),
+        };
+
+        for x in node.children().filter(|x| x.is_element()) {
+            match x.tag_name().name() {
+                "Sm" => device.sync_managers.push(EsiSyncManager::parse(x)?),
+                "TxPdo" => device.tx_pdos.push(EsiPdo::parse(x)?),
+                "RxPdo" => device.rx_pdos.push(EsiPdo::parse(x)?),
+                "Mailbox" => device.mailbox = Some(EsiMailbox::parse(x)),
+                "Eeprom" => device.eeprom = Some(EsiEeprom::parse(x)?),
+                "Dc" => {
+                    device.op_modes = xml::children(x, "OpMode")
+                        .map(EsiOpMode::parse)
+                        .collect::<result::Result<_, _>>()?
+                }
+                _ => (),
+            }
+        }
+
+        let dictionary = xml::child(node, "Profile").and_then(|x| xml::child(x, "Dictionary"));
+        if let Some(dictionary) = dictionary {
+            let data_types: Vec<Node> = xml::child(dictionary, "DataTypes")
+                .iter()
+                .flat_map(|&x| xml::children(x, "DataType"))
+                .collect();
+            let objects = xml::child(dictionary, "Objects");
+            for x in objects.iter().flat_map(|&x| xml::children(x, "Object")) {
+                device.objects.push(EsiObject::parse(x, &data_types)?);
+            }
+        }
+
+        Ok(device)
+    }
+}
+
+/// EtherCat slave information file of a vendor
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Esi {
+    vendor_id: u32,
+    vendor_name: String,
+    devices: Vec<EsiDevice>,
+}
+
+impl Esi {
+    pub const fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+    pub fn vendor_name(&self) -> &str {
+        &self.vendor_name
+    }
+    pub fn devices(&self) -> &[EsiDevice] {
+        &self.devices
+    }
+
+    pub fn parse(text: &str) -> result::Result<Esi, XmlError> {
+        let doc = Document::parse(text).map_err(|err| XmlError::Parse(err.to_string()))?;
+        let root = doc.root_element();
+        let vendor =
+            xml::child(root, "Vendor").ok_or_else(|| XmlError::Missing("Vendor".into()))?;
+        let devices = xml::child(root, "Descriptions").and_then(|x| xml::child(x, "Devices"));
+
+        Ok(Esi {
+            vendor_id: xml::number(vendor, "Id")?,
+            vendor_name: name(vendor),
+            devices: devices
+                .iter()
+                .flat_map(|&x| xml::children(x, "Device"))
+                .map(EsiDevice::parse)
+                .collect::<result::Result<_, _>>()?,
+        })
+    }
+
+    /// Device with the identity, the newest revision of the product is used when there is no
+    /// exact match
+    pub fn device(&self, vendor_id: u32, product_code: u32, revision: u32) -> Option<&EsiDevice> {
+        if vendor_id != self.vendor_id {
+            return None;
+        }
+        let same_product = self
+            .devices
+            .iter()
+            .filter(|x| x.product_code == product_code);
+        same_product
+            .clone()
+            .find(|x| x.revision == revision)
+            .or_else(|| same_product.max_by_key(|x| x.revision))
+    }
+
+    /// Device description of the slave found by [`Context::config_init`](crate::Context::config_init)
+    pub fn slave_device(&self, slave: &Slave) -> Option<&EsiDevice> {
+        self.device(
+            slave.eep_manufacturer(),
+            slave.eep_id(),
+            slave.eep_revision(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESI: &str = include_str!("testdata/esi.xml");
+
+    fn el3102(esi: &Esi) -> &EsiDevice {
+        &esi.devices()[0]
+    }
+
+    #[test]
+    fn names_prefer_english() {
+        let esi = Esi::parse(ESI).unwrap();
+        assert_eq!(esi.vendor_id(), 2);
+        assert_eq!(esi.vendor_name(), "Beckhoff Automation GmbH & Co. KG");
+        assert_eq!(esi.devices().len(), 3);
+
+        let device = el3102(&esi);
+        assert_eq!(device.type_name(), "EL3102");
+        assert_eq!(device.name(), "EL3102 2Ch. Ana. Input +/-10V, Diff.");
+        assert_eq!(device.group_type(), Some("AnaIn"));
+        assert_eq!(device.rx_pdos()[0].name(), "Control");
+
+        // No English name, the first one is taken
+        assert_eq!(
+            esi.devices()[1].name(),
+            "EL3102 2K. Ana. Eingang +/-10V, Diff."
+        );
+        let el1008 = &esi.devices()[2];
+        assert_eq!(el1008.name(), "EL1008 8Ch. Dig. Input 24V, 3ms");
+        assert_eq!(el1008.revision(), 0);
+        assert_eq!(el1008.group_type(), None);
+        assert_eq!(el1008.mailbox(), None);
+        assert!(el1008.objects().is_empty());
+    }
+
+    #[test]
+    fn sync_managers() {
+        let esi = Esi::parse(ESI).unwrap();
+        let sms = el3102(&esi).sync_managers();
+        let types: Vec<SyncManagerType> = sms.iter().map(|x| x.sm_type()).collect();
+        assert_eq!(
+            types,
+            [
+                SyncManagerType::MailboxOut,
+                SyncManagerType::MailboxIn,
+                SyncManagerType::Outputs,
+                SyncManagerType::Inputs
+            ]
+        );
+        assert_eq!(sms[0].start_address(), 0x1000);
+        assert_eq!(sms[0].control_byte(), 0x26);
+        assert_eq!(sms[0].default_size(), Some(128));
+        assert!(sms[0].enable());
+        assert_eq!(sms[2].default_size(), None);
+        assert!(!sms[2].enable());
+    }
+
+    #[test]
+    fn pdos() {
+        let esi = Esi::parse(ESI).unwrap();
+        let device = el3102(&esi);
+
+        let rx = &device.rx_pdos()[0];
+        assert_eq!((rx.index(), rx.sm()), (0x1600, Some(2)));
+        assert!(!rx.fixed() && !rx.mandatory());
+        assert!(rx.exclude().is_empty());
+        assert_eq!(rx.entries()[0].data_type(), Some("BOOL"));
+
+        let tx = device.tx_pdos();
+        assert_eq!(tx.len(), 2);
+        assert_eq!((tx[0].index(), tx[0].sm()), (0x1a00, Some(3)));
+        assert!(tx[0].fixed() && tx[0].mandatory());
+        assert_eq!(tx[0].exclude(), [0x1a01, 0x1a02]);
+        let entry = &tx[0].entries()[1];
+        assert_eq!(
+            (entry.index(), entry.subindex(), entry.bit_len()),
+            (0x3101, 2, 16)
+        );
+        assert_eq!((entry.name(), entry.data_type()), ("Value", Some("INT")));
+
+        assert_eq!(tx[1].sm(), None);
+        assert!(!tx[1].fixed());
+        assert_eq!(tx[1].exclude(), [0x1a00]);
+        let padding = &tx[1].entries()[0];
+        assert_eq!((padding.index(), padding.subindex()), (0, 0));
+        assert_eq!((padding.name(), padding.data_type()), ("", None));
+
+        let default: Vec<u16> = device.default_pdos().map(EsiPdo::index).collect();
+        assert_eq!(default, [0x1600, 0x1a00]);
+    }
+
+    #[test]
+    fn mailbox_dc_and_eeprom() {
+        let esi = Esi::parse(ESI).unwrap();
+        let device = el3102(&esi);
+
+        let mailbox = device.mailbox().unwrap();
+        assert_eq!(mailbox.protocols(), MBX_COE | MBX_FOE);
+        assert!(mailbox.sdo_info());
+        assert!(!mailbox.pdo_assign());
+        assert!(!mailbox.pdo_config());
+        assert!(mailbox.complete_access());
+
+        let mode = &device.op_modes()[0];
+        assert_eq!((mode.name(), mode.desc()), ("DcSync", Some("DC-Synchron")));
+        assert_eq!(mode.assign_activate(), 0x300);
+        assert_eq!(mode.cycle_time_sync0(), Some(0));
+        assert_eq!(mode.cycle_time_sync0_factor(), 1);
+        assert_eq!(mode.cycle_time_sync1_factor(), -1);
+        assert_eq!(mode.shift_time_sync1(), -500);
+
+        let eeprom = device.eeprom().unwrap();
+        assert_eq!(eeprom.byte_size(), Some(2048));
+        assert_eq!(eeprom.config_data(), [0x08, 0x0c, 0, 0, 0x44, 0, 0, 0]);
+        assert_eq!(eeprom.boot_strap().len(), 8);
+    }
+
+    #[test]
+    fn mailbox_protocol_bits() {
+        let doc =
+            Document::parse("<Mailbox><AoE/><EoE/><CoE/><FoE/><SoE/><VoE/></Mailbox>").unwrap();
+        let mailbox = EsiMailbox::parse(doc.root_element());
+        assert_eq!(mailbox.protocols(), 0x3f);
+        assert!(!mailbox.sdo_info());
+
+        let doc = Document::parse("<Mailbox/>").unwrap();
+        assert_eq!(EsiMailbox::parse(doc.root_element()).protocols(), 0);
+    }
+
+    #[test]
+    fn dictionary_merges_sub_items() {
+        let esi = Esi::parse(ESI).unwrap();
+        let device = el3102(&esi);
+        assert_eq!(device.objects().len(), 2);
+
+        let simple = device.object(0x1000).unwrap();
+        assert_eq!(simple.data_type(), Some("UDINT"));
+        assert_eq!(simple.bit_size(), Some(32));
+        assert!(simple.sub_items().is_empty());
+
+        let record = device.object(0x1a00).unwrap();
+        let items: Vec<(u8, &str, Option<u16>)> = record
+            .sub_items()
+            .iter()
+            .map(|x| (x.subindex(), x.name(), x.bit_size()))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                (0, "Number of entries", Some(8)),
+                (1, "Status", Some(32)),
+                // Unnamed in the Info, the name of the data type is kept
+                (2, "SubIndex 002", Some(32)),
+                // Only given by the Info
+                (3, "Reserved", None),
+            ]
+        );
+        assert_eq!(record.sub_item(1).unwrap().data_type(), Some("UDINT"));
+        assert_eq!(record.sub_item(3).unwrap().data_type(), None);
+        assert_eq!(record.sub_item(4), None);
+    }
+
+    #[test]
+    fn object_names() {
+        let esi = Esi::parse(ESI).unwrap();
+        let device = el3102(&esi);
+
+        assert_eq!(device.object_name(0x1000, 0), Some("Device type"));
+        assert_eq!(device.object_name(0x1000, 1), None);
+        assert_eq!(device.object_name(0x1a00, 1), Some("Status"));
+        assert_eq!(device.object_name(0x1a00, 4), None);
+        // Not in the dictionary, taken from the PDO entries
+        assert_eq!(device.object_name(0x3101, 2), Some("Value"));
+        assert_eq!(device.object_name(0x7000, 1), Some("Enable"));
+        assert_eq!(device.object_name(0x7000, 2), None);
+    }
+
+    #[test]
+    fn device_lookup() {
+        let esi = Esi::parse(ESI).unwrap();
+        let revision = |x: Option<&EsiDevice>| x.map(EsiDevice::revision);
+
+        assert_eq!(
+            revision(esi.device(2, 0x0c1e_3052, 0x0010_0000)),
+            Some(0x0010_0000)
+        );
+        assert_eq!(
+            revision(esi.device(2, 0x0c1e_3052, 0x0011_0000)),
+            Some(0x0011_0000)
+        );
+        // Unknown revisions fall back to the newest one
+        assert_eq!(
+            revision(esi.device(2, 0x0c1e_3052, 0x0014_0000)),
+            Some(0x0011_0000)
+        );
+        assert_eq!(revision(esi.device(2, 0x0c1e_3052, 0)), Some(0x0011_0000));
+        assert_eq!(revision(esi.device(2, 0x03f0_3052, 0x0018_0000)), Some(0));
+
+        assert_eq!(esi.device(3, 0x0c1e_3052, 0x0010_0000), None);
+        assert_eq!(esi.device(2, 0x0c1e_3053, 0x0010_0000), None);
+    }
+
+    #[test]
+    fn malformed_esi() {
+        let esi = |devices: &str| {
+            Esi::parse(&format!(
+                "<EtherCATInfo><Vendor><Id>2</Id></Vendor><Descriptions><Devices>{}\
+                 </Devices></Descriptions></EtherCATInfo>",
+                devices
+            ))
+        };
+
+        assert!(esi("").unwrap().devices().is_empty());
+        assert!(matches!(esi("<Device>"), Err(XmlError::Parse(_))));
+        assert_eq!(
+            Esi::parse("<EtherCATInfo/>"),
+            Err(XmlError::Missing("Vendor".into()))
+        );
+        assert_eq!(
+            esi("<Device><Name>EL1008</Name></Device>"),
+            Err(XmlError::Missing("Type".into()))
+        );
+        assert_eq!(
+            esi("<Device><Type RevisionNo=\"1\">EL1008</Type></Device>"),
+            Err(XmlError::Missing("ProductCode".into()))
+        );
+        assert_eq!(
+            esi("<Device><Type ProductCode=\"EL1008\">EL1008</Type></Device>"),
+            Err(XmlError::InvalidValue(
+                "ProductCode".into(),
+                "EL1008".into()
+            ))
+        );
+        assert_eq!(
+            esi("<Device><Type ProductCode=\"1\"/><TxPdo><Index>1</Index>\
+                 <Exclude>x</Exclude></TxPdo></Device>"),
+            Err(XmlError::InvalidValue("Exclude".into(), "x".into()))
+        );
+        assert_eq!(
+            esi("<Device><Type ProductCode=\"1\"/><RxPdo><Name>Out</Name></RxPdo></Device>"),
+            Err(XmlError::Missing("Index".into()))
+        );
+    }
+}
@@ -0,0 +1,420 @@
+use crate::{Context, Slave};
+use std::fmt;
+
+/// Slave declared in an [`ExpectedNetwork`]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ExpectedSlave {
+    vendor_id: u32,
+    product_code: u32,
+    revision: Option<u32>,
+    alias: Option<u16>,
+    parent: Option<u16>,
+    name: Option<String>,
+}
+
+impl ExpectedSlave {
+    pub fn new(vendor_id: u32, product_code: u32) -> Self {
+        ExpectedSlave {
+            vendor_id,
+            product_code,
+            ..Default::default()
+        }
+    }
+
+    pub const fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+    pub const fn product_code(&self) -> u32 {
+        self.product_code
+    }
+    pub const fn revision(&self) -> Option<u32> {
+        self.revision
+    }
+    pub const fn alias(&self) -> Option<u16> {
+        self.alias
+    }
+    /// Expected parent slave, 0 for the master
+    pub const fn parent(&self) -> Option<u16> {
+        self.parent
+    }
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Require the exact revision, any revision is accepted by default
+    pub fn set_revision(&mut self, revision: u32) -> &mut Self {
+        self.revision = Some(revision);
+        self
+    }
+    pub fn set_alias(&mut self, alias: u16) -> &mut Self {
+        self.alias = Some(alias);
+        self
+    }
+    /// Require the slave to be connected to the parent, see [`Slave::parent`]
+    pub fn set_parent(&mut self, parent: u16) -> &mut Self {
+        self.parent = Some(parent);
+        self
+    }
+    /// Name used in reports only
+    pub fn set_name(&mut self, name: &str) -> &mut Self {
+        self.name = Some(name.to_owned());
+        self
+    }
+
+    fn same_device(&self, slave: &Slave) -> bool {
+        self.vendor_id == slave.eep_manufacturer() && self.product_code == slave.eep_id()
+    }
+}
+
+/// Difference between the expected and the found network, slaves are numbered from 1
+#[derive(Debug, PartialEq, Clone)]
+pub enum ConfigMismatch {
+    /// No slave found for the expected position
+    Missing(u16),
+    /// Slave found beyond the expected ones or not expected at all
+    Extra(u16),
+    /// Expected vendor and product code and the found ones
+    WrongProduct {
+        slave: u16,
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    WrongRevision {
+        slave: u16,
+        expected: u32,
+        found: u32,
+    },
+    WrongAlias {
+        slave: u16,
+        expected: u16,
+        found: u16,
+    },
+    WrongParent {
+        slave: u16,
+        expected: u16,
+        found: u16,
+    },
+    /// Expected slave found at another position
+    WrongPosition { expected: u16, found: u16 },
+    /// Slave is configured more than once
+    Duplicate(u16),
+}
+
+impl fmt::Display for ConfigMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigMismatch::Missing(x) => write!(f, "Slave {} is missing", x),
+            ConfigMismatch::Extra(x) => write!(f, "Slave {} is not expected", x),
+            ConfigMismatch::WrongProduct {
+                slave,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Slave {} is {:08x}:{:08x}, expected {:08x}:{:08x}",
+                slave, found.0, found.1, expected.0, expected.1
+            ),
+            ConfigMismatch::WrongRevision {
+                slave,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Slave {} has revision {:08x}, expected {:08x}",
+                slave, found, expected
+            ),
+            ConfigMismatch::WrongAlias {
+                slave,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Slave {} has alias {}, expected {}",
+                slave, found, expected
+            ),
+            ConfigMismatch::WrongParent {
+                slave,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Slave {} is connected to {}, expected {}",
+                slave, found, expected
+            ),
+            ConfigMismatch::WrongPosition { expected, found } => {
+                write!(f, "Slave expected at {} is found at {}", expected, found)
+            }
+            ConfigMismatch::Duplicate(x) => write!(f, "Slave {} is configured twice", x),
+        }
+    }
+}
+
+/// Declared layout of the network in processing order, see [`Context::verify_config`]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ExpectedNetwork {
+    slaves: Vec<ExpectedSlave>,
+}
+
+impl ExpectedNetwork {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn slaves(&self) -> &[ExpectedSlave] {
+        &self.slaves
+    }
+    pub fn push(&mut self, slave: ExpectedSlave) -> &mut Self {
+        self.slaves.push(slave);
+        self
+    }
+
+    fn compare(
+        expected: &ExpectedSlave,
+        slave: &Slave,
+        position: u16,
+        res: &mut Vec<ConfigMismatch>,
+    ) {
+        match expected.revision {
+            Some(x) if x != slave.eep_revision() => res.push(ConfigMismatch::WrongRevision {
+                slave: position,
+                expected: x,
+                found: slave.eep_revision(),
+            }),
+            _ => (),
+        }
+        match expected.alias {
+            Some(x) if x != slave.alias() => res.push(ConfigMismatch::WrongAlias {
+                slave: position,
+                expected: x,
+                found: slave.alias(),
+            }),
+            _ => (),
+        }
+        match expected.parent {
+            Some(x) if x != slave.parent() => res.push(ConfigMismatch::WrongParent {
+                slave: position,
+                expected: x,
+                found: slave.parent(),
+            }),
+            _ => (),
+        }
+    }
+
+    /// Compare the found slaves with the expected ones, no mismatches means the network is as
+    /// declared
+    pub fn verify(&self, slaves: &[Slave]) -> Vec<ConfigMismatch> {
+        let mut res = Vec::new();
+        let mut pending_expected = Vec::new();
+        let mut pending_found: Vec<usize> = (self.slaves.len()..slaves.len()).collect();
+
+        for (i, expected) in self.slaves.iter().enumerate() {
+            match slaves.get(i) {
+                Some(slave) if expected.same_device(slave) => {
+                    ExpectedNetwork::compare(expected, slave, i as u16 + 1, &mut res)
+                }
+                Some(_) => {
+                    pending_expected.push(i);
+                    pending_found.push(i);
+                }
+                None => pending_expected.push(i),
+            }
+        }
+        pending_found.sort_unstable();
+
+        // Swapped modules show up as the expected device at another position
+        let mut unplaced = Vec::new();
+        for i in pending_expected {
+            let expected = &self.slaves[i];
+            let found = pending_found.iter().position(|&j| {
+                j != i
+                    && expected.same_device(&slaves[j])
+                    && expected.alias.map_or(true, |x| x == slaves[j].alias())
+            });
+            match found {
+                Some(k) => {
+                    let j = pending_found.remove(k);
+                    res.push(ConfigMismatch::WrongPosition {
+                        expected: i as u16 + 1,
+                        found: j as u16 + 1,
+                    });
+                    // A moved slave still has to match the rest of its declaration
+                    ExpectedNetwork::compare(expected, &slaves[j], j as u16 + 1, &mut res);
+                }
+                None => unplaced.push(i),
+            }
+        }
+
+        for i in unplaced {
+            let expected = &self.slaves[i];
+            match pending_found.iter().position(|&j| j == i) {
+                Some(k) => {
+                    pending_found.remove(k);
+                    res.push(ConfigMismatch::WrongProduct {
+                        slave: i as u16 + 1,
+                        expected: (expected.vendor_id, expected.product_code),
+                        found: (slaves[i].eep_manufacturer(), slaves[i].eep_id()),
+                    });
+                }
+                None => res.push(ConfigMismatch::Missing(i as u16 + 1)),
+            }
+        }
+
+        res.extend(
+            pending_found
+                .into_iter()
+                .map(|j| ConfigMismatch::Extra(j as u16 + 1)),
+        );
+        res
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Compare the slaves found by [`Context::config_init`] with the expected network
+    pub fn verify_config(&self, expected: &ExpectedNetwork) -> Vec<ConfigMismatch> {
+        expected.verify(self.slaves())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slave(product_code: u32, revision: u32, parent: u16) -> Slave {
+        let mut x = Slave::default();
+        x.0.eep_man = 2;
+        x.0.eep_id = product_code;
+        x.0.eep_rev = revision;
+        x.0.parent = parent;
+        x
+    }
+
+    fn network(slaves: &[ExpectedSlave]) -> ExpectedNetwork {
+        let mut x = ExpectedNetwork::new();
+        slaves.iter().for_each(|s| {
+            x.push(s.clone());
+        });
+        x
+    }
+
+    #[test]
+    fn matching_network() {
+        let mut coupler = ExpectedSlave::new(2, 1100);
+        coupler.set_revision(0x0012).set_parent(0);
+        let mut terminal = ExpectedSlave::new(2, 1008);
+        terminal.set_parent(1);
+        let expected = network(&[coupler, terminal]);
+
+        let found = [slave(1100, 0x0012, 0), slave(1008, 7, 1)];
+        assert_eq!(expected.verify(&found), vec![]);
+    }
+
+    #[test]
+    fn wrong_revision_and_parent() {
+        let mut coupler = ExpectedSlave::new(2, 1100);
+        coupler.set_revision(0x0012);
+        let mut terminal = ExpectedSlave::new(2, 1008);
+        terminal.set_parent(1);
+        let expected = network(&[coupler, terminal]);
+
+        let found = [slave(1100, 0x0011, 0), slave(1008, 0, 0)];
+        assert_eq!(
+            expected.verify(&found),
+            vec![
+                ConfigMismatch::WrongRevision {
+                    slave: 1,
+                    expected: 0x0012,
+                    found: 0x0011
+                },
+                ConfigMismatch::WrongParent {
+                    slave: 2,
+                    expected: 1,
+                    found: 0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn swapped_slaves() {
+        let mut inputs = ExpectedSlave::new(2, 1008);
+        inputs.set_revision(3);
+        let expected = network(&[
+            ExpectedSlave::new(2, 1100),
+            inputs,
+            ExpectedSlave::new(2, 2008),
+        ]);
+
+        let found = [slave(1100, 0, 0), slave(2008, 0, 1), slave(1008, 3, 2)];
+        assert_eq!(
+            expected.verify(&found),
+            vec![
+                ConfigMismatch::WrongPosition {
+                    expected: 2,
+                    found: 3
+                },
+                ConfigMismatch::WrongPosition {
+                    expected: 3,
+                    found: 2
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn moved_slave_is_still_compared() {
+        let mut inputs = ExpectedSlave::new(2, 1008);
+        inputs.set_revision(3);
+        let expected = network(&[ExpectedSlave::new(2, 1100), inputs]);
+
+        let found = [slave(1100, 0, 0), slave(2008, 0, 1), slave(1008, 2, 2)];
+        assert_eq!(
+            expected.verify(&found),
+            vec![
+                ConfigMismatch::WrongPosition {
+                    expected: 2,
+                    found: 3
+                },
+                ConfigMismatch::WrongRevision {
+                    slave: 3,
+                    expected: 3,
+                    found: 2
+                },
+                ConfigMismatch::Extra(2),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_extra_and_wrong_products() {
+        let expected = network(&[
+            ExpectedSlave::new(2, 1100),
+            ExpectedSlave::new(2, 1008),
+            ExpectedSlave::new(2, 3102),
+        ]);
+
+        let found = [slave(1100, 0, 0), slave(2008, 0, 1)];
+        assert_eq!(
+            expected.verify(&found),
+            vec![
+                ConfigMismatch::WrongProduct {
+                    slave: 2,
+                    expected: (2, 1008),
+                    found: (2, 2008)
+                },
+                ConfigMismatch::Missing(3),
+            ]
+        );
+
+        let found = [
+            slave(1100, 0, 0),
+            slave(1008, 0, 1),
+            slave(3102, 0, 2),
+            slave(1, 0, 3),
+        ];
+        assert_eq!(expected.verify(&found), vec![ConfigMismatch::Extra(4)]);
+        assert_eq!(
+            ExpectedNetwork::new().verify(&found[..1]),
+            vec![ConfigMismatch::Extra(1)]
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{
+    error::FirmwareError, Context, EtherCatState, EC_TIMEOUTEEP, EC_TIMEOUTRET, EC_TIMEOUTSTATE,
+};
+use boolinator::Boolinator;
+use std::{ffi::c_void, fs, mem, os::raw::c_int, path::Path, result};
+use SOEM_sys::{ec_smt, ecx_FPWR, ecx_readeeprom};
+
+/** SII word address of the revision number */
+const SII_REV: u16 = 0x000c;
+/** SII word address of the bootstrap receive mailbox */
+const SII_BOOTRXMBX: u16 = 0x0014;
+/** SII word address of the bootstrap send mailbox */
+const SII_BOOTTXMBX: u16 = 0x0016;
+/** ESC register of sync manager 0 */
+const REG_SM0: u16 = 0x0800;
+/** ESC register of sync manager 1 */
+const REG_SM1: u16 = 0x0808;
+/** sync manager flags of the mailbox written by the master */
+const EC_DEFAULTMBXSM0: u32 = 0x00010026;
+/** sync manager flags of the mailbox read by the master */
+const EC_DEFAULTMBXSM1: u32 = 0x00010022;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct FirmwareUpdate {
+    size: usize,
+    previous_revision: u32,
+    revision: u32,
+}
+
+impl FirmwareUpdate {
+    /// Size of the transferred image
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+    /// Revision reported by the SII before the update
+    pub const fn previous_revision(&self) -> u32 {
+        self.previous_revision
+    }
+    /// Revision reported by the SII after the update
+    pub const fn revision(&self) -> u32 {
+        self.revision
+    }
+}
+
+impl<'a> Context<'a> {
+    fn request_slave_state(
+        &mut self,
+        slave: u16,
+        state: EtherCatState,
+        timeout: c_int,
+    ) -> result::Result<(), FirmwareError> {
+        self.set_state(state, slave);
+        self.write_state(slave)
+            .map_err(FirmwareError::EtherCatError)?;
+        match self.check_state(slave, state, timeout) {
+            x if x == state => Ok(()),
+            x => Err(FirmwareError::StateError(state, x)),
+        }
+    }
+
+    fn write_sm(
+        &mut self,
+        slave: u16,
+        reg: u16,
+        mut sm: ec_smt,
+    ) -> result::Result<(), FirmwareError> {
+        let configadr = self.slave_mut(slave).configured_addr();
+        let wkc = unsafe {
+            ecx_FPWR(
+                self.context.port,
+                configadr,
+                reg,
+                mem::size_of::<ec_smt>() as u16,
+                &mut sm as *mut ec_smt as *mut c_void,
+                EC_TIMEOUTRET,
+            )
+        };
+        (wkc > 0).as_result((), FirmwareError::NoResponse)
+    }
+
+    fn read_sii(&mut self, slave: u16, word: u16) -> u32 {
+        unsafe { ecx_readeeprom(&mut self.context, slave, word, EC_TIMEOUTEEP) }
+    }
+
+    fn boot_transfer(
+        &mut self,
+        slave: u16,
+        filename: &str,
+        password: u32,
+        data: &[u8],
+    ) -> result::Result<(), FirmwareError> {
+        let rx = self.read_sii(slave, SII_BOOTRXMBX);
+        let tx = self.read_sii(slave, SII_BOOTTXMBX);
+
+        let raw = &mut self.slave_mut(slave).0;
+        raw.SM[0].StartAddr = rx as u16;
+        raw.SM[0].SMlength = (rx >> 16) as u16;
+        raw.SM[0].SMflags = EC_DEFAULTMBXSM0;
+        raw.mbx_wo = rx as u16;
+        raw.mbx_l = (rx >> 16) as u16;
+        raw.SM[1].StartAddr = tx as u16;
+        raw.SM[1].SMlength = (tx >> 16) as u16;
+        raw.SM[1].SMflags = EC_DEFAULTMBXSM1;
+        raw.mbx_ro = tx as u16;
+        raw.mbx_rl = (tx >> 16) as u16;
+        let (sm0, sm1) = (raw.SM[0], raw.SM[1]);
+
+        self.write_sm(slave, REG_SM0, sm0)?;
+        self.write_sm(slave, REG_SM1, sm1)?;
+
+        self.request_slave_state(slave, EtherCatState::Boot, EC_TIMEOUTSTATE * 10)?;
+        self.foe_write(slave, filename, password, data, EC_TIMEOUTSTATE)
+            .map_err(FirmwareError::FoEError)
+    }
+
+    /// Flash a firmware image to the slave.
+    ///
+    /// The slave is brought to Init, its mailbox is switched to the bootstrap one and the image
+    /// is transferred with FoE in Boot state under its file name. Afterwards the slave is
+    /// returned to Init with the regular mailbox, also when the transfer fails, and the revision
+    /// is read back from the SII and checked against `expected_revision` if given.
+    /// Run [`Context::config_init`] again to bring the slave back into operation.
+    pub fn firmware_update<P: AsRef<Path>>(
+        &mut self,
+        slave: u16,
+        file: P,
+        password: u32,
+        expected_revision: Option<u32>,
+    ) -> result::Result<FirmwareUpdate, FirmwareError> {
+        if !self.has_slave(slave) {
+            return Err(FirmwareError::UnknownSlave(slave));
+        }
+
+        let file = file.as_ref();
+        let data = fs::read(file).map_err(FirmwareError::IOError)?;
+        let filename = file
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        self.request_slave_state(slave, EtherCatState::Init, EC_TIMEOUTSTATE * 4)?;
+
+        let previous_revision = self.read_sii(slave, SII_REV);
+        let mailbox = {
+            let raw = &self.slave_mut(slave).0;
+            (
+                raw.SM[0], raw.SM[1], raw.mbx_wo, raw.mbx_l, raw.mbx_ro, raw.mbx_rl,
+            )
+        };
+
+        let transfer = self.boot_transfer(slave, &filename, password, &data);
+        let init = self.request_slave_state(slave, EtherCatState::Init, EC_TIMEOUTSTATE * 4);
+
+        let raw = &mut self.slave_mut(slave).0;
+        raw.SM[0] = mailbox.0;
+        raw.SM[1] = mailbox.1;
+        raw.mbx_wo = mailbox.2;
+        raw.mbx_l = mailbox.3;
+        raw.mbx_ro = mailbox.4;
+        raw.mbx_rl = mailbox.5;
+
+        let restore = self
+            .write_sm(slave, REG_SM0, mailbox.0)
+            .and_then(|_| self.write_sm(slave, REG_SM1, mailbox.1));
+
+        transfer?;
+        init?;
+        restore?;
+
+        let revision = self.read_sii(slave, SII_REV);
+        self.slave_mut(slave).0.eep_rev = revision;
+        if let Some(expected) = expected_revision.filter(|&x| x != revision) {
+            return Err(FirmwareError::RevisionMismatch(expected, revision));
+        }
+
+        Ok(FirmwareUpdate {
+            size: data.len(),
+            previous_revision,
+            revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+
+    #[test]
+    fn unknown_slaves_are_rejected() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        let mut c = buffers.context();
+
+        for &slave in [0, 3, 0xffff].iter() {
+            match c.firmware_update(slave, "missing.efw", 0, None) {
+                Err(FirmwareError::UnknownSlave(x)) => assert_eq!(x, slave),
+                x => panic!("slave {} updated: {:?}", slave, x),
+            }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use crate::{error::FoEError, Context};
+use std::{
+    any::Any,
+    cell::Cell,
+    ffi::{c_void, CString},
+    fmt,
+    io::{Read, Write},
+    os::raw::c_int,
+    panic,
+    panic::AssertUnwindSafe,
+    ptr, result,
+};
+use SOEM_sys::{ecx_FOEread, ecx_FOEwrite};
+
+type Progress<'a> = dyn FnMut(u16, u32, usize) + Send + 'a;
+
+pub(crate) struct FoEHook<'a>(Box<Progress<'a>>);
+
+impl<'a> fmt::Debug for FoEHook<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FoEHook")
+    }
+}
+
+thread_local! {
+    // SOEM passes no user data to the hook, so the closure of the running transfer is
+    // published here for the duration of the call.
+    static PROGRESS: Cell<*mut c_void> = const { Cell::new(ptr::null_mut()) };
+    // Panic of the closure, unwinding through SOEM is undefined behaviour
+    static PANIC: Cell<Option<Box<dyn Any + Send>>> = const { Cell::new(None) };
+}
+
+unsafe extern "C" fn foe_progress(slave: u16, packetnumber: c_int, datasize: c_int) -> c_int {
+    let progress = PROGRESS.with(Cell::get) as *mut &mut Progress;
+    if let Some(progress) = progress.as_mut() {
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            progress(slave, packetnumber as u32, datasize as usize)
+        }));
+        if let Err(payload) = res {
+            PROGRESS.with(|x| x.set(ptr::null_mut()));
+            PANIC.with(|x| x.set(Some(payload)));
+            return -1;
+        }
+    }
+    0
+}
+
+impl<'a> Context<'a> {
+    /// Report FoE transfer progress to `hook`.
+    ///
+    /// The hook receives the slave, the packet number and the number of bytes received so far
+    /// when reading, or the number of bytes left when writing. SOEM gives the hook no way to
+    /// cancel the transfer, so when it panics it is not called again and the panic is resumed
+    /// once the transfer is over.
+    pub fn on_foe_progress<F: FnMut(u16, u32, usize) + Send + 'a>(&mut self, hook: F) {
+        self.foe_hook = Some(FoEHook(Box::new(hook)));
+        self.context.FOEhook = Some(foe_progress);
+    }
+
+    pub fn clear_foe_progress(&mut self) {
+        self.foe_hook = None;
+        self.context.FOEhook = None;
+    }
+
+    fn with_foe_progress<R, F: FnOnce(&mut Self) -> R>(&mut self, f: F) -> R {
+        let mut hook = self.foe_hook.take();
+        let mut progress = hook.as_mut().map(|x| &mut *x.0);
+        let ptr = match progress {
+            Some(ref mut progress) => progress as *mut &mut Progress as *mut c_void,
+            None => ptr::null_mut(),
+        };
+
+        let prev = PROGRESS.with(|x| x.replace(ptr));
+        let res = f(self);
+        PROGRESS.with(|x| x.set(prev));
+
+        self.foe_hook = hook;
+        if let Some(payload) = PANIC.with(Cell::take) {
+            panic::resume_unwind(payload);
+        }
+        self.resume_eoe_panic();
+        res
+    }
+
+    /// Read a file from the slave into `buf`, returns the file size
+    pub fn foe_read(
+        &mut self,
+        slave: u16,
+        filename: &str,
+        password: u32,
+        buf: &mut [u8],
+        timeout: c_int,
+    ) -> result::Result<usize, FoEError> {
+        let filename = CString::new(filename).map_err(FoEError::CStringError)?;
+        let mut psize = buf.len() as c_int;
+
+        let ret = self.with_foe_progress(|c| unsafe {
+            ecx_FOEread(
+                &mut c.context,
+                slave,
+                filename.as_ptr() as *mut _,
+                password,
+                &mut psize,
+                buf.as_mut_ptr() as *mut c_void,
+                timeout,
+            )
+        });
+
+        match FoEError::from_code(ret) {
+            Ok(err) => Err(err),
+            Err(_) => Ok(psize as usize),
+        }
+    }
+
+    pub fn foe_write(
+        &mut self,
+        slave: u16,
+        filename: &str,
+        password: u32,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<(), FoEError> {
+        let filename = CString::new(filename).map_err(FoEError::CStringError)?;
+
+        let ret = self.with_foe_progress(|c| unsafe {
+            ecx_FOEwrite(
+                &mut c.context,
+                slave,
+                filename.as_ptr() as *mut _,
+                password,
+                data.len() as c_int,
+                data.as_ptr() as *mut c_void,
+                timeout,
+            )
+        });
+
+        match FoEError::from_code(ret) {
+            Ok(err) => Err(err),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Read a file of at most `max_size` bytes from the slave into `writer`
+    pub fn foe_read_to<W: Write>(
+        &mut self,
+        slave: u16,
+        filename: &str,
+        password: u32,
+        max_size: usize,
+        writer: &mut W,
+        timeout: c_int,
+    ) -> result::Result<usize, FoEError> {
+        let mut buf = vec![0u8; max_size];
+        let size = self.foe_read(slave, filename, password, &mut buf, timeout)?;
+        writer.write_all(&buf[..size]).map_err(FoEError::IOError)?;
+        Ok(size)
+    }
+
+    /// Write everything `reader` yields to a file on the slave
+    pub fn foe_write_from<R: Read>(
+        &mut self,
+        slave: u16,
+        filename: &str,
+        password: u32,
+        reader: &mut R,
+        timeout: c_int,
+    ) -> result::Result<usize, FoEError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(FoEError::IOError)?;
+        self.foe_write(slave, filename, password, &data, timeout)?;
+        Ok(data.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+    use std::sync::mpsc;
+
+    #[test]
+    fn panicking_hook_does_not_unwind_into_soem() {
+        let mut buffers = MockBuffers::with_slaves(1, 1);
+        let mut c = buffers.context();
+        let (packets, received) = mpsc::channel();
+        c.on_foe_progress(move |_, packet, _| {
+            packets.send(packet).unwrap();
+            assert!(packet < 2, "hook failed");
+        });
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            c.with_foe_progress(|_| unsafe {
+                let rets = [
+                    foe_progress(1, 1, 100),
+                    foe_progress(1, 2, 200),
+                    foe_progress(1, 3, 300),
+                ];
+                assert_eq!(rets, [0, -1, 0]);
+            })
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(received.try_iter().collect::<Vec<_>>(), [1, 2]);
+        assert!(c.foe_hook.is_some());
+        assert!(PROGRESS.with(Cell::get).is_null());
+        assert!(c.with_foe_progress(|_| true));
+    }
+}
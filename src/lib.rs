@@ -1,12 +1,89 @@
+mod aoe;
+#[cfg(feature = "tokio")]
+mod async_context;
+#[cfg(feature = "serde")]
+mod config;
+mod config_table;
+mod datagram;
+mod diagnostics;
+mod eeprom;
+mod emergency;
+#[cfg(feature = "xml")]
+mod eni;
+mod eoe;
 mod error;
+mod esc;
+#[cfg(feature = "xml")]
+mod esi;
+mod expected;
+mod firmware;
+mod foe;
+mod mailbox;
+mod sii;
+mod soe;
+mod topology;
+mod voe;
+#[cfg(feature = "xml")]
+mod xml;
 
 #[macro_use]
 extern crate num_derive;
 
+pub use crate::aoe::{AdsDeviceState, AdsState, AmsAddr, AmsNetId, AoE};
+#[cfg(feature = "tokio")]
+pub use crate::async_context::{AsyncContext, InputSnapshot, InputStream};
+#[cfg(feature = "serde")]
+pub use crate::config::{
+    ConfigDc, ConfigPdo, ConfigPdoEntry, ConfigSdo, ConfigSlave, ConfigSyncManager, MasterConfig,
+    SdoValue,
+};
+pub use crate::config_table::{SlaveConfig, SmConfig};
+pub use crate::diagnostics::{ErrorMonitor, PortCounters, PortDelta};
+use crate::emergency::EmergencyHandler;
+pub use crate::emergency::{Emergency, EmergencyClass};
+#[cfg(feature = "xml")]
+pub use crate::eni::{
+    Eni, EniCommand, EniDc, EniInitCmd, EniPdo, EniPdoEntry, EniSlave, EniSyncManager, EniValidate,
+    EniVariable,
+};
+use crate::eoe::EoEHook;
+pub use crate::eoe::{EoE, EoEParam};
+#[cfg(feature = "tokio")]
+pub use crate::error::AsyncError;
+#[cfg(feature = "serde")]
+pub use crate::error::ConfigError;
+pub use crate::error::{
+    AoEError, DatagramError, EepromError, EoEError, FirmwareError, FoEError, MailboxError,
+    ParseIdnError, ParseNetIdError, ParseTransitionError, SiiError, SoEError, VoEError,
+};
+#[cfg(feature = "xml")]
+pub use crate::error::{EniError, XmlError};
 use crate::error::{ErrorGenerator, ErrorIterator, EtherCatError, InitError};
+pub use crate::esc::{
+    AlControl, AlStatus, DcTimes, DlControl, DlStatus, ErrorCounters, EscInfo, EscRegister, Fmmu,
+    SiiInterface, StationAddress, SyncManager, WatchdogDivider, WatchdogStatus, WatchdogTime,
+    WritableRegister,
+};
+#[cfg(feature = "xml")]
+pub use crate::esi::{
+    Esi, EsiDevice, EsiEeprom, EsiMailbox, EsiObject, EsiOpMode, EsiPdo, EsiPdoEntry, EsiSubItem,
+    EsiSyncManager,
+};
+pub use crate::expected::{ConfigMismatch, ExpectedNetwork, ExpectedSlave};
+pub use crate::firmware::FirmwareUpdate;
+use crate::foe::FoEHook;
+pub use crate::mailbox::{Mailbox, MailboxHeader, MailboxType};
+pub use crate::sii::{
+    FmmuUsage, Sii, SiiDc, SiiGeneral, SiiMailbox, SiiPdo, SiiPdoEntry, SiiSyncManager,
+    SyncManagerType,
+};
+pub use crate::soe::{ElementFlags, Idn};
+pub use crate::topology::{Topology, TopologyNode};
+pub use crate::voe::VoEMessage;
 use boolinator::Boolinator;
 use std::{
     borrow::Cow,
+    convert::TryFrom,
     default::Default,
     ffi::{CStr, CString},
     fmt,
@@ -14,23 +91,33 @@ use std::{
     mem,
     mem::zeroed,
     ops::Not,
-    os::raw::c_int,
+    os::raw::{c_char, c_int},
     result, slice,
+    str::FromStr,
 };
 use SOEM_sys::{
     boolean, ec_PDOassignt, ec_PDOdesct, ec_SMcommtypet, ec_eepromFMMUt, ec_eepromSMt, ec_eringt,
     ec_group, ec_idxstackT, ec_slave, ec_state_EC_STATE_BOOT, ec_state_EC_STATE_ERROR,
     ec_state_EC_STATE_INIT, ec_state_EC_STATE_NONE, ec_state_EC_STATE_OPERATIONAL,
     ec_state_EC_STATE_PRE_OP, ec_state_EC_STATE_SAFE_OP, ecx_SDOread, ecx_SDOwrite, ecx_close,
-    ecx_config_init, ecx_config_map_group, ecx_configdc, ecx_context, ecx_elist2string, ecx_init,
-    ecx_iserror, ecx_portt, ecx_readstate, ecx_receive_processdata, ecx_send_processdata,
-    ecx_statecheck, ecx_writestate,
+    ecx_config_init, ecx_config_map_group, ecx_configdc, ecx_context, ecx_dcsync0, ecx_dcsync01,
+    ecx_elist2string, ecx_init, ecx_init_redundant, ecx_iserror, ecx_portt, ecx_readstate,
+    ecx_receive_processdata_group, ecx_redportt, ecx_send_processdata_group, ecx_statecheck,
+    ecx_writestate,
 };
 
 /** size of EEPROM bitmap cache */
 const EC_MAXEEPBITMAP: usize = 128;
 /** size of EEPROM cache buffer */
 const EC_MAXEEPBUF: usize = EC_MAXEEPBITMAP << 5;
+/** timeout value in us for tx frame to return to rx */
+pub const EC_TIMEOUTRET: c_int = 2000;
+/** timeout value in us for EEPROM access */
+pub const EC_TIMEOUTEEP: c_int = 20000;
+/** timeout value in us for check statechange */
+pub const EC_TIMEOUTSTATE: c_int = 2000000;
+/** timeout value in us for return "mailbox read" */
+pub const EC_TIMEOUTRXM: c_int = 700000;
 
 pub type Boolean = boolean;
 
@@ -67,6 +154,73 @@ impl fmt::Display for EtherCatState {
     }
 }
 
+/// State transition, written in the ETG short notation such as `PS` for PreOp to SafeOp
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transition {
+    from: EtherCatState,
+    to: EtherCatState,
+}
+
+impl Transition {
+    pub const fn new(from: EtherCatState, to: EtherCatState) -> Self {
+        Transition { from, to }
+    }
+    pub const fn from(&self) -> EtherCatState {
+        self.from
+    }
+    pub const fn to(&self) -> EtherCatState {
+        self.to
+    }
+
+    fn state_letter(state: EtherCatState) -> char {
+        match state {
+            EtherCatState::Init => 'I',
+            EtherCatState::PreOp => 'P',
+            EtherCatState::SafeOp => 'S',
+            EtherCatState::Op => 'O',
+            EtherCatState::Boot => 'B',
+            _ => '?',
+        }
+    }
+
+    fn letter_state(x: char) -> Option<EtherCatState> {
+        match x.to_ascii_uppercase() {
+            'I' => Some(EtherCatState::Init),
+            'P' => Some(EtherCatState::PreOp),
+            'S' => Some(EtherCatState::SafeOp),
+            'O' => Some(EtherCatState::Op),
+            'B' => Some(EtherCatState::Boot),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Transition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            Transition::state_letter(self.from),
+            Transition::state_letter(self.to)
+        )
+    }
+}
+
+impl FromStr for Transition {
+    type Err = ParseTransitionError;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let mut chars = s.trim().chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(from), Some(to), None) => Transition::letter_state(from)
+                .zip(Transition::letter_state(to))
+                .map(|(from, to)| Transition { from, to })
+                .ok_or(ParseTransitionError),
+            _ => Err(ParseTransitionError),
+        }
+    }
+}
+
 #[repr(C)]
 pub struct Port(ecx_portt);
 
@@ -76,6 +230,15 @@ impl Default for Port {
     }
 }
 
+/// Buffers of the second interface used for cable redundancy, see [`Context::init_redundant`]
+pub struct RedPort(ecx_redportt);
+
+impl Default for RedPort {
+    fn default() -> RedPort {
+        RedPort(unsafe { zeroed() })
+    }
+}
+
 #[repr(C)]
 pub struct Slave(ec_slave);
 
@@ -96,21 +259,31 @@ impl Slave {
     pub const fn input_size(&self) -> u16 {
         self.0.Ibits
     }
-    pub fn outputs(&mut self) -> &mut [u8] {
-        let size = (if self.0.Obytes == 0 && self.0.Obits > 0 {
+    fn outputs_len(&self) -> usize {
+        (if self.0.Obytes == 0 && self.0.Obits > 0 {
             1
         } else {
             self.0.Obytes
-        }) as usize;
-        unsafe { slice::from_raw_parts_mut(self.0.outputs, size) }
+        }) as usize
     }
-    pub fn inputs(&self) -> &[u8] {
-        let size = (if self.0.Ibytes == 0 && self.0.Ibits > 0 {
+    fn inputs_len(&self) -> usize {
+        (if self.0.Ibytes == 0 && self.0.Ibits > 0 {
             1
         } else {
             self.0.Ibytes
-        }) as usize;
-        unsafe { slice::from_raw_parts_mut(self.0.inputs, size) }
+        }) as usize
+    }
+    pub fn outputs(&self) -> &[u8] {
+        if self.0.outputs.is_null() {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(self.0.outputs, self.outputs_len()) }
+    }
+    pub fn inputs(&self) -> &[u8] {
+        if self.0.inputs.is_null() {
+            return &[];
+        }
+        unsafe { slice::from_raw_parts(self.0.inputs, self.inputs_len()) }
     }
     pub fn state(&self) -> EtherCatState {
         num::FromPrimitive::from_u16(self.0.state).unwrap()
@@ -133,9 +306,36 @@ impl Slave {
     pub const fn parent_port(&self) -> u8 {
         self.0.parentport
     }
+    /// Index of the slave this one is connected to, 0 for the master
+    pub const fn parent(&self) -> u16 {
+        self.0.parent
+    }
+    /// Number of ports with established communication
+    pub const fn topology(&self) -> u8 {
+        self.0.topology
+    }
+    /// Ports with established communication, a bit per port
+    pub const fn active_ports(&self) -> u8 {
+        self.0.activeports
+    }
+    /// Port the frames enter the slave, known after [`Context::config_dc`]
+    pub const fn entry_port(&self) -> u8 {
+        self.0.entryport
+    }
     pub const fn configured_addr(&self) -> u16 {
         self.0.configadr
     }
+    /// Configured station alias as read from the SII, 0 if none
+    pub const fn alias(&self) -> u16 {
+        self.0.aliasadr
+    }
+    pub const fn group(&self) -> u8 {
+        self.0.group
+    }
+    /// Size of the mailbox written by the master
+    pub const fn mailbox_len(&self) -> u16 {
+        self.0.mbx_l
+    }
 }
 
 impl fmt::Display for Slave {
@@ -151,6 +351,7 @@ impl fmt::Display for Slave {
             writeln!(f, " DCParentport: {}", self.parent_port())?;
         }
         writeln!(f, " Configured address: {:04x}", self.configured_addr())?;
+        writeln!(f, " Alias: {:04x}", self.alias())?;
         writeln!(
             f,
             " Man: {:08x} ID: {:08x} Rev: {:08x}",
@@ -161,6 +362,38 @@ impl fmt::Display for Slave {
     }
 }
 
+/// Process data of the slaves, see [`Context::process_image`].
+///
+/// Slaves are numbered the same way as in the rest of the API, slave 0 stands for the whole
+/// image of the first group and is read only. Neighbour slaves with less than 8 bits of process
+/// data share bytes, so only one output slice may be borrowed mutably at a time.
+pub struct ProcessImage<'c> {
+    slavelist: &'c [Slave],
+    _phantom: PhantomData<&'c mut ()>,
+}
+
+impl<'c> ProcessImage<'c> {
+    pub fn slaves(&self) -> &[Slave] {
+        &self.slavelist[1..]
+    }
+    /// `None` for unknown slaves
+    pub fn inputs(&self, slave: u16) -> Option<&[u8]> {
+        self.slavelist.get(slave as usize).map(Slave::inputs)
+    }
+    /// `None` for unknown slaves
+    pub fn outputs(&self, slave: u16) -> Option<&[u8]> {
+        self.slavelist.get(slave as usize).map(Slave::outputs)
+    }
+    /// `None` for unknown slaves and for slave 0, which overlaps every other slave
+    pub fn outputs_mut(&mut self, slave: u16) -> Option<&mut [u8]> {
+        let s = self.slavelist.get(slave as usize).filter(|_| slave != 0)?;
+        if s.0.outputs.is_null() {
+            return Some(&mut []);
+        }
+        Some(unsafe { slice::from_raw_parts_mut(s.0.outputs, s.outputs_len()) })
+    }
+}
+
 #[repr(C)]
 pub struct Group(ec_group);
 
@@ -182,6 +415,114 @@ impl Default for Group {
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ProcessDataStatus {
+    /// Working counter reached the expected value
+    Ok,
+    /// Frame returned, but not every slave processed it
+    Partial,
+    /// No frame received, SOEM reports a frame not returned within the timeout the same way
+    NoFrame,
+}
+
+impl fmt::Display for ProcessDataStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProcessDataStatus::Ok => write!(f, "Ok"),
+            ProcessDataStatus::Partial => write!(f, "Partial"),
+            ProcessDataStatus::NoFrame => write!(f, "No frame"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ProcessDataResult {
+    wkc: u16,
+    expected_wkc: u16,
+    status: ProcessDataStatus,
+}
+
+impl ProcessDataResult {
+    fn new(ret: c_int, expected_wkc: u16) -> Self {
+        // Every negative return is an error code, known to EtherCatError or not
+        let (wkc, status) = match u16::try_from(ret) {
+            Err(_) => (0, ProcessDataStatus::NoFrame),
+            Ok(wkc) if wkc >= expected_wkc => (wkc, ProcessDataStatus::Ok),
+            Ok(wkc) => (wkc, ProcessDataStatus::Partial),
+        };
+
+        ProcessDataResult {
+            wkc,
+            expected_wkc,
+            status,
+        }
+    }
+
+    pub const fn wkc(&self) -> u16 {
+        self.wkc
+    }
+    pub const fn expected_wkc(&self) -> u16 {
+        self.expected_wkc
+    }
+    pub const fn status(&self) -> ProcessDataStatus {
+        self.status
+    }
+    pub fn is_ok(&self) -> bool {
+        self.status == ProcessDataStatus::Ok
+    }
+}
+
+impl fmt::Display for ProcessDataResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (WKC {}/{})",
+            self.status, self.wkc, self.expected_wkc
+        )
+    }
+}
+
+/// Rolling process data counters of a single group
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct GroupStats {
+    cycles: u64,
+    wkc_mismatches: u64,
+    lost_frames: u64,
+    consecutive_failures: u32,
+}
+
+impl GroupStats {
+    fn update(&mut self, result: &ProcessDataResult) {
+        self.cycles += 1;
+        match result.status() {
+            ProcessDataStatus::Ok => {
+                self.consecutive_failures = 0;
+                return;
+            }
+            ProcessDataStatus::Partial => self.wkc_mismatches += 1,
+            ProcessDataStatus::NoFrame => self.lost_frames += 1,
+        }
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+    }
+
+    /// Total number of received cycles
+    pub const fn cycles(&self) -> u64 {
+        self.cycles
+    }
+    /// Number of cycles with working counter below the expected value
+    pub const fn wkc_mismatches(&self) -> u64 {
+        self.wkc_mismatches
+    }
+    /// Number of cycles without returned frame
+    pub const fn lost_frames(&self) -> u64 {
+        self.lost_frames
+    }
+    /// Number of failed cycles since the last successful one
+    pub const fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+}
+
 #[repr(C)]
 pub struct ESIBuf([u8; EC_MAXEEPBUF]);
 
@@ -263,14 +604,32 @@ impl Default for EEPROMSM {
     }
 }
 
+/// EtherCat master context.
+///
+/// SOEM keeps raw pointers to every buffer passed to [`Context::new`] and to the process data
+/// image passed to [`Context::config_map_group`]. All of them are borrowed exclusively for `'a`,
+/// so the context is the only way to reach them until it is dropped and the port is closed.
 #[derive(Debug)]
 pub struct Context<'a> {
     context: ecx_context,
+    group_stats: Vec<GroupStats>,
+    emergency_handler: Option<EmergencyHandler<'a>>,
+    foe_hook: Option<FoEHook<'a>>,
+    eoe_hook: Option<Box<EoEHook<'a>>>,
+    aoe_invoke_id: u32,
+    slave_configs: Vec<SlaveConfig>,
     _phantom: PhantomData<&'a ()>,
 }
 
+// SAFETY: The pointers inside `ecx_context` originate from `&'a mut` borrows, so moving the
+// context to another thread moves the only path to the buffers along with it. SOEM keeps no
+// thread-local state: the socket and the port mutexes may be used from any thread. The context
+// is not `Sync` since every SOEM call requires exclusive access.
+unsafe impl<'a> Send for Context<'a> {}
+
 impl<'a> Drop for Context<'a> {
     fn drop(&mut self) {
+        self.clear_eoe_handler();
         unsafe { ecx_close(&mut self.context) };
     }
 }
@@ -297,10 +656,10 @@ impl<'a> Context<'a> {
         let mut c = Context {
             context: ecx_context {
                 port: &mut port.0,
-                slavelist: &mut slaves[0].0,
+                slavelist: slaves.as_mut_ptr() as *mut ec_slave,
                 slavecount: &mut *slavecount,
                 maxslave: slaves.len() as c_int,
-                grouplist: &mut groups[0].0,
+                grouplist: groups.as_mut_ptr() as *mut ec_group,
                 maxgroup: groups.len() as c_int,
                 esibuf: esibuf.0.as_mut_ptr(),
                 esimap: esimap.0.as_mut_ptr(),
@@ -320,6 +679,12 @@ impl<'a> Context<'a> {
                 EOEhook: Default::default(),
                 manualstatechange: Default::default(),
             },
+            group_stats: vec![Default::default(); groups.len()],
+            emergency_handler: None,
+            foe_hook: None,
+            eoe_hook: None,
+            aoe_invoke_id: 0,
+            slave_configs: Vec::new(),
             _phantom: Default::default(),
         };
 
@@ -333,9 +698,50 @@ impl<'a> Context<'a> {
             )
     }
 
+    /// Reopen the network with a second interface at the other end of the ring.
+    ///
+    /// Frames are sent both ways, so the slaves stay reachable when the cable breaks once.
+    pub fn init_redundant(
+        &mut self,
+        red_port: &'a mut RedPort,
+        iface_name: &str,
+        redundant_iface_name: &str,
+    ) -> result::Result<(), InitError> {
+        let iface = CString::new(iface_name).map_err(InitError::CStringError)?;
+        let redundant_iface =
+            CString::new(redundant_iface_name).map_err(InitError::CStringError)?;
+
+        unsafe { ecx_close(&mut self.context) };
+        match unsafe {
+            ecx_init_redundant(
+                &mut self.context,
+                &mut red_port.0,
+                iface.as_ptr(),
+                redundant_iface.as_ptr() as *mut c_char,
+            )
+        } {
+            x if x > 0 => Ok(()),
+            _ => Err(InitError::IOError(std::io::Error::last_os_error())),
+        }
+    }
+
+    /// Close the port and open it again on the interface
+    pub fn reinit(&mut self, iface_name: &str) -> result::Result<(), InitError> {
+        let iface = CString::new(iface_name).map_err(InitError::CStringError)?;
+
+        unsafe { ecx_close(&mut self.context) };
+        match unsafe { ecx_init(&mut self.context, iface.as_ptr()) } {
+            x if x > 0 => Ok(()),
+            _ => Err(InitError::IOError(std::io::Error::last_os_error())),
+        }
+    }
+
     pub fn config_init(&mut self, usetable: bool) -> result::Result<usize, EtherCatError> {
         match unsafe { ecx_config_init(&mut self.context, usetable as u8) } {
-            x if x > 0 => Ok(x as usize),
+            x if x > 0 => {
+                self.apply_slave_configs()?;
+                Ok(x as usize)
+            }
             x => Err(EtherCatError::from_code(x).unwrap()),
         }
     }
@@ -345,13 +751,13 @@ impl<'a> Context<'a> {
         io_map: &'a mut [u8; 4096],
         group: u8,
     ) -> result::Result<usize, ErrorIterator<'b>> {
-        let iomap_size = unsafe {
+        let iomap_size = self.with_eoe_hook(|c| unsafe {
             ecx_config_map_group(
-                &mut self.context,
+                &mut c.context,
                 io_map.as_mut_ptr() as *mut std::ffi::c_void,
                 group as u8,
             ) as usize
-        };
+        });
         self.iserror()
             .not()
             .as_result(iomap_size, ErrorIterator::new(self))
@@ -388,26 +794,63 @@ impl<'a> Context<'a> {
     }
 
     pub fn set_state(&mut self, state: EtherCatState, slave: u16) {
+        self.slave_mut(slave).0.state = state as u16;
+    }
+
+    pub(crate) fn slave_mut(&mut self, slave: u16) -> &mut Slave {
         let raw_slaves = unsafe {
-            slice::from_raw_parts_mut(self.context.slavelist, *self.context.slavecount as usize)
+            slice::from_raw_parts_mut(
+                self.context.slavelist as *mut Slave,
+                *self.context.slavecount as usize + 1,
+            )
         };
-        raw_slaves[slave as usize].state = state as u16;
+        &mut raw_slaves[slave as usize]
+    }
+
+    /// True if `slave` is one of the slaves found, the whole network 0 does not count
+    pub(crate) fn has_slave(&self, slave: u16) -> bool {
+        slave != 0 && slave as usize <= self.slaves().len()
     }
 
     pub fn dc_time(&mut self) -> i64 {
         unsafe { *self.context.DCtime }
     }
 
-    pub fn slaves(&mut self) -> &mut [Slave] {
+    pub fn slaves(&self) -> &[Slave] {
+        &self.slavelist()[1..]
+    }
+
+    /// Index of the slave with the configured station alias
+    pub fn slave_by_alias(&self, alias: u16) -> Option<u16> {
+        if alias == 0 {
+            return None;
+        }
+        self.slaves()
+            .iter()
+            .position(|x| x.alias() == alias)
+            .map(|x| x as u16 + 1)
+    }
+
+    fn slavelist(&self) -> &[Slave] {
         unsafe {
-            slice::from_raw_parts_mut(
-                (self.context.slavelist as *mut Slave).offset(1),
-                *self.context.slavecount as usize,
+            slice::from_raw_parts(
+                self.context.slavelist as *const Slave,
+                *self.context.slavecount as usize + 1,
             )
         }
     }
 
-    pub fn groups(&mut self) -> &'a [Group] {
+    /// Access process data between two exchanges.
+    ///
+    /// The image borrows the context, so SOEM cannot touch the process data while it is alive.
+    pub fn process_image(&mut self) -> ProcessImage<'_> {
+        ProcessImage {
+            slavelist: self.slavelist(),
+            _phantom: Default::default(),
+        }
+    }
+
+    pub fn groups(&self) -> &[Group] {
         unsafe {
             slice::from_raw_parts(
                 self.context.grouplist as *const Group,
@@ -417,11 +860,46 @@ impl<'a> Context<'a> {
     }
 
     pub fn send_processdata(&mut self) {
-        unsafe { ecx_send_processdata(&mut self.context) };
+        self.send_processdata_group(0);
     }
 
-    pub fn receive_processdata(&mut self, timeout: c_int) -> u16 {
-        unsafe { ecx_receive_processdata(&mut self.context, timeout) as u16 }
+    /// Groups beyond the ones passed to [`Context::new`] are ignored
+    pub fn send_processdata_group(&mut self, group: u8) {
+        if (group as usize) < self.groups().len() {
+            unsafe { ecx_send_processdata_group(&mut self.context, group) };
+        }
+    }
+
+    pub fn receive_processdata(&mut self, timeout: c_int) -> ProcessDataResult {
+        // Context::new requires at least one group
+        self.receive_group(0, timeout)
+    }
+
+    /// `None` if the group is beyond the ones passed to [`Context::new`]
+    pub fn receive_processdata_group(
+        &mut self,
+        group: u8,
+        timeout: c_int,
+    ) -> Option<ProcessDataResult> {
+        ((group as usize) < self.groups().len()).as_option()?;
+        Some(self.receive_group(group, timeout))
+    }
+
+    fn receive_group(&mut self, group: u8, timeout: c_int) -> ProcessDataResult {
+        let ret = unsafe { ecx_receive_processdata_group(&mut self.context, group, timeout) };
+        let result = ProcessDataResult::new(ret, self.groups()[group as usize].expected_wkc());
+        self.group_stats[group as usize].update(&result);
+        result
+    }
+
+    pub fn group_stats(&self, group: u8) -> Option<&GroupStats> {
+        self.group_stats.get(group as usize)
+    }
+
+    pub fn reset_group_stats(&mut self, group: u8) {
+        if let Some(x) = self.group_stats.get_mut(group as usize) {
+            *x = Default::default();
+        }
     }
 
     pub fn write_sdo<'b, T: num::PrimInt + ?Sized>(
@@ -436,9 +914,9 @@ impl<'a> Context<'a> {
         let psize = mem::size_of_val(&value_le) as c_int;
         let value_ptr = &mut value_le as *mut T;
 
-        unsafe {
+        self.with_eoe_hook(|c| unsafe {
             ecx_SDOwrite(
-                &mut self.context,
+                &mut c.context,
                 slave,
                 index,
                 subindex,
@@ -447,7 +925,7 @@ impl<'a> Context<'a> {
                 value_ptr as *mut std::ffi::c_void,
                 timeout,
             )
-        };
+        });
 
         self.iserror().not().as_result((), ErrorIterator::new(self))
     }
@@ -464,9 +942,9 @@ impl<'a> Context<'a> {
         let psize_ptr = &mut psize as *mut c_int;
         let value_ptr = &mut value_le as *mut T;
 
-        unsafe {
+        self.with_eoe_hook(|c| unsafe {
             ecx_SDOread(
-                &mut self.context,
+                &mut c.context,
                 slave,
                 index,
                 subindex,
@@ -475,16 +953,77 @@ impl<'a> Context<'a> {
                 value_ptr as *mut std::ffi::c_void,
                 timeout,
             )
-        };
+        });
 
         self.iserror()
             .not()
             .as_result(num::PrimInt::from_le(value_le), ErrorIterator::new(self))
     }
+
+    /// Write raw SDO data, `complete_access` writes the subindices following `subindex` as well
+    pub fn write_sdo_bytes(
+        &mut self,
+        slave: u16,
+        index: u16,
+        subindex: u8,
+        complete_access: bool,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<(), ErrorIterator<'_>> {
+        self.with_eoe_hook(|c| unsafe {
+            ecx_SDOwrite(
+                &mut c.context,
+                slave,
+                index,
+                subindex,
+                complete_access as boolean,
+                data.len() as c_int,
+                data.as_ptr() as *mut std::ffi::c_void,
+                timeout,
+            )
+        });
+
+        self.iserror().not().as_result((), ErrorIterator::new(self))
+    }
+
+    /// Configure SYNC0 of the slave, the shift is in ns relative to the cycle start
+    pub fn dc_sync0(&mut self, slave: u16, active: bool, cycle_time: u32, cycle_shift: i32) {
+        unsafe {
+            ecx_dcsync0(
+                &mut self.context,
+                slave,
+                active as boolean,
+                cycle_time,
+                cycle_shift,
+            )
+        }
+    }
+
+    /// Configure SYNC0 and SYNC1 of the slave, SYNC1 fires `cycle_time1` ns after SYNC0
+    pub fn dc_sync01(
+        &mut self,
+        slave: u16,
+        active: bool,
+        cycle_time0: u32,
+        cycle_time1: u32,
+        cycle_shift: i32,
+    ) {
+        unsafe {
+            ecx_dcsync01(
+                &mut self.context,
+                slave,
+                active as boolean,
+                cycle_time0,
+                cycle_time1,
+                cycle_shift,
+            )
+        }
+    }
 }
 
 impl<'a> ErrorGenerator for Context<'a> {
     fn iserror(&mut self) -> bool {
+        self.dispatch_emergencies();
         unsafe { ecx_iserror(&mut self.context) != 0 }
     }
     fn next(&mut self) -> Option<String> {
@@ -495,3 +1034,206 @@ impl<'a> ErrorGenerator for Context<'a> {
         })
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::{
+        mem::ManuallyDrop,
+        ops::{Deref, DerefMut},
+        thread,
+    };
+
+    /// Buffers of a context over plain memory, `slaves[0]` is the whole image as in SOEM
+    pub(crate) struct MockBuffers {
+        slaves: Vec<Slave>,
+        slavecount: c_int,
+        groups: Vec<Group>,
+    }
+
+    impl MockBuffers {
+        pub(crate) fn new(slaves: Vec<Slave>, groups: usize) -> Self {
+            MockBuffers {
+                slavecount: slaves.len() as c_int - 1,
+                slaves,
+                groups: (0..groups).map(|_| Default::default()).collect(),
+            }
+        }
+
+        pub(crate) fn with_slaves(slaves: usize, groups: usize) -> Self {
+            MockBuffers::new((0..=slaves).map(|_| Default::default()).collect(), groups)
+        }
+
+        #[cfg(feature = "xml")]
+        pub(crate) fn groups_mut(&mut self) -> &mut [Group] {
+            &mut self.groups
+        }
+
+        /// Nothing is opened, so only calls which stay off the port may be used
+        pub(crate) fn context(&mut self) -> MockContext<'_> {
+            let mut context: ecx_context = unsafe { zeroed() };
+            context.slavelist = self.slaves.as_mut_ptr() as *mut ec_slave;
+            context.slavecount = &mut self.slavecount;
+            context.maxslave = self.slaves.len() as c_int;
+            context.grouplist = self.groups.as_mut_ptr() as *mut ec_group;
+            context.maxgroup = self.groups.len() as c_int;
+
+            MockContext(ManuallyDrop::new(Context {
+                context,
+                group_stats: vec![Default::default(); self.groups.len()],
+                emergency_handler: None,
+                foe_hook: None,
+                eoe_hook: None,
+                aoe_invoke_id: 0,
+                slave_configs: Vec::new(),
+                _phantom: Default::default(),
+            }))
+        }
+    }
+
+    /// Context which is not closed when dropped, its port was never opened
+    pub(crate) struct MockContext<'a>(ManuallyDrop<Context<'a>>);
+
+    impl<'a> Deref for MockContext<'a> {
+        type Target = Context<'a>;
+
+        fn deref(&self) -> &Context<'a> {
+            &self.0
+        }
+    }
+
+    impl<'a> DerefMut for MockContext<'a> {
+        fn deref_mut(&mut self) -> &mut Context<'a> {
+            &mut self.0
+        }
+    }
+
+    impl<'a> Drop for MockContext<'a> {
+        fn drop(&mut self) {
+            let c = &mut *self.0;
+            c.group_stats = Vec::new();
+            c.emergency_handler = None;
+            c.foe_hook = None;
+            c.eoe_hook = None;
+            c.slave_configs = Vec::new();
+        }
+    }
+
+    #[test]
+    fn context_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Context<'static>>();
+    }
+
+    #[test]
+    fn context_moves_to_another_thread() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        let mut c = buffers.context();
+        c.set_state(EtherCatState::PreOp, 1);
+        c.push_slave_config(SlaveConfig::new(2, 0x044c2c52));
+
+        let c = thread::scope(|s| {
+            s.spawn(move || {
+                assert_eq!(c.slaves()[0].state(), EtherCatState::PreOp);
+                assert_eq!(c.slave_configs().len(), 1);
+                c.set_state(EtherCatState::SafeOp, 2);
+                c
+            })
+            .join()
+            .unwrap()
+        });
+
+        assert_eq!(c.slaves()[1].state(), EtherCatState::SafeOp);
+    }
+
+    #[test]
+    fn process_image_borrows_slaves_apart() {
+        let mut io_map = vec![0u8; 6];
+        let base = io_map.as_mut_ptr();
+        let mut slaves: Vec<Slave> = (0..4).map(|_| Default::default()).collect();
+        unsafe {
+            *base.add(4) = 7;
+            *base.add(5) = 8;
+            let layout = [(32, base, 16, base.add(4)), (16, base, 8, base.add(4))];
+            for (slave, &(obits, outputs, ibits, inputs)) in slaves.iter_mut().zip(layout.iter()) {
+                slave.0.Obits = obits;
+                slave.0.Obytes = obits as u32 / 8;
+                slave.0.outputs = outputs;
+                slave.0.Ibits = ibits;
+                slave.0.Ibytes = ibits as u32 / 8;
+                slave.0.inputs = inputs;
+            }
+            // Less than a byte of outputs
+            slaves[2].0.Obits = 4;
+            slaves[2].0.outputs = base.add(2);
+            slaves[2].0.Ibits = 8;
+            slaves[2].0.Ibytes = 1;
+            slaves[2].0.inputs = base.add(5);
+        }
+
+        let mut buffers = MockBuffers::new(slaves, 1);
+        let mut c = buffers.context();
+        let mut image = c.process_image();
+        assert_eq!(image.slaves().len(), 3);
+
+        image.outputs_mut(1).unwrap().copy_from_slice(&[1, 2]);
+        image.outputs_mut(2).unwrap()[0] = 3;
+        assert!(image.outputs_mut(0).is_none());
+        assert!(image.outputs_mut(4).is_none());
+        assert_eq!(image.outputs_mut(3).map(|x| x.len()), Some(0));
+
+        assert_eq!(image.outputs(0), Some(&[1, 2, 3, 0][..]));
+        assert_eq!(image.outputs(2), Some(&[3][..]));
+        assert_eq!(image.inputs(0), Some(&[7, 8][..]));
+        assert_eq!(image.inputs(1), Some(&[7][..]));
+        assert_eq!(image.inputs(2), Some(&[8][..]));
+        assert_eq!(image.inputs(3), Some(&[][..]));
+        assert_eq!(image.inputs(4), None);
+
+        drop(c);
+        assert_eq!(io_map, [1, 2, 3, 0, 7, 8]);
+    }
+
+    #[test]
+    fn process_data_result_classifies_wkc() {
+        let ok = ProcessDataResult::new(3, 3);
+        assert_eq!(ok.status(), ProcessDataStatus::Ok);
+        assert_eq!(ok.wkc(), 3);
+        assert!(ok.is_ok());
+
+        let partial = ProcessDataResult::new(2, 3);
+        assert_eq!(partial.status(), ProcessDataStatus::Partial);
+        assert_eq!(partial.wkc(), 2);
+        assert_eq!(partial.expected_wkc(), 3);
+
+        assert_eq!(
+            ProcessDataResult::new(0, 3).status(),
+            ProcessDataStatus::Partial
+        );
+        assert_eq!(ProcessDataResult::new(0, 0).status(), ProcessDataStatus::Ok);
+    }
+
+    #[test]
+    fn process_data_result_reports_lost_frames() {
+        for &code in [-1, -2, -3, -5, -4, -100, c_int::MIN].iter() {
+            let x = ProcessDataResult::new(code, 3);
+            assert_eq!(x.status(), ProcessDataStatus::NoFrame);
+            assert_eq!(x.wkc(), 0);
+        }
+    }
+
+    #[test]
+    fn group_stats_counts_failures() {
+        let mut stats = GroupStats::default();
+        stats.update(&ProcessDataResult::new(1, 3));
+        stats.update(&ProcessDataResult::new(-1, 3));
+        assert_eq!(stats.cycles(), 2);
+        assert_eq!(stats.wkc_mismatches(), 1);
+        assert_eq!(stats.lost_frames(), 1);
+        assert_eq!(stats.consecutive_failures(), 2);
+
+        stats.update(&ProcessDataResult::new(3, 3));
+        assert_eq!(stats.cycles(), 3);
+        assert_eq!(stats.consecutive_failures(), 0);
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{error::MailboxError, Context};
+use std::{mem, mem::zeroed, os::raw::c_int, result};
+use SOEM_sys::{ec_mbxbuft, ec_nextmbxcnt, ecx_mbxreceive, ecx_mbxsend};
+
+/** Size of the mailbox header */
+const MAILBOX_HEADER_SIZE: usize = 6;
+
+#[derive(FromPrimitive, Debug, PartialEq, Clone, Copy)]
+#[repr(u8)]
+pub enum MailboxType {
+    /// Mailbox error reply
+    Error = 0x00,
+    /// ADS over EtherCat
+    AoE = 0x01,
+    /// Ethernet over EtherCat
+    EoE = 0x02,
+    /// CANopen over EtherCat
+    CoE = 0x03,
+    /// File access over EtherCat
+    FoE = 0x04,
+    /// Servo profile over EtherCat
+    SoE = 0x05,
+    /// Vendor specific
+    VoE = 0x0f,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct MailboxHeader {
+    length: u16,
+    address: u16,
+    channel: u8,
+    priority: u8,
+    mailbox_type: u8,
+    counter: u8,
+}
+
+impl MailboxHeader {
+    /// Length of the data following the header
+    pub const fn length(&self) -> u16 {
+        self.length
+    }
+    /// Station address of the originator
+    pub const fn address(&self) -> u16 {
+        self.address
+    }
+    pub const fn channel(&self) -> u8 {
+        self.channel
+    }
+    pub const fn priority(&self) -> u8 {
+        self.priority
+    }
+    /// Protocol of the mailbox, `None` for types unknown to the crate
+    pub fn mailbox_type(&self) -> Option<MailboxType> {
+        num::FromPrimitive::from_u8(self.mailbox_type)
+    }
+    pub const fn raw_type(&self) -> u8 {
+        self.mailbox_type
+    }
+    /// Session counter, 1 to 7
+    pub const fn counter(&self) -> u8 {
+        self.counter
+    }
+
+    fn to_bytes(self) -> [u8; MAILBOX_HEADER_SIZE] {
+        let length = self.length.to_le_bytes();
+        let address = self.address.to_le_bytes();
+        [
+            length[0],
+            length[1],
+            address[0],
+            address[1],
+            (self.channel & 0x3f) | (self.priority << 6),
+            (self.mailbox_type & 0x0f) | ((self.counter & 0x07) << 4),
+        ]
+    }
+
+    fn from_bytes(x: &[u8]) -> Self {
+        MailboxHeader {
+            length: u16::from_le_bytes([x[0], x[1]]),
+            address: u16::from_le_bytes([x[2], x[3]]),
+            channel: x[4] & 0x3f,
+            priority: x[4] >> 6,
+            mailbox_type: x[5] & 0x0f,
+            counter: (x[5] >> 4) & 0x07,
+        }
+    }
+}
+
+/// Mailbox message as exchanged with a slave
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mailbox {
+    header: MailboxHeader,
+    data: Vec<u8>,
+}
+
+impl Mailbox {
+    pub fn new(mailbox_type: MailboxType, data: &[u8]) -> Self {
+        Mailbox {
+            header: MailboxHeader {
+                length: data.len() as u16,
+                mailbox_type: mailbox_type as u8,
+                ..Default::default()
+            },
+            data: data.to_vec(),
+        }
+    }
+
+    pub const fn header(&self) -> &MailboxHeader {
+        &self.header
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn set_address(&mut self, address: u16) -> &mut Self {
+        self.header.address = address;
+        self
+    }
+    pub fn set_channel(&mut self, channel: u8) -> &mut Self {
+        self.header.channel = channel & 0x3f;
+        self
+    }
+    pub fn set_priority(&mut self, priority: u8) -> &mut Self {
+        self.header.priority = priority & 0x03;
+        self
+    }
+    /// Set the session counter, by default [`Context::mbx_send`] takes the next one of the slave
+    pub fn set_counter(&mut self, counter: u8) -> &mut Self {
+        self.header.counter = counter & 0x07;
+        self
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Write the mailbox to the slave.
+    ///
+    /// The message is sent as soon as the slave mailbox is empty or `timeout` expires.
+    pub fn mbx_send(
+        &mut self,
+        slave: u16,
+        mbx: &Mailbox,
+        timeout: c_int,
+    ) -> result::Result<(), MailboxError> {
+        if !self.has_slave(slave) {
+            return Err(MailboxError::UnknownSlave(slave));
+        }
+
+        let size = MAILBOX_HEADER_SIZE + mbx.data.len();
+        let mailbox_len = self.slave_mut(slave).mailbox_len() as usize;
+        if size > mailbox_len || size > mem::size_of::<ec_mbxbuft>() {
+            return Err(MailboxError::TooLarge(size, mailbox_len));
+        }
+
+        let mut header = mbx.header;
+        if header.counter == 0 {
+            let raw = &mut self.slave_mut(slave).0;
+            raw.mbx_cnt = unsafe { ec_nextmbxcnt(raw.mbx_cnt) };
+            header.counter = raw.mbx_cnt;
+        }
+
+        let mut buf: ec_mbxbuft = unsafe { zeroed() };
+        buf[..MAILBOX_HEADER_SIZE].copy_from_slice(&header.to_bytes());
+        buf[MAILBOX_HEADER_SIZE..size].copy_from_slice(&mbx.data);
+
+        match unsafe { ecx_mbxsend(&mut self.context, slave, &mut buf, timeout) } {
+            x if x > 0 => Ok(()),
+            _ => Err(MailboxError::NoResponse),
+        }
+    }
+
+    /// Read the mailbox of the slave.
+    ///
+    /// Mailbox errors, CoE emergencies and, while [`Context::on_eoe_frame`] is active, EoE
+    /// fragments are consumed by SOEM and never returned here.
+    pub fn mbx_receive(
+        &mut self,
+        slave: u16,
+        timeout: c_int,
+    ) -> result::Result<Mailbox, MailboxError> {
+        if !self.has_slave(slave) {
+            return Err(MailboxError::UnknownSlave(slave));
+        }
+
+        let mut buf: ec_mbxbuft = unsafe { zeroed() };
+
+        match self
+            .with_eoe_hook(|c| unsafe { ecx_mbxreceive(&mut c.context, slave, &mut buf, timeout) })
+        {
+            x if x > 0 => {
+                let header = MailboxHeader::from_bytes(&buf);
+                let end = (MAILBOX_HEADER_SIZE + header.length as usize).min(buf.len());
+                Ok(Mailbox {
+                    header,
+                    data: buf[MAILBOX_HEADER_SIZE..end].to_vec(),
+                })
+            }
+            _ => Err(MailboxError::NoResponse),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+
+    #[test]
+    fn header_round_trip() {
+        let bytes = [0x0a, 0x00, 0x01, 0x10, 0xc5, 0x53];
+        let header = MailboxHeader::from_bytes(&bytes);
+        assert_eq!(header.length(), 10);
+        assert_eq!(header.address(), 0x1001);
+        assert_eq!(header.channel(), 5);
+        assert_eq!(header.priority(), 3);
+        assert_eq!(header.mailbox_type(), Some(MailboxType::CoE));
+        assert_eq!(header.counter(), 5);
+        assert_eq!(header.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn unknown_slaves_are_rejected() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        let mut c = buffers.context();
+        let mbx = Mailbox::new(MailboxType::VoE, &[1, 2, 3]);
+
+        for &slave in [0, 3, 0xffff].iter() {
+            assert_eq!(
+                c.mbx_send(slave, &mbx, 0),
+                Err(MailboxError::UnknownSlave(slave))
+            );
+            assert_eq!(
+                c.mbx_receive(slave, 0),
+                Err(MailboxError::UnknownSlave(slave))
+            );
+        }
+    }
+}
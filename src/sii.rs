@@ -0,0 +1,1370 @@
+use crate::{
+    error::{EepromError, SiiError},
+    Context, EC_MAXEEPBUF,
+};
+use std::{convert::TryFrom, result};
+
+/** Size of the fixed area preceding the categories */
+const SII_HEADER_SIZE: usize = 0x80;
+/** Category types */
+const SII_STRINGS: u16 = 10;
+const SII_GENERAL: u16 = 30;
+const SII_FMMU: u16 = 40;
+const SII_SYNCM: u16 = 41;
+const SII_TXPDO: u16 = 50;
+const SII_RXPDO: u16 = 51;
+const SII_DC: u16 = 60;
+const SII_END: u16 = 0xffff;
+/** Category type read from an erased or never written EEPROM */
+const SII_BLANK: u16 = 0x0000;
+/** Word address of the configured station alias */
+const SII_ALIAS: u16 = 0x04;
+/** Word address of the config area checksum */
+const SII_CHECKSUM: u16 = 0x07;
+/** Number of bytes covered by the config area checksum */
+const SII_CHECKSUM_SIZE: usize = 14;
+
+struct Reader<'b>(&'b [u8]);
+
+impl<'b> Reader<'b> {
+    fn bytes(&mut self, n: usize) -> Option<&'b [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (x, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Some(x)
+    }
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|x| x[0])
+    }
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|x| u16::from_le_bytes([x[0], x[1]]))
+    }
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4)
+            .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+    }
+}
+
+/// Mailbox location as described in the SII
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SiiMailbox {
+    receive_offset: u16,
+    receive_size: u16,
+    send_offset: u16,
+    send_size: u16,
+}
+
+impl SiiMailbox {
+    pub const fn new(
+        receive_offset: u16,
+        receive_size: u16,
+        send_offset: u16,
+        send_size: u16,
+    ) -> Self {
+        SiiMailbox {
+            receive_offset,
+            receive_size,
+            send_offset,
+            send_size,
+        }
+    }
+
+    /// Offset of the mailbox written by the master
+    pub const fn receive_offset(&self) -> u16 {
+        self.receive_offset
+    }
+    pub const fn receive_size(&self) -> u16 {
+        self.receive_size
+    }
+    /// Offset of the mailbox read by the master
+    pub const fn send_offset(&self) -> u16 {
+        self.send_offset
+    }
+    pub const fn send_size(&self) -> u16 {
+        self.send_size
+    }
+
+    fn parse(r: &mut Reader) -> Option<Self> {
+        Some(SiiMailbox {
+            receive_offset: r.u16()?,
+            receive_size: r.u16()?,
+            send_offset: r.u16()?,
+            send_size: r.u16()?,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.receive_offset.to_le_bytes());
+        out.extend_from_slice(&self.receive_size.to_le_bytes());
+        out.extend_from_slice(&self.send_offset.to_le_bytes());
+        out.extend_from_slice(&self.send_size.to_le_bytes());
+    }
+}
+
+/// General category
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SiiGeneral {
+    group_idx: u8,
+    image_idx: u8,
+    order_idx: u8,
+    name_idx: u8,
+    coe_details: u8,
+    foe_details: u8,
+    eoe_details: u8,
+    soe_channels: u8,
+    ds402_channels: u8,
+    sysman_class: u8,
+    flags: u8,
+    current_on_ebus: i16,
+    physical_port: u16,
+    physical_memory_address: u16,
+}
+
+impl SiiGeneral {
+    /// String index of the device group
+    pub const fn group_idx(&self) -> u8 {
+        self.group_idx
+    }
+    /// String index of the image name
+    pub const fn image_idx(&self) -> u8 {
+        self.image_idx
+    }
+    /// String index of the order number
+    pub const fn order_idx(&self) -> u8 {
+        self.order_idx
+    }
+    /// String index of the device name
+    pub const fn name_idx(&self) -> u8 {
+        self.name_idx
+    }
+    pub const fn coe_details(&self) -> u8 {
+        self.coe_details
+    }
+    pub const fn foe_details(&self) -> u8 {
+        self.foe_details
+    }
+    pub const fn eoe_details(&self) -> u8 {
+        self.eoe_details
+    }
+    pub const fn soe_channels(&self) -> u8 {
+        self.soe_channels
+    }
+    pub const fn ds402_channels(&self) -> u8 {
+        self.ds402_channels
+    }
+    pub const fn sysman_class(&self) -> u8 {
+        self.sysman_class
+    }
+    pub const fn flags(&self) -> u8 {
+        self.flags
+    }
+    /// Current consumption from E-Bus in mA, negative values are fed into E-Bus
+    pub const fn current_on_ebus(&self) -> i16 {
+        self.current_on_ebus
+    }
+    /// Physical layer of the ports, a nibble per port
+    pub const fn physical_port(&self) -> u16 {
+        self.physical_port
+    }
+    pub const fn physical_memory_address(&self) -> u16 {
+        self.physical_memory_address
+    }
+
+    fn parse(r: &mut Reader) -> Option<Self> {
+        let group_idx = r.u8()?;
+        let image_idx = r.u8()?;
+        let order_idx = r.u8()?;
+        let name_idx = r.u8()?;
+        r.u8()?;
+        let coe_details = r.u8()?;
+        let foe_details = r.u8()?;
+        let eoe_details = r.u8()?;
+        let soe_channels = r.u8()?;
+        let ds402_channels = r.u8()?;
+        let sysman_class = r.u8()?;
+        let flags = r.u8()?;
+        let current_on_ebus = r.u16()? as i16;
+        r.bytes(2)?;
+        let physical_port = r.u16()?;
+        let physical_memory_address = r.u16()?;
+
+        Some(SiiGeneral {
+            group_idx,
+            image_idx,
+            order_idx,
+            name_idx,
+            coe_details,
+            foe_details,
+            eoe_details,
+            soe_channels,
+            ds402_channels,
+            sysman_class,
+            flags,
+            current_on_ebus,
+            physical_port,
+            physical_memory_address,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[
+            self.group_idx,
+            self.image_idx,
+            self.order_idx,
+            self.name_idx,
+            0,
+            self.coe_details,
+            self.foe_details,
+            self.eoe_details,
+            self.soe_channels,
+            self.ds402_channels,
+            self.sysman_class,
+            self.flags,
+        ]);
+        out.extend_from_slice(&self.current_on_ebus.to_le_bytes());
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&self.physical_port.to_le_bytes());
+        out.extend_from_slice(&self.physical_memory_address.to_le_bytes());
+        out.extend_from_slice(&[0; 12]);
+    }
+
+    pub fn set_group_idx(&mut self, group_idx: u8) -> &mut Self {
+        self.group_idx = group_idx;
+        self
+    }
+    pub fn set_image_idx(&mut self, image_idx: u8) -> &mut Self {
+        self.image_idx = image_idx;
+        self
+    }
+    pub fn set_order_idx(&mut self, order_idx: u8) -> &mut Self {
+        self.order_idx = order_idx;
+        self
+    }
+    pub fn set_name_idx(&mut self, name_idx: u8) -> &mut Self {
+        self.name_idx = name_idx;
+        self
+    }
+    pub fn set_coe_details(&mut self, coe_details: u8) -> &mut Self {
+        self.coe_details = coe_details;
+        self
+    }
+    pub fn set_foe_details(&mut self, foe_details: u8) -> &mut Self {
+        self.foe_details = foe_details;
+        self
+    }
+    pub fn set_eoe_details(&mut self, eoe_details: u8) -> &mut Self {
+        self.eoe_details = eoe_details;
+        self
+    }
+    pub fn set_soe_channels(&mut self, soe_channels: u8) -> &mut Self {
+        self.soe_channels = soe_channels;
+        self
+    }
+    pub fn set_ds402_channels(&mut self, ds402_channels: u8) -> &mut Self {
+        self.ds402_channels = ds402_channels;
+        self
+    }
+    pub fn set_sysman_class(&mut self, sysman_class: u8) -> &mut Self {
+        self.sysman_class = sysman_class;
+        self
+    }
+    pub fn set_flags(&mut self, flags: u8) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+    pub fn set_current_on_ebus(&mut self, current_on_ebus: i16) -> &mut Self {
+        self.current_on_ebus = current_on_ebus;
+        self
+    }
+    pub fn set_physical_port(&mut self, physical_port: u16) -> &mut Self {
+        self.physical_port = physical_port;
+        self
+    }
+    pub fn set_physical_memory_address(&mut self, physical_memory_address: u16) -> &mut Self {
+        self.physical_memory_address = physical_memory_address;
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FmmuUsage {
+    Unused,
+    Outputs,
+    Inputs,
+    /// Mailbox status of a sync manager
+    SyncManagerStatus,
+    Other(u8),
+}
+
+impl From<u8> for FmmuUsage {
+    fn from(x: u8) -> Self {
+        match x {
+            0x00 | 0xff => FmmuUsage::Unused,
+            0x01 => FmmuUsage::Outputs,
+            0x02 => FmmuUsage::Inputs,
+            0x03 => FmmuUsage::SyncManagerStatus,
+            x => FmmuUsage::Other(x),
+        }
+    }
+}
+
+impl From<FmmuUsage> for u8 {
+    fn from(x: FmmuUsage) -> Self {
+        match x {
+            FmmuUsage::Unused => 0x00,
+            FmmuUsage::Outputs => 0x01,
+            FmmuUsage::Inputs => 0x02,
+            FmmuUsage::SyncManagerStatus => 0x03,
+            FmmuUsage::Other(x) => x,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SyncManagerType {
+    Unused,
+    /// Mailbox written by the master
+    MailboxOut,
+    /// Mailbox read by the master
+    MailboxIn,
+    Outputs,
+    Inputs,
+    Other(u8),
+}
+
+impl From<u8> for SyncManagerType {
+    fn from(x: u8) -> Self {
+        match x {
+            0x00 => SyncManagerType::Unused,
+            0x01 => SyncManagerType::MailboxOut,
+            0x02 => SyncManagerType::MailboxIn,
+            0x03 => SyncManagerType::Outputs,
+            0x04 => SyncManagerType::Inputs,
+            x => SyncManagerType::Other(x),
+        }
+    }
+}
+
+impl From<SyncManagerType> for u8 {
+    fn from(x: SyncManagerType) -> Self {
+        match x {
+            SyncManagerType::Unused => 0x00,
+            SyncManagerType::MailboxOut => 0x01,
+            SyncManagerType::MailboxIn => 0x02,
+            SyncManagerType::Outputs => 0x03,
+            SyncManagerType::Inputs => 0x04,
+            SyncManagerType::Other(x) => x,
+        }
+    }
+}
+
+/// Sync manager entry of the SyncM category
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SiiSyncManager {
+    start_addr: u16,
+    length: u16,
+    control: u8,
+    status: u8,
+    enable: u8,
+    sm_type: SyncManagerType,
+}
+
+impl SiiSyncManager {
+    pub const fn new(
+        start_addr: u16,
+        length: u16,
+        control: u8,
+        enable: u8,
+        sm_type: SyncManagerType,
+    ) -> Self {
+        SiiSyncManager {
+            start_addr,
+            length,
+            control,
+            status: 0,
+            enable,
+            sm_type,
+        }
+    }
+
+    pub const fn start_addr(&self) -> u16 {
+        self.start_addr
+    }
+    pub const fn length(&self) -> u16 {
+        self.length
+    }
+    /// Initial value of the control register
+    pub const fn control(&self) -> u8 {
+        self.control
+    }
+    pub const fn status(&self) -> u8 {
+        self.status
+    }
+    pub const fn enable(&self) -> u8 {
+        self.enable
+    }
+    pub const fn sm_type(&self) -> SyncManagerType {
+        self.sm_type
+    }
+
+    fn parse(r: &mut Reader) -> Option<Self> {
+        Some(SiiSyncManager {
+            start_addr: r.u16()?,
+            length: r.u16()?,
+            control: r.u8()?,
+            status: r.u8()?,
+            enable: r.u8()?,
+            sm_type: r.u8()?.into(),
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.start_addr.to_le_bytes());
+        out.extend_from_slice(&self.length.to_le_bytes());
+        out.extend_from_slice(&[self.control, self.status, self.enable, self.sm_type.into()]);
+    }
+}
+
+/// Object mapped by a PDO
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SiiPdoEntry {
+    index: u16,
+    subindex: u8,
+    name_idx: u8,
+    data_type: u8,
+    bit_len: u8,
+    flags: u16,
+}
+
+impl SiiPdoEntry {
+    pub const fn new(index: u16, subindex: u8, data_type: u8, bit_len: u8) -> Self {
+        SiiPdoEntry {
+            index,
+            subindex,
+            name_idx: 0,
+            data_type,
+            bit_len,
+            flags: 0,
+        }
+    }
+
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    pub const fn subindex(&self) -> u8 {
+        self.subindex
+    }
+    pub const fn name_idx(&self) -> u8 {
+        self.name_idx
+    }
+    /// CoE data type, the index of the type in the object dictionary
+    pub const fn data_type(&self) -> u8 {
+        self.data_type
+    }
+    pub const fn bit_len(&self) -> u8 {
+        self.bit_len
+    }
+    pub const fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    fn parse(r: &mut Reader) -> Option<Self> {
+        Some(SiiPdoEntry {
+            index: r.u16()?,
+            subindex: r.u8()?,
+            name_idx: r.u8()?,
+            data_type: r.u8()?,
+            bit_len: r.u8()?,
+            flags: r.u16()?,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.index.to_le_bytes());
+        out.extend_from_slice(&[self.subindex, self.name_idx, self.data_type, self.bit_len]);
+        out.extend_from_slice(&self.flags.to_le_bytes());
+    }
+
+    pub fn set_name_idx(&mut self, name_idx: u8) -> &mut Self {
+        self.name_idx = name_idx;
+        self
+    }
+    pub fn set_flags(&mut self, flags: u16) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+}
+
+/// TxPDO or RxPDO category
+#[derive(Debug, PartialEq, Clone)]
+pub struct SiiPdo {
+    index: u16,
+    sync_manager: u8,
+    synchronization: u8,
+    name_idx: u8,
+    flags: u16,
+    entries: Vec<SiiPdoEntry>,
+}
+
+impl SiiPdo {
+    pub const fn new(index: u16, sync_manager: u8) -> Self {
+        SiiPdo {
+            index,
+            sync_manager,
+            synchronization: 0,
+            name_idx: 0,
+            flags: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub const fn index(&self) -> u16 {
+        self.index
+    }
+    /// Sync manager the PDO is assigned to
+    pub const fn sync_manager(&self) -> u8 {
+        self.sync_manager
+    }
+    pub const fn synchronization(&self) -> u8 {
+        self.synchronization
+    }
+    pub const fn name_idx(&self) -> u8 {
+        self.name_idx
+    }
+    pub const fn flags(&self) -> u16 {
+        self.flags
+    }
+    pub fn entries(&self) -> &[SiiPdoEntry] {
+        &self.entries
+    }
+    pub fn bit_size(&self) -> usize {
+        self.entries.iter().map(|x| x.bit_len as usize).sum()
+    }
+
+    fn parse(r: &mut Reader) -> Option<Self> {
+        let index = r.u16()?;
+        let n = r.u8()?;
+        let sync_manager = r.u8()?;
+        let synchronization = r.u8()?;
+        let name_idx = r.u8()?;
+        let flags = r.u16()?;
+        let entries = (0..n)
+            .map(|_| SiiPdoEntry::parse(r))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(SiiPdo {
+            index,
+            sync_manager,
+            synchronization,
+            name_idx,
+            flags,
+            entries,
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) -> Option<()> {
+        out.extend_from_slice(&self.index.to_le_bytes());
+        out.extend_from_slice(&[
+            u8::try_from(self.entries.len()).ok()?,
+            self.sync_manager,
+            self.synchronization,
+            self.name_idx,
+        ]);
+        out.extend_from_slice(&self.flags.to_le_bytes());
+        self.entries.iter().for_each(|x| x.write(out));
+        Some(())
+    }
+
+    pub fn set_synchronization(&mut self, synchronization: u8) -> &mut Self {
+        self.synchronization = synchronization;
+        self
+    }
+    pub fn set_name_idx(&mut self, name_idx: u8) -> &mut Self {
+        self.name_idx = name_idx;
+        self
+    }
+    pub fn set_flags(&mut self, flags: u16) -> &mut Self {
+        self.flags = flags;
+        self
+    }
+    pub fn push_entry(&mut self, entry: SiiPdoEntry) -> &mut Self {
+        self.entries.push(entry);
+        self
+    }
+}
+
+/// Operation mode of the DC category
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct SiiDc {
+    cycle_time0: u32,
+    shift_time0: u32,
+    shift_time1: u32,
+    sync1_cycle_factor: i16,
+    assign_activate: u16,
+    sync0_cycle_factor: i16,
+    name_idx: u8,
+    desc_idx: u8,
+}
+
+impl SiiDc {
+    pub const fn cycle_time0(&self) -> u32 {
+        self.cycle_time0
+    }
+    pub const fn shift_time0(&self) -> u32 {
+        self.shift_time0
+    }
+    pub const fn shift_time1(&self) -> u32 {
+        self.shift_time1
+    }
+    pub const fn sync1_cycle_factor(&self) -> i16 {
+        self.sync1_cycle_factor
+    }
+    /// Value of the DC activation register 0x0980
+    pub const fn assign_activate(&self) -> u16 {
+        self.assign_activate
+    }
+    pub const fn sync0_cycle_factor(&self) -> i16 {
+        self.sync0_cycle_factor
+    }
+    pub const fn name_idx(&self) -> u8 {
+        self.name_idx
+    }
+    pub const fn desc_idx(&self) -> u8 {
+        self.desc_idx
+    }
+
+    fn parse(r: &mut Reader) -> Option<Self> {
+        let res = SiiDc {
+            cycle_time0: r.u32()?,
+            shift_time0: r.u32()?,
+            shift_time1: r.u32()?,
+            sync1_cycle_factor: r.u16()? as i16,
+            assign_activate: r.u16()?,
+            sync0_cycle_factor: r.u16()? as i16,
+            name_idx: r.u8()?,
+            desc_idx: r.u8()?,
+        };
+        r.bytes(4)?;
+        Some(res)
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.cycle_time0.to_le_bytes());
+        out.extend_from_slice(&self.shift_time0.to_le_bytes());
+        out.extend_from_slice(&self.shift_time1.to_le_bytes());
+        out.extend_from_slice(&self.sync1_cycle_factor.to_le_bytes());
+        out.extend_from_slice(&self.assign_activate.to_le_bytes());
+        out.extend_from_slice(&self.sync0_cycle_factor.to_le_bytes());
+        out.extend_from_slice(&[self.name_idx, self.desc_idx, 0, 0, 0, 0]);
+    }
+
+    pub fn set_cycle_time0(&mut self, cycle_time0: u32) -> &mut Self {
+        self.cycle_time0 = cycle_time0;
+        self
+    }
+    pub fn set_shift_time0(&mut self, shift_time0: u32) -> &mut Self {
+        self.shift_time0 = shift_time0;
+        self
+    }
+    pub fn set_shift_time1(&mut self, shift_time1: u32) -> &mut Self {
+        self.shift_time1 = shift_time1;
+        self
+    }
+    pub fn set_sync1_cycle_factor(&mut self, sync1_cycle_factor: i16) -> &mut Self {
+        self.sync1_cycle_factor = sync1_cycle_factor;
+        self
+    }
+    pub fn set_assign_activate(&mut self, assign_activate: u16) -> &mut Self {
+        self.assign_activate = assign_activate;
+        self
+    }
+    pub fn set_sync0_cycle_factor(&mut self, sync0_cycle_factor: i16) -> &mut Self {
+        self.sync0_cycle_factor = sync0_cycle_factor;
+        self
+    }
+    pub fn set_name_idx(&mut self, name_idx: u8) -> &mut Self {
+        self.name_idx = name_idx;
+        self
+    }
+    pub fn set_desc_idx(&mut self, desc_idx: u8) -> &mut Self {
+        self.desc_idx = desc_idx;
+        self
+    }
+}
+
+/// Slave information interface, the content of the slave EEPROM
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Sii {
+    pdi_control: u16,
+    pdi_config: u16,
+    sync_impulse_len: u16,
+    pdi_config2: u16,
+    alias: u16,
+    /// Reserved words 5 and 6 of the config area, kept as read
+    config_reserved: [u8; 4],
+    checksum: u8,
+    vendor_id: u32,
+    product_code: u32,
+    revision: u32,
+    serial_number: u32,
+    bootstrap_mailbox: SiiMailbox,
+    standard_mailbox: SiiMailbox,
+    mailbox_protocols: u16,
+    eeprom_size: u16,
+    version: u16,
+    strings: Vec<String>,
+    general: Option<SiiGeneral>,
+    fmmu: Vec<FmmuUsage>,
+    sync_managers: Vec<SiiSyncManager>,
+    tx_pdos: Vec<SiiPdo>,
+    rx_pdos: Vec<SiiPdo>,
+    dc: Vec<SiiDc>,
+    other: Vec<(u16, Vec<u8>)>,
+}
+
+impl Sii {
+    pub const fn pdi_control(&self) -> u16 {
+        self.pdi_control
+    }
+    pub const fn pdi_config(&self) -> u16 {
+        self.pdi_config
+    }
+    pub const fn sync_impulse_len(&self) -> u16 {
+        self.sync_impulse_len
+    }
+    pub const fn pdi_config2(&self) -> u16 {
+        self.pdi_config2
+    }
+    /// Configured station alias
+    pub const fn alias(&self) -> u16 {
+        self.alias
+    }
+    /// CRC of the config area as stored in the image
+    pub const fn checksum(&self) -> u8 {
+        self.checksum
+    }
+    pub const fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+    pub const fn product_code(&self) -> u32 {
+        self.product_code
+    }
+    pub const fn revision(&self) -> u32 {
+        self.revision
+    }
+    pub const fn serial_number(&self) -> u32 {
+        self.serial_number
+    }
+    pub const fn bootstrap_mailbox(&self) -> &SiiMailbox {
+        &self.bootstrap_mailbox
+    }
+    pub const fn standard_mailbox(&self) -> &SiiMailbox {
+        &self.standard_mailbox
+    }
+    /// Supported mailbox protocols, a bit per [`MailboxType`](crate::MailboxType)
+    pub const fn mailbox_protocols(&self) -> u16 {
+        self.mailbox_protocols
+    }
+    /// EEPROM size in KiBit minus one
+    pub const fn eeprom_size(&self) -> u16 {
+        self.eeprom_size
+    }
+    pub const fn version(&self) -> u16 {
+        self.version
+    }
+    pub fn strings(&self) -> &[String] {
+        &self.strings
+    }
+    /// String by its 1-based index, index 0 stands for no string
+    pub fn string(&self, idx: u8) -> Option<&str> {
+        match idx {
+            0 => None,
+            x => self.strings.get(x as usize - 1).map(String::as_str),
+        }
+    }
+    pub const fn general(&self) -> Option<&SiiGeneral> {
+        self.general.as_ref()
+    }
+    /// Device name from the general category
+    pub fn name(&self) -> Option<&str> {
+        self.general.and_then(|x| self.string(x.name_idx))
+    }
+    pub fn fmmu(&self) -> &[FmmuUsage] {
+        &self.fmmu
+    }
+    pub fn sync_managers(&self) -> &[SiiSyncManager] {
+        &self.sync_managers
+    }
+    pub fn tx_pdos(&self) -> &[SiiPdo] {
+        &self.tx_pdos
+    }
+    pub fn rx_pdos(&self) -> &[SiiPdo] {
+        &self.rx_pdos
+    }
+    pub fn dc(&self) -> &[SiiDc] {
+        &self.dc
+    }
+    /// Categories unknown to the parser with their type
+    pub fn other(&self) -> &[(u16, Vec<u8>)] {
+        &self.other
+    }
+
+    /// Parse a raw EEPROM image
+    pub fn parse(image: &[u8]) -> result::Result<Sii, SiiError> {
+        let header = image
+            .get(..SII_HEADER_SIZE)
+            .ok_or(SiiError::TooShort(image.len()))?;
+
+        let mut r = Reader(header);
+        let mut sii = Sii::parse_header(&mut r).ok_or(SiiError::TooShort(image.len()))?;
+
+        let mut r = Reader(&image[SII_HEADER_SIZE..]);
+        loop {
+            // Images dumped up to the end of the data may miss the end marker
+            let cat = match r.u16() {
+                Some(SII_END) | Some(SII_BLANK) | None => break,
+                Some(x) => x,
+            };
+            let size = r.u16().ok_or(SiiError::Truncated(cat))? as usize * 2;
+            let data = r.bytes(size).ok_or(SiiError::Truncated(cat))?;
+            sii.parse_category(cat, data)
+                .ok_or(SiiError::Truncated(cat))?;
+        }
+
+        Ok(sii)
+    }
+
+    fn parse_header(r: &mut Reader) -> Option<Sii> {
+        let pdi_control = r.u16()?;
+        let pdi_config = r.u16()?;
+        let sync_impulse_len = r.u16()?;
+        let pdi_config2 = r.u16()?;
+        let alias = r.u16()?;
+        let mut config_reserved = [0; 4];
+        config_reserved.copy_from_slice(r.bytes(4)?);
+        let checksum = r.u16()? as u8;
+        let vendor_id = r.u32()?;
+        let product_code = r.u32()?;
+        let revision = r.u32()?;
+        let serial_number = r.u32()?;
+        r.bytes(8)?;
+        let bootstrap_mailbox = SiiMailbox::parse(r)?;
+        let standard_mailbox = SiiMailbox::parse(r)?;
+        let mailbox_protocols = r.u16()?;
+        r.bytes(66)?;
+        let eeprom_size = r.u16()?;
+        let version = r.u16()?;
+
+        Some(Sii {
+            pdi_control,
+            pdi_config,
+            sync_impulse_len,
+            pdi_config2,
+            alias,
+            config_reserved,
+            checksum,
+            vendor_id,
+            product_code,
+            revision,
+            serial_number,
+            bootstrap_mailbox,
+            standard_mailbox,
+            mailbox_protocols,
+            eeprom_size,
+            version,
+            ..Default::default()
+        })
+    }
+
+    fn parse_category(&mut self, cat: u16, data: &[u8]) -> Option<()> {
+        let mut r = Reader(data);
+        match cat {
+            SII_STRINGS => {
+                let n = r.u8()?;
+                for _ in 0..n {
+                    let len = r.u8()? as usize;
+                    // Strings are ISO 8859-1 encoded
+                    self.strings
+                        .push(r.bytes(len)?.iter().map(|&x| x as char).collect());
+                }
+            }
+            SII_GENERAL => self.general = Some(SiiGeneral::parse(&mut r)?),
+            SII_FMMU => self.fmmu = data.iter().map(|&x| x.into()).collect(),
+            SII_SYNCM => {
+                while !r.0.is_empty() {
+                    self.sync_managers.push(SiiSyncManager::parse(&mut r)?);
+                }
+            }
+            SII_TXPDO | SII_RXPDO => {
+                while !r.0.is_empty() {
+                    let pdo = SiiPdo::parse(&mut r)?;
+                    match cat {
+                        SII_TXPDO => self.tx_pdos.push(pdo),
+                        _ => self.rx_pdos.push(pdo),
+                    }
+                }
+            }
+            SII_DC => {
+                while !r.0.is_empty() {
+                    self.dc.push(SiiDc::parse(&mut r)?);
+                }
+            }
+            _ => self.other.push((cat, data.to_vec())),
+        }
+        Some(())
+    }
+
+    pub fn set_pdi_control(&mut self, pdi_control: u16) -> &mut Self {
+        self.pdi_control = pdi_control;
+        self
+    }
+    pub fn set_pdi_config(&mut self, pdi_config: u16) -> &mut Self {
+        self.pdi_config = pdi_config;
+        self
+    }
+    pub fn set_sync_impulse_len(&mut self, sync_impulse_len: u16) -> &mut Self {
+        self.sync_impulse_len = sync_impulse_len;
+        self
+    }
+    pub fn set_pdi_config2(&mut self, pdi_config2: u16) -> &mut Self {
+        self.pdi_config2 = pdi_config2;
+        self
+    }
+    pub fn set_alias(&mut self, alias: u16) -> &mut Self {
+        self.alias = alias;
+        self
+    }
+    pub fn set_vendor_id(&mut self, vendor_id: u32) -> &mut Self {
+        self.vendor_id = vendor_id;
+        self
+    }
+    pub fn set_product_code(&mut self, product_code: u32) -> &mut Self {
+        self.product_code = product_code;
+        self
+    }
+    pub fn set_revision(&mut self, revision: u32) -> &mut Self {
+        self.revision = revision;
+        self
+    }
+    pub fn set_serial_number(&mut self, serial_number: u32) -> &mut Self {
+        self.serial_number = serial_number;
+        self
+    }
+    pub fn set_bootstrap_mailbox(&mut self, mailbox: SiiMailbox) -> &mut Self {
+        self.bootstrap_mailbox = mailbox;
+        self
+    }
+    pub fn set_standard_mailbox(&mut self, mailbox: SiiMailbox) -> &mut Self {
+        self.standard_mailbox = mailbox;
+        self
+    }
+    pub fn set_mailbox_protocols(&mut self, mailbox_protocols: u16) -> &mut Self {
+        self.mailbox_protocols = mailbox_protocols;
+        self
+    }
+    /// Set the EEPROM size in KiBit minus one, the size is raised to fit the image if needed
+    pub fn set_eeprom_size(&mut self, eeprom_size: u16) -> &mut Self {
+        self.eeprom_size = eeprom_size;
+        self
+    }
+    pub fn set_version(&mut self, version: u16) -> &mut Self {
+        self.version = version;
+        self
+    }
+    /// Append a string, returns its index to be referenced from the other categories.
+    ///
+    /// The category holds up to 255 strings of up to 255 characters each.
+    pub fn push_string(&mut self, s: &str) -> result::Result<u8, SiiError> {
+        let idx =
+            u8::try_from(self.strings.len() + 1).map_err(|_| SiiError::TooLarge(SII_STRINGS))?;
+        if s.chars().count() > 0xff {
+            return Err(SiiError::TooLarge(SII_STRINGS));
+        }
+        self.strings.push(s.to_owned());
+        Ok(idx)
+    }
+    pub fn set_general(&mut self, general: SiiGeneral) -> &mut Self {
+        self.general = Some(general);
+        self
+    }
+    pub fn push_fmmu(&mut self, fmmu: FmmuUsage) -> &mut Self {
+        self.fmmu.push(fmmu);
+        self
+    }
+    pub fn push_sync_manager(&mut self, sm: SiiSyncManager) -> &mut Self {
+        self.sync_managers.push(sm);
+        self
+    }
+    pub fn push_tx_pdo(&mut self, pdo: SiiPdo) -> &mut Self {
+        self.tx_pdos.push(pdo);
+        self
+    }
+    pub fn push_rx_pdo(&mut self, pdo: SiiPdo) -> &mut Self {
+        self.rx_pdos.push(pdo);
+        self
+    }
+    pub fn push_dc(&mut self, dc: SiiDc) -> &mut Self {
+        self.dc.push(dc);
+        self
+    }
+
+    /// CRC-8 of the config area, words 0 to 6 of the image.
+    ///
+    /// The ESC refuses to load the config area unless word 7 holds this value.
+    pub fn config_checksum(image: &[u8]) -> u8 {
+        image.iter().take(SII_CHECKSUM_SIZE).fold(0xff, |crc, &x| {
+            (0..8).fold(crc ^ x, |crc, _| match crc & 0x80 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x07,
+            })
+        })
+    }
+
+    fn write_category(out: &mut Vec<u8>, cat: u16, data: &[u8]) -> result::Result<(), SiiError> {
+        let size = u16::try_from(data.len().div_ceil(2)).map_err(|_| SiiError::TooLarge(cat))?;
+        out.extend_from_slice(&cat.to_le_bytes());
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(data);
+        if data.len() % 2 != 0 {
+            out.push(0);
+        }
+        Ok(())
+    }
+
+    /// Raw EEPROM image with the config area checksum filled in.
+    ///
+    /// Fails if a category exceeds its counters, e.g. more than 255 strings or PDO entries.
+    pub fn to_bytes(&self) -> result::Result<Vec<u8>, SiiError> {
+        let mut out = Vec::with_capacity(EC_MAXEEPBUF);
+        for x in [
+            self.pdi_control,
+            self.pdi_config,
+            self.sync_impulse_len,
+            self.pdi_config2,
+            self.alias,
+        ]
+        .iter()
+        {
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        out.extend_from_slice(&self.config_reserved);
+        let checksum = Sii::config_checksum(&out);
+        out.extend_from_slice(&[checksum, 0]);
+        for x in [
+            self.vendor_id,
+            self.product_code,
+            self.revision,
+            self.serial_number,
+        ]
+        .iter()
+        {
+            out.extend_from_slice(&x.to_le_bytes());
+        }
+        out.extend_from_slice(&[0; 8]);
+        self.bootstrap_mailbox.write(&mut out);
+        self.standard_mailbox.write(&mut out);
+        out.extend_from_slice(&self.mailbox_protocols.to_le_bytes());
+        out.resize(SII_HEADER_SIZE - 4, 0);
+        // Size is patched once the categories are known
+        out.extend_from_slice(&[0, 0]);
+        out.extend_from_slice(&self.version.to_le_bytes());
+
+        if !self.strings.is_empty() {
+            let too_large = |_| SiiError::TooLarge(SII_STRINGS);
+            let mut data = vec![u8::try_from(self.strings.len()).map_err(too_large)?];
+            for x in self.strings.iter() {
+                // Strings are ISO 8859-1 encoded, other characters are replaced
+                let bytes: Vec<u8> = x
+                    .chars()
+                    .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+                    .collect();
+                data.push(u8::try_from(bytes.len()).map_err(too_large)?);
+                data.extend_from_slice(&bytes);
+            }
+            Sii::write_category(&mut out, SII_STRINGS, &data)?;
+        }
+        if let Some(ref general) = self.general {
+            let mut data = Vec::new();
+            general.write(&mut data);
+            Sii::write_category(&mut out, SII_GENERAL, &data)?;
+        }
+        if !self.fmmu.is_empty() {
+            let data: Vec<u8> = self.fmmu.iter().map(|&x| x.into()).collect();
+            Sii::write_category(&mut out, SII_FMMU, &data)?;
+        }
+        if !self.sync_managers.is_empty() {
+            let mut data = Vec::new();
+            self.sync_managers.iter().for_each(|x| x.write(&mut data));
+            Sii::write_category(&mut out, SII_SYNCM, &data)?;
+        }
+        // SOEM only looks at the first category of a type, so all PDOs of a direction share one
+        for (cat, pdos) in [(SII_TXPDO, &self.tx_pdos), (SII_RXPDO, &self.rx_pdos)].iter() {
+            if !pdos.is_empty() {
+                let mut data = Vec::new();
+                pdos.iter()
+                    .try_for_each(|x| x.write(&mut data))
+                    .ok_or(SiiError::TooLarge(*cat))?;
+                Sii::write_category(&mut out, *cat, &data)?;
+            }
+        }
+        if !self.dc.is_empty() {
+            let mut data = Vec::new();
+            self.dc.iter().for_each(|x| x.write(&mut data));
+            Sii::write_category(&mut out, SII_DC, &data)?;
+        }
+        for (cat, data) in self.other.iter() {
+            Sii::write_category(&mut out, *cat, data)?;
+        }
+        out.extend_from_slice(&SII_END.to_le_bytes());
+
+        let required = (out.len() * 8).div_ceil(1024).max(1) as u16 - 1;
+        let size = self.eeprom_size.max(required).to_le_bytes();
+        out[SII_HEADER_SIZE - 4..SII_HEADER_SIZE - 2].copy_from_slice(&size);
+
+        Ok(out)
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Read the whole EEPROM of the slave, up to the end category or the first blank one
+    pub fn read_sii_image(&mut self, slave: u16) -> result::Result<Vec<u8>, EepromError> {
+        let mut image = self.read_eeprom(slave, 0, SII_HEADER_SIZE)?;
+
+        loop {
+            let addr = (image.len() / 2) as u16;
+            let header = self.read_eeprom(slave, addr, 4)?;
+            let cat = u16::from_le_bytes([header[0], header[1]]);
+            let size = u16::from_le_bytes([header[2], header[3]]) as usize * 2;
+            // The image must stay below the 64 KiWord address space
+            let data_addr = addr
+                .checked_add(2)
+                .filter(|_| image.len() + 4 + size < 0x20000);
+            match (cat, data_addr) {
+                (SII_END, _) | (SII_BLANK, _) | (_, None) => {
+                    image.extend_from_slice(&header[..2]);
+                    return Ok(image);
+                }
+                (_, Some(data_addr)) => {
+                    image.extend_from_slice(&header);
+                    image.extend(self.read_eeprom(slave, data_addr, size)?);
+                }
+            }
+        }
+    }
+
+    /// Write the configured station alias to the SII and update the config area checksum.
+    ///
+    /// The ESC takes the new alias over on the next power cycle or EEPROM reload.
+    pub fn write_alias(&mut self, slave: u16, alias: u16) -> result::Result<(), EepromError> {
+        self.check_eeprom_slave(slave)?;
+        let mut config = self.read_eeprom(slave, 0, SII_CHECKSUM_SIZE + 2)?;
+        let alias_pos = SII_ALIAS as usize * 2;
+        config[alias_pos..alias_pos + 2].copy_from_slice(&alias.to_le_bytes());
+        config[SII_CHECKSUM_SIZE] = Sii::config_checksum(&config);
+
+        self.write_eeprom(slave, SII_ALIAS, &config[alias_pos..alias_pos + 2])?;
+        self.write_eeprom(slave, SII_CHECKSUM, &config[SII_CHECKSUM_SIZE..])?;
+        self.slave_mut(slave).0.aliasadr = alias;
+        Ok(())
+    }
+
+    /// Read and parse the EEPROM of the slave
+    pub fn sii(&mut self, slave: u16) -> result::Result<Sii, SiiError> {
+        let image = self.read_sii_image(slave).map_err(SiiError::EepromError)?;
+        Sii::parse(&image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::MockBuffers;
+
+    const EL1859: &[u8] = include_bytes!("testdata/el1859.bin");
+
+    #[test]
+    fn parse_header() {
+        let sii = Sii::parse(EL1859).unwrap();
+        assert_eq!(sii.pdi_control(), 0x0c80);
+        assert_eq!(sii.pdi_config(), 0x6e00);
+        assert_eq!(sii.checksum(), 0x8d);
+        assert_eq!(sii.vendor_id(), 2);
+        assert_eq!(sii.product_code(), 0x0743_3052);
+        assert_eq!(sii.revision(), 0x0011_0000);
+        assert_eq!(sii.standard_mailbox(), &SiiMailbox::default());
+        assert_eq!(sii.eeprom_size(), 0x000f);
+        assert_eq!(sii.version(), 1);
+    }
+
+    #[test]
+    fn parse_general_and_strings() {
+        let sii = Sii::parse(EL1859).unwrap();
+        assert_eq!(sii.strings().len(), 6);
+        assert_eq!(sii.string(0), None);
+        assert_eq!(sii.string(1), Some("EL1859"));
+        assert_eq!(sii.string(7), None);
+
+        let general = sii.general().unwrap();
+        assert_eq!(sii.string(general.group_idx()), Some("DigInOut"));
+        assert_eq!(general.current_on_ebus(), 100);
+        assert_eq!(general.physical_port(), 0x0011);
+        assert_eq!(
+            sii.name(),
+            Some("EL1859 8Ch. Dig. In, 24V, 3ms, 8Ch. Dig. Out 24V, 0.5A")
+        );
+    }
+
+    #[test]
+    fn parse_fmmu_and_sync_managers() {
+        let sii = Sii::parse(EL1859).unwrap();
+        assert_eq!(sii.fmmu(), &[FmmuUsage::Outputs, FmmuUsage::Inputs]);
+
+        let sm = sii.sync_managers();
+        assert_eq!(sm.len(), 2);
+        assert_eq!(
+            sm[0],
+            SiiSyncManager::new(0x0f00, 1, 0x44, 0x09, SyncManagerType::Outputs)
+        );
+        assert_eq!(
+            sm[1],
+            SiiSyncManager::new(0x1000, 1, 0x00, 0x01, SyncManagerType::Inputs)
+        );
+    }
+
+    #[test]
+    fn parse_pdos() {
+        let sii = Sii::parse(EL1859).unwrap();
+        assert_eq!(sii.tx_pdos().len(), 1);
+        assert_eq!(sii.rx_pdos().len(), 1);
+
+        let tx = &sii.tx_pdos()[0];
+        assert_eq!(tx.index(), 0x1a00);
+        assert_eq!(tx.sync_manager(), 1);
+        assert_eq!(sii.string(tx.name_idx()), Some("Channel 1"));
+        assert_eq!(tx.bit_size(), 8);
+        assert_eq!(tx.entries()[0].index(), 0x6000);
+        assert_eq!(tx.entries()[0].subindex(), 1);
+        assert_eq!(sii.string(tx.entries()[0].name_idx()), Some("Input"));
+
+        let rx = &sii.rx_pdos()[0];
+        assert_eq!(rx.index(), 0x1600);
+        assert_eq!(rx.sync_manager(), 0);
+        assert_eq!(rx.entries()[0].index(), 0x7000);
+        assert_eq!(rx.entries()[1].bit_len(), 7);
+        assert!(sii.dc().is_empty());
+        assert!(sii.other().is_empty());
+    }
+
+    #[test]
+    fn parse_stops_on_blank_categories() {
+        let mut image = EL1859[..SII_HEADER_SIZE].to_vec();
+        image.resize(0x800, 0);
+        let sii = Sii::parse(&image).unwrap();
+        assert!(sii.other().is_empty());
+
+        image[SII_HEADER_SIZE..].iter_mut().for_each(|x| *x = 0xff);
+        assert_eq!(Sii::parse(&image).unwrap().strings().len(), 0);
+    }
+
+    #[test]
+    fn parse_rejects_truncated_images() {
+        assert_eq!(Sii::parse(&EL1859[..0x40]), Err(SiiError::TooShort(0x40)));
+        assert_eq!(
+            Sii::parse(&EL1859[..SII_HEADER_SIZE + 8]),
+            Err(SiiError::Truncated(SII_STRINGS))
+        );
+    }
+
+    #[test]
+    fn config_checksum_vectors() {
+        // CRC-8 with polynomial x^8 + x^2 + x + 1 and initial value 0xff
+        assert_eq!(Sii::config_checksum(b"123456789"), 0xfb);
+        assert_eq!(Sii::config_checksum(&[0; SII_CHECKSUM_SIZE]), 0x30);
+        assert_eq!(Sii::config_checksum(EL1859), EL1859[SII_CHECKSUM_SIZE]);
+    }
+
+    #[test]
+    fn write_alias_rejects_unknown_slaves() {
+        let mut buffers = MockBuffers::with_slaves(2, 1);
+        let mut c = buffers.context();
+
+        for &slave in [0, 3, 0xffff].iter() {
+            assert_eq!(
+                c.write_alias(slave, 0x1234),
+                Err(EepromError::UnknownSlave(slave))
+            );
+        }
+    }
+
+    #[test]
+    fn to_bytes_round_trip() {
+        let sii = Sii::parse(EL1859).unwrap();
+        assert_eq!(sii.to_bytes().unwrap(), EL1859);
+        assert_eq!(Sii::parse(&sii.to_bytes().unwrap()).unwrap(), sii);
+    }
+
+    #[test]
+    fn to_bytes_writes_one_category_per_direction() {
+        let mut sii = Sii::default();
+        let mut pdo = SiiPdo::new(0x1a00, 3);
+        pdo.push_entry(SiiPdoEntry::new(0x6000, 1, 1, 1));
+        sii.push_tx_pdo(pdo);
+        let mut pdo = SiiPdo::new(0x1a01, 3);
+        pdo.push_entry(SiiPdoEntry::new(0x6010, 1, 1, 1))
+            .push_entry(SiiPdoEntry::new(0x6010, 2, 1, 1));
+        sii.push_tx_pdo(pdo);
+        sii.push_rx_pdo(SiiPdo::new(0x1600, 2));
+        sii.push_rx_pdo(SiiPdo::new(0x1601, 2));
+
+        let image = sii.to_bytes().unwrap();
+        let parsed = Sii::parse(&image).unwrap();
+        assert_eq!(parsed.tx_pdos(), sii.tx_pdos());
+        assert_eq!(parsed.rx_pdos(), sii.rx_pdos());
+        assert_eq!(parsed.to_bytes().unwrap(), image);
+
+        let mut categories = Vec::new();
+        let mut pos = SII_HEADER_SIZE;
+        loop {
+            let cat = u16::from_le_bytes([image[pos], image[pos + 1]]);
+            if cat == SII_END {
+                break;
+            }
+            categories.push(cat);
+            pos += 4 + u16::from_le_bytes([image[pos + 2], image[pos + 3]]) as usize * 2;
+        }
+        assert_eq!(categories, [SII_TXPDO, SII_RXPDO]);
+    }
+
+    #[test]
+    fn to_bytes_keeps_reserved_config_words() {
+        let mut image = EL1859.to_vec();
+        image[10..14].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        image[SII_CHECKSUM_SIZE] = Sii::config_checksum(&image);
+        assert_eq!(Sii::parse(&image).unwrap().to_bytes().unwrap(), image);
+    }
+
+    #[test]
+    fn counters_are_bounded() {
+        let mut sii = Sii::default();
+        assert_eq!(sii.push_string("first"), Ok(1));
+        assert_eq!(
+            sii.push_string(&"x".repeat(0x100)),
+            Err(SiiError::TooLarge(SII_STRINGS))
+        );
+        for i in 2..=0xff {
+            assert_eq!(sii.push_string(""), Ok(i));
+        }
+        assert_eq!(sii.push_string(""), Err(SiiError::TooLarge(SII_STRINGS)));
+        assert!(sii.to_bytes().is_ok());
+
+        let mut pdo = SiiPdo::new(0x1a00, 3);
+        for _ in 0..0x100 {
+            pdo.push_entry(SiiPdoEntry::new(0x6000, 1, 1, 1));
+        }
+        let mut sii = Sii::default();
+        sii.push_tx_pdo(pdo);
+        assert_eq!(sii.to_bytes(), Err(SiiError::TooLarge(SII_TXPDO)));
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    error::{drain_errors, ParseIdnError, SoEError},
+    Context,
+};
+use std::{
+    ffi::c_void,
+    fmt, mem,
+    mem::zeroed,
+    ops::{BitOr, BitOrAssign},
+    os::raw::c_int,
+    result,
+    str::FromStr,
+};
+use SOEM_sys::{
+    ec_err_type_EC_ERR_TYPE_PACKET_ERROR, ec_err_type_EC_ERR_TYPE_SOE_ERROR, ecx_SoEread,
+    ecx_SoEwrite, ecx_readIDNmap,
+};
+
+/// Identification number of a drive parameter
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Idn(u16);
+
+impl Idn {
+    /// Standard parameter S-`set`-`block`
+    pub const fn standard(set: u8, block: u16) -> Self {
+        Idn(((set as u16 & 0x7) << 12) | (block & 0xfff))
+    }
+    /// Product specific parameter P-`set`-`block`
+    pub const fn product(set: u8, block: u16) -> Self {
+        Idn(0x8000 | Idn::standard(set, block).0)
+    }
+
+    pub const fn is_product(&self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+    pub const fn parameter_set(&self) -> u8 {
+        ((self.0 >> 12) & 0x7) as u8
+    }
+    pub const fn data_block(&self) -> u16 {
+        self.0 & 0xfff
+    }
+    pub const fn raw(&self) -> u16 {
+        self.0
+    }
+}
+
+impl From<u16> for Idn {
+    fn from(x: u16) -> Self {
+        Idn(x)
+    }
+}
+
+impl fmt::Display for Idn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}-{:04}",
+            if self.is_product() { "P" } else { "S" },
+            self.parameter_set(),
+            self.data_block()
+        )
+    }
+}
+
+impl FromStr for Idn {
+    type Err = ParseIdnError;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let mut parts = s.trim().splitn(3, '-');
+        let kind = parts.next().ok_or(ParseIdnError)?;
+        let set = parts
+            .next()
+            .and_then(|x| x.parse::<u8>().ok())
+            .filter(|&x| x < 8)
+            .ok_or(ParseIdnError)?;
+        let block = parts
+            .next()
+            .and_then(|x| x.parse::<u16>().ok())
+            .filter(|&x| x < 0x1000)
+            .ok_or(ParseIdnError)?;
+
+        match kind {
+            "S" | "s" => Ok(Idn::standard(set, block)),
+            "P" | "p" => Ok(Idn::product(set, block)),
+            _ => Err(ParseIdnError),
+        }
+    }
+}
+
+/// Elements of an IDN to be transferred
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ElementFlags(u8);
+
+impl ElementFlags {
+    pub const DATASTATE: ElementFlags = ElementFlags(0x01);
+    pub const NAME: ElementFlags = ElementFlags(0x02);
+    pub const ATTRIBUTE: ElementFlags = ElementFlags(0x04);
+    pub const UNIT: ElementFlags = ElementFlags(0x08);
+    pub const MIN: ElementFlags = ElementFlags(0x10);
+    pub const MAX: ElementFlags = ElementFlags(0x20);
+    pub const VALUE: ElementFlags = ElementFlags(0x40);
+    pub const DEFAULT: ElementFlags = ElementFlags(0x80);
+
+    pub const fn from_bits(x: u8) -> Self {
+        ElementFlags(x)
+    }
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+    pub const fn contains(&self, other: ElementFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ElementFlags {
+    type Output = ElementFlags;
+
+    fn bitor(self, rhs: ElementFlags) -> ElementFlags {
+        ElementFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ElementFlags {
+    fn bitor_assign(&mut self, rhs: ElementFlags) {
+        self.0 |= rhs.0
+    }
+}
+
+impl<'a> Context<'a> {
+    fn soe_error(&mut self, slave: u16) -> SoEError {
+        self.dispatch_emergencies();
+
+        let mut res = SoEError::NoResponse;
+        drain_errors(&mut self.context, |err| {
+            if err.Slave != slave {
+                return false;
+            }
+            let code = unsafe { err.__bindgen_anon_1.__bindgen_anon_1.ErrorCode };
+            res = match err.Etype {
+                x if x == ec_err_type_EC_ERR_TYPE_SOE_ERROR => SoEError::Idn(code),
+                // SOEM reports a missing response as packet error 4
+                x if x == ec_err_type_EC_ERR_TYPE_PACKET_ERROR => match code {
+                    4 => SoEError::NoResponse,
+                    _ => SoEError::UnexpectedMailbox,
+                },
+                _ => return false,
+            };
+            true
+        });
+
+        res
+    }
+
+    /// Read elements of the IDN into `buf`, returns the number of bytes read
+    pub fn soe_read(
+        &mut self,
+        slave: u16,
+        drive: u8,
+        elements: ElementFlags,
+        idn: Idn,
+        buf: &mut [u8],
+        timeout: c_int,
+    ) -> result::Result<usize, SoEError> {
+        let mut psize = buf.len() as c_int;
+
+        match self.with_eoe_hook(|c| unsafe {
+            ecx_SoEread(
+                &mut c.context,
+                slave,
+                drive,
+                elements.bits(),
+                idn.raw(),
+                &mut psize,
+                buf.as_mut_ptr() as *mut c_void,
+                timeout,
+            )
+        }) {
+            x if x > 0 => Ok(psize as usize),
+            _ => Err(self.soe_error(slave)),
+        }
+    }
+
+    pub fn soe_write(
+        &mut self,
+        slave: u16,
+        drive: u8,
+        elements: ElementFlags,
+        idn: Idn,
+        data: &[u8],
+        timeout: c_int,
+    ) -> result::Result<(), SoEError> {
+        match self.with_eoe_hook(|c| unsafe {
+            ecx_SoEwrite(
+                &mut c.context,
+                slave,
+                drive,
+                elements.bits(),
+                idn.raw(),
+                data.len() as c_int,
+                data.as_ptr() as *mut c_void,
+                timeout,
+            )
+        }) {
+            x if x > 0 => Ok(()),
+            _ => Err(self.soe_error(slave)),
+        }
+    }
+
+    /// Read the operation data of the IDN
+    pub fn soe_read_value<T: num::PrimInt>(
+        &mut self,
+        slave: u16,
+        drive: u8,
+        idn: Idn,
+        timeout: c_int,
+    ) -> result::Result<T, SoEError> {
+        let mut value_le: T = unsafe { zeroed() };
+        let buf = unsafe {
+            std::slice::from_raw_parts_mut(&mut value_le as *mut T as *mut u8, mem::size_of::<T>())
+        };
+
+        let size = self.soe_read(slave, drive, ElementFlags::VALUE, idn, buf, timeout)?;
+        if size < mem::size_of::<T>() {
+            return Err(SoEError::ShortRead(size));
+        }
+        Ok(num::PrimInt::from_le(value_le))
+    }
+
+    /// Write the operation data of the IDN
+    pub fn soe_write_value<T: num::PrimInt>(
+        &mut self,
+        slave: u16,
+        drive: u8,
+        idn: Idn,
+        value: T,
+        timeout: c_int,
+    ) -> result::Result<(), SoEError> {
+        let value_le = value.to_le();
+        let data = unsafe {
+            std::slice::from_raw_parts(&value_le as *const T as *const u8, mem::size_of::<T>())
+        };
+
+        self.soe_write(slave, drive, ElementFlags::VALUE, idn, data, timeout)
+    }
+
+    /// Size in bits of the output (MDT) and input (AT) mapping of every drive of the slave
+    pub fn read_idn_map(&mut self, slave: u16) -> result::Result<(usize, usize), SoEError> {
+        let mut osize: c_int = 0;
+        let mut isize: c_int = 0;
+
+        match self.with_eoe_hook(|c| unsafe {
+            ecx_readIDNmap(&mut c.context, slave, &mut osize, &mut isize)
+        }) {
+            x if x > 0 => Ok((osize as usize, isize as usize)),
+            _ => Err(SoEError::NoMapping),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idn_round_trip() {
+        let idn: Idn = "S-0-0036".parse().unwrap();
+        assert_eq!(idn, Idn::standard(0, 36));
+        assert_eq!(idn.raw(), 36);
+        assert!(!idn.is_product());
+        assert_eq!(idn.to_string(), "S-0-0036");
+
+        let idn: Idn = " p-7-4095 ".parse().unwrap();
+        assert_eq!(idn, Idn::product(7, 4095));
+        assert_eq!(idn.raw(), 0xffff);
+        assert!(idn.is_product());
+        assert_eq!((idn.parameter_set(), idn.data_block()), (7, 4095));
+        assert_eq!(idn.to_string(), "P-7-4095");
+
+        assert_eq!(Idn::from(0x9005).to_string(), "P-1-0005");
+        assert_eq!("s-1-5".parse::<Idn>().unwrap().to_string(), "S-1-0005");
+    }
+
+    #[test]
+    fn idn_bounds() {
+        // Out of range parts are masked by the constructors
+        assert_eq!(Idn::standard(8, 0x1000), Idn::standard(0, 0));
+        assert_eq!(Idn::product(9, 0x1001), Idn::product(1, 1));
+
+        for s in [
+            "", "S", "S-0", "S-8-0", "S-0-4096", "X-0-1", "S--1", "S-0-", "S-0-1-2", "S-a-1",
+            "S-0-0x10", "SP-0-1", "S-256-1",
+        ]
+        .iter()
+        {
+            assert_eq!(s.parse::<Idn>(), Err(ParseIdnError), "{}", s);
+        }
+    }
+
+    #[test]
+    fn element_flags() {
+        let flags = [
+            ElementFlags::DATASTATE,
+            ElementFlags::NAME,
+            ElementFlags::ATTRIBUTE,
+            ElementFlags::UNIT,
+            ElementFlags::MIN,
+            ElementFlags::MAX,
+            ElementFlags::VALUE,
+            ElementFlags::DEFAULT,
+        ];
+        for (i, x) in flags.iter().enumerate() {
+            assert_eq!(x.bits(), 1 << i);
+        }
+
+        let mut x = ElementFlags::NAME | ElementFlags::VALUE;
+        assert_eq!(x.bits(), 0x42);
+        assert!(x.contains(ElementFlags::VALUE));
+        assert!(!x.contains(ElementFlags::UNIT));
+        assert!(!x.contains(ElementFlags::VALUE | ElementFlags::UNIT));
+
+        x |= ElementFlags::UNIT;
+        assert_eq!(x, ElementFlags::from_bits(0x4a));
+        assert!(x.contains(ElementFlags::VALUE | ElementFlags::UNIT));
+        assert!(ElementFlags::from_bits(0xff).contains(ElementFlags::from_bits(0)));
+    }
+}
@@ -0,0 +1,347 @@
+use crate::{Context, Slave};
+use std::fmt::{self, Write};
+
+/** Number of ports of an ESC */
+const ESC_PORTS: u8 = 4;
+
+/// Slave as a node of the [`Topology`] tree
+#[derive(Debug, PartialEq, Clone)]
+pub struct TopologyNode {
+    slave: u16,
+    name: String,
+    configured_addr: u16,
+    parent: u16,
+    parent_port: u8,
+    entry_port: u8,
+    active_ports: u8,
+    children: Vec<u16>,
+}
+
+impl TopologyNode {
+    pub const fn slave(&self) -> u16 {
+        self.slave
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub const fn configured_addr(&self) -> u16 {
+        self.configured_addr
+    }
+    /// Parent slave, 0 for the master
+    pub const fn parent(&self) -> u16 {
+        self.parent
+    }
+    /// Port of the parent the slave is connected to
+    pub const fn parent_port(&self) -> u8 {
+        self.parent_port
+    }
+    pub const fn entry_port(&self) -> u8 {
+        self.entry_port
+    }
+    pub const fn active_ports(&self) -> u8 {
+        self.active_ports
+    }
+    /// Slaves connected to the ports of this one, in processing order
+    pub fn children(&self) -> &[u16] {
+        &self.children
+    }
+
+    /// Ports with a link but no slave found behind them
+    pub fn open_ports(&self, topology: &Topology) -> Vec<u8> {
+        let ports: Vec<u8> = (0..ESC_PORTS)
+            .filter(|&port| self.active_ports & (1 << port) != 0 && port != self.entry_port)
+            .collect();
+        if ports.len() <= self.children.len() {
+            return Vec::new();
+        }
+
+        ports
+            .into_iter()
+            .filter(|&port| {
+                !self
+                    .children
+                    .iter()
+                    .filter_map(|&x| topology.node(x))
+                    .any(|x| x.parent_port == port)
+            })
+            .collect()
+    }
+}
+
+/// Physical tree of the network as found by [`Context::config_init`].
+///
+/// Ports are known only after [`Context::config_dc`], before that every slave reports port 0.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct Topology {
+    nodes: Vec<TopologyNode>,
+    roots: Vec<u16>,
+}
+
+impl Topology {
+    pub fn from_slaves(slaves: &[Slave]) -> Self {
+        let mut nodes: Vec<TopologyNode> = slaves
+            .iter()
+            .enumerate()
+            .map(|(i, x)| TopologyNode {
+                slave: i as u16 + 1,
+                name: x.name().into_owned(),
+                configured_addr: x.configured_addr(),
+                parent: x.parent(),
+                parent_port: x.parent_port(),
+                entry_port: x.entry_port(),
+                active_ports: x.active_ports(),
+                children: Vec::new(),
+            })
+            .collect();
+
+        let mut roots = Vec::new();
+        for i in 0..nodes.len() {
+            let (slave, parent) = (nodes[i].slave, nodes[i].parent as usize);
+            // Slaves are numbered in processing order, children are sorted as well
+            match nodes.get_mut(parent.wrapping_sub(1)) {
+                Some(x) if parent < slave as usize => x.children.push(slave),
+                _ => roots.push(slave),
+            }
+        }
+
+        Topology { nodes, roots }
+    }
+
+    /// Slaves connected directly to the master
+    pub fn roots(&self) -> &[u16] {
+        &self.roots
+    }
+    pub fn nodes(&self) -> &[TopologyNode] {
+        &self.nodes
+    }
+    pub fn node(&self, slave: u16) -> Option<&TopologyNode> {
+        self.nodes.get((slave as usize).wrapping_sub(1))
+    }
+
+    /// Slaves on the way from the master to the slave, the slave included
+    pub fn path(&self, slave: u16) -> Vec<u16> {
+        let mut res = Vec::new();
+        let mut x = self.node(slave);
+        while let Some(node) = x {
+            if res.contains(&node.slave) {
+                break;
+            }
+            res.push(node.slave);
+            x = self.node(node.parent);
+        }
+        res.reverse();
+        res
+    }
+
+    fn fmt_node(&self, f: &mut fmt::Formatter, slave: u16, prefix: &str) -> fmt::Result {
+        let node = match self.node(slave) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let mut branches: Vec<(u8, Option<u16>)> = node
+            .children
+            .iter()
+            .filter_map(|&x| self.node(x))
+            .map(|x| (x.parent_port, Some(x.slave)))
+            .collect();
+        branches.extend(node.open_ports(self).into_iter().map(|x| (x, None)));
+
+        for (i, (port, child)) in branches.iter().enumerate() {
+            let last = i + 1 == branches.len();
+            let (branch, indent) = if last { ("`-", "   ") } else { ("+-", "|  ") };
+            match child.and_then(|x| self.node(x)) {
+                Some(x) => {
+                    writeln!(
+                        f,
+                        "{}{} port {}: {} {} [{:04x}]",
+                        prefix, branch, port, x.slave, x.name, x.configured_addr
+                    )?;
+                    self.fmt_node(f, x.slave, &format!("{}{}", prefix, indent))?;
+                }
+                None => writeln!(f, "{}{} port {}: link without slave", prefix, branch, port)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Graphviz DOT representation of the tree
+    pub fn to_dot(&self) -> String {
+        let mut res = String::new();
+        let _ = writeln!(res, "digraph ethercat {{");
+        let _ = writeln!(res, "    master [shape=box];");
+        for x in self.nodes.iter() {
+            let _ = writeln!(
+                res,
+                "    slave{} [label=\"{} {}\\n{:04x}\"];",
+                x.slave,
+                x.slave,
+                x.name.replace('\\', "\\\\").replace('"', "\\\""),
+                x.configured_addr
+            );
+            for port in x.open_ports(self) {
+                let _ = writeln!(
+                    res,
+                    "    open{}_{} [label=\"?\", shape=point, color=red];",
+                    x.slave, port
+                );
+                let _ = writeln!(
+                    res,
+                    "    slave{} -> open{}_{} [taillabel=\"{}\", color=red];",
+                    x.slave, x.slave, port, port
+                );
+            }
+        }
+        for &x in self.roots.iter() {
+            let _ = writeln!(res, "    master -> slave{};", x);
+        }
+        for x in self.nodes.iter() {
+            for child in x.children.iter().filter_map(|&c| self.node(c)) {
+                let _ = writeln!(
+                    res,
+                    "    slave{} -> slave{} [taillabel=\"{}\", headlabel=\"{}\"];",
+                    x.slave, child.slave, child.parent_port, child.entry_port
+                );
+            }
+        }
+        let _ = writeln!(res, "}}");
+        res
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "master")?;
+        for (i, &root) in self.roots.iter().enumerate() {
+            let node = match self.node(root) {
+                Some(x) => x,
+                None => continue,
+            };
+            let (branch, indent) = if i + 1 == self.roots.len() {
+                ("`-", "   ")
+            } else {
+                ("+-", "|  ")
+            };
+            writeln!(
+                f,
+                "{} {} {} [{:04x}]",
+                branch, node.slave, node.name, node.configured_addr
+            )?;
+            self.fmt_node(f, root, indent)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Context<'a> {
+    /// Topology of the network as found by [`Context::config_init`]
+    pub fn topology(&self) -> Topology {
+        Topology::from_slaves(self.slaves())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::raw::c_char;
+
+    fn slave(name: &str, parent: u16, parent_port: u8, active_ports: u8) -> Slave {
+        let mut x = Slave::default();
+        x.0.name
+            .iter_mut()
+            .zip(name.bytes())
+            .for_each(|(x, c)| *x = c as c_char);
+        x.0.parent = parent;
+        x.0.parentport = parent_port;
+        x.0.activeports = active_ports;
+        x
+    }
+
+    /// Coupler with a terminal chain on port 3 and an extension on port 1 with an open link
+    fn topology() -> Topology {
+        let mut slaves = [
+            slave("EK1100", 0, 0, 0b1011),
+            slave("EL1008", 1, 3, 0b0011),
+            slave("EL2008", 2, 1, 0b0001),
+            slave("EK1110 \"X1\" \\2", 1, 1, 0b0011),
+        ];
+        for (i, x) in slaves.iter_mut().enumerate() {
+            x.0.configadr = 0x1001 + i as u16;
+        }
+        Topology::from_slaves(&slaves)
+    }
+
+    #[test]
+    fn tree_from_slaves() {
+        let topology = topology();
+        assert_eq!(topology.roots(), [1]);
+        assert_eq!(topology.nodes().len(), 4);
+        assert_eq!(topology.node(0), None);
+        assert_eq!(topology.node(5), None);
+
+        let coupler = topology.node(1).unwrap();
+        assert_eq!(coupler.name(), "EK1100");
+        assert_eq!(coupler.configured_addr(), 0x1001);
+        assert_eq!(coupler.children(), [2, 4]);
+        let terminal = topology.node(3).unwrap();
+        assert_eq!((terminal.parent(), terminal.parent_port()), (2, 1));
+        assert!(terminal.children().is_empty());
+
+        assert_eq!(topology.path(3), [1, 2, 3]);
+        assert_eq!(topology.path(4), [1, 4]);
+        assert!(topology.path(5).is_empty());
+    }
+
+    #[test]
+    fn parents_after_the_slave_are_roots() {
+        let topology = Topology::from_slaves(&[slave("A", 2, 0, 1), slave("B", 0, 0, 1)]);
+        assert_eq!(topology.roots(), [1, 2]);
+        assert!(topology.node(2).unwrap().children().is_empty());
+    }
+
+    #[test]
+    fn open_ports() {
+        let topology = topology();
+        let open: Vec<Vec<u8>> = topology
+            .nodes()
+            .iter()
+            .map(|x| x.open_ports(&topology))
+            .collect();
+        assert_eq!(open, [vec![], vec![], vec![], vec![1]]);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            topology().to_string(),
+            r#"master
+`- 1 EK1100 [1001]
+   +- port 3: 2 EL1008 [1002]
+   |  `- port 1: 3 EL2008 [1003]
+   `- port 1: 4 EK1110 "X1" \2 [1004]
+      `- port 1: link without slave
+"#
+        );
+    }
+
+    #[test]
+    fn dot() {
+        assert_eq!(
+            topology().to_dot(),
+            r#"digraph ethercat {
+    master [shape=box];
+    slave1 [label="1 EK1100\n1001"];
+    slave2 [label="2 EL1008\n1002"];
+    slave3 [label="3 EL2008\n1003"];
+    slave4 [label="4 EK1110 \"X1\" \\2\n1004"];
+    open4_1 [label="?", shape=point, color=red];
+    slave4 -> open4_1 [taillabel="1", color=red];
+    master -> slave1;
+    slave1 -> slave2 [taillabel="3", headlabel="0"];
+    slave1 -> slave4 [taillabel="1", headlabel="0"];
+    slave2 -> slave3 [taillabel="1", headlabel="0"];
+}
+"#
+        );
+    }
+}
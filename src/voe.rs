@@ -0,0 +1,155 @@
+use crate::{
+    error::{MailboxError, VoEError},
+    Context, Mailbox, MailboxType,
+};
+use std::{os::raw::c_int, result};
+
+/** Size of the VoE header following the mailbox header */
+const VOE_HEADER_SIZE: usize = 6;
+
+/// Vendor specific mailbox message
+#[derive(Debug, PartialEq, Clone)]
+pub struct VoEMessage {
+    vendor_id: u32,
+    vendor_type: u16,
+    data: Vec<u8>,
+}
+
+impl VoEMessage {
+    pub fn new(vendor_id: u32, vendor_type: u16, data: &[u8]) -> Self {
+        VoEMessage {
+            vendor_id,
+            vendor_type,
+            data: data.to_vec(),
+        }
+    }
+
+    pub const fn vendor_id(&self) -> u32 {
+        self.vendor_id
+    }
+    pub const fn vendor_type(&self) -> u16 {
+        self.vendor_type
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    fn to_mailbox(&self) -> Mailbox {
+        let mut payload = Vec::with_capacity(VOE_HEADER_SIZE + self.data.len());
+        payload.extend_from_slice(&self.vendor_id.to_le_bytes());
+        payload.extend_from_slice(&self.vendor_type.to_le_bytes());
+        payload.extend_from_slice(&self.data);
+        Mailbox::new(MailboxType::VoE, &payload)
+    }
+
+    fn from_mailbox(mbx: &Mailbox) -> result::Result<Self, VoEError> {
+        if mbx.header().mailbox_type() != Some(MailboxType::VoE) {
+            return Err(VoEError::UnexpectedMailbox(mbx.header().raw_type()));
+        }
+
+        let data = mbx.data();
+        if data.len() < VOE_HEADER_SIZE {
+            return Err(VoEError::TooShort(data.len()));
+        }
+
+        Ok(VoEMessage {
+            vendor_id: u32::from_le_bytes([data[0], data[1], data[2], data[3]]),
+            vendor_type: u16::from_le_bytes([data[4], data[5]]),
+            data: data[VOE_HEADER_SIZE..].to_vec(),
+        })
+    }
+}
+
+impl<'a> Context<'a> {
+    pub fn voe_send(
+        &mut self,
+        slave: u16,
+        msg: &VoEMessage,
+        timeout: c_int,
+    ) -> result::Result<(), MailboxError> {
+        self.mbx_send(slave, &msg.to_mailbox(), timeout)
+    }
+
+    pub fn voe_receive(
+        &mut self,
+        slave: u16,
+        timeout: c_int,
+    ) -> result::Result<VoEMessage, VoEError> {
+        let mbx = self
+            .mbx_receive(slave, timeout)
+            .map_err(VoEError::MailboxError)?;
+        VoEMessage::from_mailbox(&mbx)
+    }
+
+    /// Send the message and wait for the reply of the same vendor
+    pub fn voe_request(
+        &mut self,
+        slave: u16,
+        msg: &VoEMessage,
+        timeout: c_int,
+    ) -> result::Result<VoEMessage, VoEError> {
+        self.voe_send(slave, msg, timeout)
+            .map_err(VoEError::MailboxError)?;
+
+        let reply = self.voe_receive(slave, timeout)?;
+        if reply.vendor_id != msg.vendor_id {
+            return Err(VoEError::VendorMismatch(reply.vendor_id));
+        }
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tests::MockBuffers, Slave};
+
+    #[test]
+    fn message_round_trip() {
+        let msg = VoEMessage::new(0x0000_0002, 0x1234, &[0xaa, 0xbb]);
+        let mbx = msg.to_mailbox();
+        assert_eq!(mbx.header().mailbox_type(), Some(MailboxType::VoE));
+        assert_eq!(mbx.header().length(), 8);
+        assert_eq!(mbx.data(), [0x02, 0x00, 0x00, 0x00, 0x34, 0x12, 0xaa, 0xbb]);
+        assert_eq!(VoEMessage::from_mailbox(&mbx), Ok(msg));
+
+        let empty = Mailbox::new(MailboxType::VoE, &[1, 0, 0, 0, 2, 0]);
+        let msg = VoEMessage::from_mailbox(&empty).unwrap();
+        assert_eq!((msg.vendor_id(), msg.vendor_type()), (1, 2));
+        assert!(msg.data().is_empty());
+    }
+
+    #[test]
+    fn invalid_mailboxes_are_rejected() {
+        let coe = Mailbox::new(MailboxType::CoE, &[0; 8]);
+        assert_eq!(
+            VoEMessage::from_mailbox(&coe),
+            Err(VoEError::UnexpectedMailbox(0x03))
+        );
+
+        for len in 0..VOE_HEADER_SIZE {
+            let short = Mailbox::new(MailboxType::VoE, &vec![0; len]);
+            assert_eq!(
+                VoEMessage::from_mailbox(&short),
+                Err(VoEError::TooShort(len))
+            );
+        }
+    }
+
+    #[test]
+    fn messages_exceeding_the_mailbox_are_rejected() {
+        let mut slaves: Vec<Slave> = (0..2).map(|_| Default::default()).collect();
+        slaves[1].0.mbx_l = 16;
+        let mut buffers = MockBuffers::new(slaves, 1);
+        let mut c = buffers.context();
+
+        // Mailbox header, VoE header and 5 bytes of data
+        let msg = VoEMessage::new(2, 1, &[0; 5]);
+        assert_eq!(c.voe_send(1, &msg, 0), Err(MailboxError::TooLarge(17, 16)));
+        assert_eq!(
+            c.voe_request(1, &msg, 0),
+            Err(VoEError::MailboxError(MailboxError::TooLarge(17, 16)))
+        );
+        assert_eq!(c.voe_send(2, &msg, 0), Err(MailboxError::UnknownSlave(2)));
+    }
+}
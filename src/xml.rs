@@ -0,0 +1,129 @@
+use crate::error::XmlError;
+use roxmltree::Node;
+use std::result;
+
+pub(crate) fn child<'a, 'i>(node: Node<'a, 'i>, name: &str) -> Option<Node<'a, 'i>> {
+    node.children().find(|x| x.has_tag_name(name))
+}
+
+pub(crate) fn children<'a, 'i: 'a>(
+    node: Node<'a, 'i>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'i>> + 'a {
+    node.children().filter(move |x| x.has_tag_name(name))
+}
+
+/// Trimmed text of the child element
+pub(crate) fn text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)
+        .and_then(|x| x.text())
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+}
+
+/// Number in the ETG notation, hexadecimal ones are written as `#x1A00`.
+///
+/// A leading '-' is accepted for signed types only.
+pub(crate) fn parse_number<T: num::Num>(s: &str) -> Option<T> {
+    let s = s.trim();
+    let (sign, s) = match s.strip_prefix('-') {
+        Some(x) => ("-", x),
+        None => ("", s),
+    };
+    let (digits, radix) = match s.strip_prefix("#x").or_else(|| s.strip_prefix("0x")) {
+        Some(x) => (x, 16),
+        None => (s, 10),
+    };
+    // The sign is left to the parser of the type, which rejects it for unsigned ones
+    T::from_str_radix(&format!("{}{}", sign, digits), radix).ok()
+}
+
+pub(crate) fn opt_number<T: num::Num>(
+    node: Node,
+    name: &str,
+) -> result::Result<Option<T>, XmlError> {
+    match text(node, name) {
+        Some(x) => parse_number(x)
+            .map(Some)
+            .ok_or_else(|| XmlError::InvalidValue(name.to_owned(), x.to_owned())),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn number<T: num::Num>(node: Node, name: &str) -> result::Result<T, XmlError> {
+    opt_number(node, name)?.ok_or_else(|| XmlError::Missing(name.to_owned()))
+}
+
+pub(crate) fn opt_attribute<T: num::Num>(
+    node: Node,
+    name: &str,
+) -> result::Result<Option<T>, XmlError> {
+    match node.attribute(name) {
+        Some(x) => parse_number(x)
+            .map(Some)
+            .ok_or_else(|| XmlError::InvalidValue(name.to_owned(), x.to_owned())),
+        None => Ok(None),
+    }
+}
+
+pub(crate) fn parse_bool(s: &str) -> bool {
+    matches!(s.trim(), "1" | "true")
+}
+
+/// Binary data written as a string of hex digits
+pub(crate) fn hex_data(node: Node, name: &str) -> result::Result<Vec<u8>, XmlError> {
+    let s: String = match text(node, name) {
+        Some(x) => x.chars().filter(|c| !c.is_whitespace()).collect(),
+        None => return Ok(Vec::new()),
+    };
+    if s.len() % 2 != 0 || !s.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return Err(XmlError::InvalidValue(name.to_owned(), s));
+    }
+    Ok(s.as_bytes()
+        .chunks(2)
+        .map(|x| (hex_digit(x[0]) << 4) | hex_digit(x[1]))
+        .collect())
+}
+
+fn hex_digit(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        _ => c - b'A' + 10,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use roxmltree::Document;
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_number::<u16>("#x1A00"), Some(0x1a00));
+        assert_eq!(parse_number::<u16>(" 0x1a00 "), Some(0x1a00));
+        assert_eq!(parse_number::<u32>("4096"), Some(4096));
+        assert_eq!(parse_number::<i32>("-1"), Some(-1));
+        assert_eq!(parse_number::<i16>("-#x10"), Some(-16));
+        assert_eq!(parse_number::<i8>("-128"), Some(-128));
+        assert_eq!(parse_number::<u8>("-1"), None);
+        assert_eq!(parse_number::<u16>("-0"), None);
+        assert_eq!(parse_number::<u8>("256"), None);
+        assert_eq!(parse_number::<i16>("--1"), None);
+        assert_eq!(parse_number::<u16>("#xZZ"), None);
+    }
+
+    #[test]
+    fn hex_strings() {
+        let doc = Document::parse(
+            "<Cmd><Data>0a 1B\nff</Data><Odd>abc</Odd><Wide>a\u{e9}0</Wide><Plus>+1</Plus></Cmd>",
+        )
+        .unwrap();
+        let node = doc.root_element();
+        assert_eq!(hex_data(node, "Data"), Ok(vec![0x0a, 0x1b, 0xff]));
+        assert_eq!(hex_data(node, "Missing"), Ok(vec![]));
+        assert!(hex_data(node, "Odd").is_err());
+        assert!(hex_data(node, "Wide").is_err());
+        assert!(hex_data(node, "Plus").is_err());
+    }
+}